@@ -1,4 +1,5 @@
 use std::fmt::{self, Display, Formatter};
+use std::net::SocketAddr;
 use std::path::PathBuf;
 
 use clap::{ArgAction, Parser, Subcommand, ValueEnum};
@@ -21,55 +22,107 @@ pub struct CliArguments {
 #[derive(Debug, Clone, Subcommand)]
 #[command()]
 pub enum Command {
-    /// Compiles an input file into a PDF or PNG file
+    /// Compiles an input file into a PDF, PNG, or SVG file
     #[command(visible_alias = "c")]
     Compile(CompileCommand),
 
     /// Watches an input file and recompiles on changes
     #[command(visible_alias = "w")]
-    Watch(CompileCommand),
+    Watch(WatchCommand),
+
+    /// Processes an input file to extract provided metadata
+    Query(QueryCommand),
 
     /// Lists all discovered fonts in system and custom font paths
     Fonts(FontsCommand),
+
+    /// Runs a language server that communicates over stdin and stdout
+    Lsp(LspCommand),
+
+    /// Formats Typst source files in place
+    Fmt(FmtCommand),
 }
 
 /// Compiles the input file into a PDF file
 #[derive(Debug, Clone, Parser)]
 pub struct CompileCommand {
-    /// Path to input Typst file
-    pub input: PathBuf,
+    /// Shared arguments.
+    #[clap(flatten)]
+    pub common: SharedArgs,
 
-    /// Path to output PDF file or PNG file(s)
+    /// Path to output PDF file, PNG/JPEG/WebP/SVG file(s), HTML, Markdown or
+    /// text file
     pub output: Option<PathBuf>,
 
-    /// Configures the project root
-    #[clap(long = "root", env = "TYPST_ROOT", value_name = "DIR")]
-    pub root: Option<PathBuf>,
-
-    /// Adds additional directories to search for fonts
-    #[clap(
-        long = "font-path",
-        env = "TYPST_FONT_PATHS",
-        value_name = "DIR",
-        action = ArgAction::Append,
-    )]
-    pub font_paths: Vec<PathBuf>,
+    /// Which pages to export, e.g. `1,3-5` or `4-`. All pages by default
+    #[arg(long = "pages", value_delimiter = ',', value_parser = parse_page_range)]
+    pub pages: Option<Vec<PageRange>>,
 
     /// Opens the output file using the default viewer after compilation
     #[arg(long = "open")]
     pub open: Option<Option<String>>,
 
-    /// The PPI (pixels per inch) to use for PNG export
+    /// The PPI (pixels per inch) to use for PNG, JPEG, and WebP export
     #[arg(long = "ppi", default_value_t = 144.0)]
     pub ppi: f32,
 
-    /// In which format to emit diagnostics
-    #[clap(
-        long,
-        default_value_t = DiagnosticFormat::Human,
-        value_parser = clap::value_parser!(DiagnosticFormat)
+    /// The quality (from 1 to 100) to use for JPEG and WebP export
+    #[arg(
+        long = "quality",
+        default_value_t = 90,
+        value_parser = clap::value_parser!(u8).range(1..=100),
     )]
-    pub diagnostic_format: DiagnosticFormat,
+    pub quality: u8,
+
+    /// Renders PNG and WebP images with a transparent instead of a white
+    /// background
+    #[arg(long = "transparent")]
+    pub transparent: bool,
+
+    /// The PDF standard the output should conform to
+    #[arg(long = "pdf-standard", value_enum, default_value_t = PdfStandard::V1_7)]
+    pub pdf_standard: PdfStandard,
+
+    /// Makes headings linkable from other PDF files by their text, like
+    /// labelled elements are by their label
+    #[arg(long = "pdf-heading-destinations")]
+    pub pdf_heading_destinations: bool,
+
+    /// An ICC profile of the printing device to embed as the PDF's output
+    /// intent. Requires the PDF/A or PDF/X standard
+    #[arg(long = "pdf-output-profile", value_name = "ICC_FILE")]
+    pub pdf_output_profile: Option<PathBuf>,
+
+    /// The name of the printing condition the output profile describes,
+    /// like `FOGRA39`. Defaults to the profile's file name
+    #[arg(long = "pdf-output-condition", requires = "pdf_output_profile")]
+    pub pdf_output_condition: Option<String>,
+
+    /// Downsamples raster images in PDF output that exceed this resolution
+    /// (in dots per inch) at the size they are placed at
+    #[arg(
+        long = "pdf-image-dpi",
+        value_name = "DPI",
+        value_parser = clap::value_parser!(u32).range(1..),
+    )]
+    pub pdf_image_dpi: Option<u32>,
+
+    /// The quality (from 1 to 100) at which JPEG images in PDF output are
+    /// re-encoded when they can't be embedded as they are
+    #[arg(
+        long = "pdf-jpeg-quality",
+        value_parser = clap::value_parser!(u8).range(1..=100),
+    )]
+    pub pdf_jpeg_quality: Option<u8>,
+
+    /// Writes the files that the output depends on to a dependency file for
+    /// build systems like Make and Ninja
+    #[arg(long = "deps", value_name = "PATH")]
+    pub deps: Option<PathBuf>,
+
+    /// The format of the dependency file
+    #[arg(long = "deps-format", value_enum, default_value_t = DepsFormat::Make)]
+    pub deps_format: DepsFormat,
 
     /// Produces a flamegraph of the compilation process
     #[arg(long = "flamegraph", value_name = "OUTPUT_SVG")]
@@ -81,8 +134,171 @@ impl CompileCommand {
     pub fn output(&self) -> PathBuf {
         self.output
             .clone()
-            .unwrap_or_else(|| self.input.with_extension("pdf"))
+            .unwrap_or_else(|| self.common.input.with_extension("pdf"))
+    }
+
+    /// The zero-based indices of the pages to export out of the given number
+    /// of pages, in ascending order.
+    pub fn exported_pages(&self, total: usize) -> Vec<usize> {
+        (0..total)
+            .filter(|&i| match &self.pages {
+                Some(ranges) => ranges.iter().any(|range| range.contains(i + 1)),
+                None => true,
+            })
+            .collect()
+    }
+}
+
+/// Watches an input file and recompiles on changes
+#[derive(Debug, Clone, Parser)]
+pub struct WatchCommand {
+    /// Arguments for compilation.
+    #[clap(flatten)]
+    pub compile: CompileCommand,
+
+    /// Serves a live preview of the pages at the given address, at
+    /// `127.0.0.1:3000` by default
+    #[arg(
+        long = "serve",
+        value_name = "ADDR",
+        num_args = 0..=1,
+        default_missing_value = "127.0.0.1:3000",
+    )]
+    pub serve: Option<SocketAddr>,
+}
+
+/// An inclusive range of one-based page numbers. Missing bounds extend to the
+/// first or last page.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct PageRange {
+    pub start: Option<usize>,
+    pub end: Option<usize>,
+}
+
+impl PageRange {
+    /// Whether the range contains the page with the given number.
+    pub fn contains(&self, page: usize) -> bool {
+        self.start.map_or(true, |start| start <= page)
+            && self.end.map_or(true, |end| page <= end)
+    }
+}
+
+/// Parses a page range like `3`, `3-5`, `3-` or `-5`.
+fn parse_page_range(raw: &str) -> Result<PageRange, String> {
+    let number = |s: &str| -> Result<Option<usize>, String> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Ok(None);
+        }
+        match s.parse::<usize>() {
+            Ok(0) => Err("page numbers start at 1".into()),
+            Ok(n) => Ok(Some(n)),
+            Err(_) => Err(format!("invalid page number: {s}")),
+        }
+    };
+
+    let range = match raw.split_once('-') {
+        Some((start, end)) => PageRange { start: number(start)?, end: number(end)? },
+        None => {
+            let page = number(raw)?.ok_or("page range must not be empty")?;
+            PageRange { start: Some(page), end: Some(page) }
+        }
+    };
+
+    if let (Some(start), Some(end)) = (range.start, range.end) {
+        if start > end {
+            return Err("page range must not end before it starts".into());
+        }
+    }
+
+    Ok(range)
+}
+
+/// Processes an input file to extract provided metadata
+#[derive(Debug, Clone, Parser)]
+pub struct QueryCommand {
+    /// Shared arguments.
+    #[clap(flatten)]
+    pub common: SharedArgs,
+
+    /// Defines which elements to retrieve, e.g. `heading`, `<intro>` or
+    /// `figure.where(kind: table)`
+    pub selector: String,
+
+    /// Extracts just one field from all retrieved elements
+    #[clap(long = "field")]
+    pub field: Option<String>,
+
+    /// Expects and retrieves exactly one element
+    #[clap(long = "one", default_value = "false")]
+    pub one: bool,
+
+    /// The format to serialize in
+    #[clap(long = "format", default_value = "json")]
+    pub format: SerializationFormat,
+}
+
+/// Arguments that are shared by commands that compile a document.
+#[derive(Debug, Clone, clap::Args)]
+pub struct SharedArgs {
+    /// Path to input Typst file
+    pub input: PathBuf,
+
+    /// Arguments for the world the document is compiled in.
+    #[clap(flatten)]
+    pub world: WorldArgs,
+
+    /// In which format to emit diagnostics
+    #[clap(
+        long,
+        default_value_t = DiagnosticFormat::Human,
+        value_parser = clap::value_parser!(DiagnosticFormat)
+    )]
+    pub diagnostic_format: DiagnosticFormat,
+
+    /// Fails the compilation if there are warnings, reporting them as errors
+    #[clap(long = "deny-warnings")]
+    pub deny_warnings: bool,
+}
+
+/// Arguments that set up the world in which documents are compiled.
+#[derive(Debug, Clone, clap::Args)]
+pub struct WorldArgs {
+    /// Configures the project root. Defaults to the directory of the input
+    /// file or, for the language server, the workspace folder opened in the
+    /// editor
+    #[clap(long = "root", env = "TYPST_ROOT", value_name = "DIR")]
+    pub root: Option<PathBuf>,
+
+    /// Adds additional directories to search for fonts
+    #[clap(
+        long = "font-path",
+        env = "TYPST_FONT_PATHS",
+        value_name = "DIR",
+        action = ArgAction::Append,
+    )]
+    pub font_paths: Vec<PathBuf>,
+
+    /// Adds a string key-value pair, visible to the document through
+    /// `sys.inputs`
+    #[clap(
+        long = "input",
+        value_name = "key=value",
+        action = ArgAction::Append,
+        value_parser = parse_input_pair,
+    )]
+    pub inputs: Vec<(String, String)>,
+}
+
+/// Parses a key-value pair of the form `key=value`.
+fn parse_input_pair(raw: &str) -> Result<(String, String), String> {
+    let (key, value) = raw
+        .split_once('=')
+        .ok_or("input must be a key and a value separated by an equal sign")?;
+    if key.trim().is_empty() {
+        return Err("input key must not be empty".into());
     }
+    Ok((key.trim().to_owned(), value.to_owned()))
 }
 
 /// Lists all discovered fonts in system and custom font paths
@@ -102,11 +318,47 @@ pub struct FontsCommand {
     pub variants: bool,
 }
 
+/// Runs a language server that communicates over stdin and stdout
+#[derive(Debug, Clone, Parser)]
+pub struct LspCommand {
+    /// Path to the project's main Typst file, which is compiled to check the
+    /// open files. Defaults to the open file that was edited last
+    #[clap(long = "main", value_name = "FILE")]
+    pub main: Option<PathBuf>,
+
+    /// Arguments for the world the open files are compiled in.
+    #[clap(flatten)]
+    pub world: WorldArgs,
+}
+
+/// Formats Typst source files in place
+#[derive(Debug, Clone, Parser)]
+pub struct FmtCommand {
+    /// Files or directories to format. Directories are searched recursively
+    /// for `.typ` files. Defaults to the current directory
+    pub paths: Vec<PathBuf>,
+
+    /// Lists the files that aren't formatted instead of changing them and
+    /// fails if there are any
+    #[arg(long = "check")]
+    pub check: bool,
+
+    /// The maximum line width that code is laid out for
+    #[arg(long = "width", default_value_t = 80)]
+    pub width: usize,
+}
+
 /// Which format to use for diagnostics.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, ValueEnum)]
 pub enum DiagnosticFormat {
+    /// Annotated source snippets, colored if printed to a terminal
     Human,
+    /// One line per diagnostic
     Short,
+    /// One JSON object per diagnostic and line
+    Json,
+    /// A SARIF log for code scanning tools
+    Sarif,
 }
 
 impl Display for DiagnosticFormat {
@@ -117,3 +369,60 @@ impl Display for DiagnosticFormat {
             .fmt(f)
     }
 }
+
+/// A PDF standard that the output can conform to.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, ValueEnum)]
+pub enum PdfStandard {
+    /// Plain PDF 1.7
+    #[value(name = "1.7")]
+    V1_7,
+    /// PDF/A-2b for long-term archival
+    #[value(name = "a-2b")]
+    A2b,
+    /// PDF/X-4 for print production, requires an output profile
+    #[value(name = "x-4")]
+    X4,
+}
+
+impl Display for PdfStandard {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.to_possible_value()
+            .expect("no values are skipped")
+            .get_name()
+            .fmt(f)
+    }
+}
+
+/// Which format to write dependency files in.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, ValueEnum)]
+pub enum DepsFormat {
+    /// A Makefile rule, as understood by Make and Ninja
+    Make,
+    /// A JSON object with the outputs and the inputs they depend on
+    Json,
+}
+
+impl Display for DepsFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.to_possible_value()
+            .expect("no values are skipped")
+            .get_name()
+            .fmt(f)
+    }
+}
+
+/// Which format to use for serialized output.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, ValueEnum)]
+pub enum SerializationFormat {
+    Json,
+    Yaml,
+}
+
+impl Display for SerializationFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.to_possible_value()
+            .expect("no values are skipped")
+            .get_name()
+            .fmt(f)
+    }
+}
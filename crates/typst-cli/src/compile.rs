@@ -1,18 +1,31 @@
-use std::fs;
-use std::path::Path;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
 
 use codespan_reporting::diagnostic::{Diagnostic, Label};
 use codespan_reporting::term::{self, termcolor};
+use image::codecs::jpeg::JpegEncoder;
+#[cfg(feature = "webp")]
+use image::codecs::webp::{WebPEncoder, WebPQuality};
+#[cfg(feature = "webp")]
+use image::{ColorType, Rgba, RgbaImage};
+use image::{Rgb, RgbImage};
+use serde_json::{json, Value};
 use termcolor::{ColorChoice, StandardStream};
-use typst::diag::{bail, Severity, SourceDiagnostic, StrResult};
+use tiny_skia::Pixmap;
+use typst::diag::{
+    bail, Severity, SourceDiagnostic, SourceResult, StrResult, Tracepoint,
+};
 use typst::doc::Document;
 use typst::eval::{eco_format, Tracer};
-use typst::geom::Color;
-use typst::syntax::{FileId, Source};
+use typst::export::{OutputProfile, PdfOptions, PdfStandard};
+use typst::geom::{Color, RgbaColor};
+use typst::syntax::{FileId, Source, Span};
 use typst::World;
 
-use crate::args::{CompileCommand, DiagnosticFormat};
-use crate::watch::Status;
+use crate::args::{self, CompileCommand, DepsFormat, DiagnosticFormat};
+use crate::lsp::{encode_uri_path, path_to_uri};
+use crate::watch::{Status, Watching};
 use crate::world::SystemWorld;
 use crate::{color_stream, set_failed};
 
@@ -21,25 +34,28 @@ type CodespanError = codespan_reporting::files::Error;
 
 /// Execute a compilation command.
 pub fn compile(mut command: CompileCommand) -> StrResult<()> {
-    let mut world = SystemWorld::new(&command)?;
-    compile_once(&mut world, &mut command, false)?;
+    let mut world = SystemWorld::new(&command.common)?;
+    compile_once(&mut world, &mut command, None)?;
     Ok(())
 }
 
 /// Compile a single time.
 ///
-/// Returns whether it compiled without errors.
+/// When watching, the status is printed along with the given watching state.
+///
+/// Returns the laid out document if there is one, even if exporting it
+/// failed.
 #[tracing::instrument(skip_all)]
 pub fn compile_once(
     world: &mut SystemWorld,
     command: &mut CompileCommand,
-    watching: bool,
-) -> StrResult<()> {
+    watching: Option<Watching>,
+) -> StrResult<Option<Document>> {
     tracing::info!("Starting compilation");
 
     let start = std::time::Instant::now();
-    if watching {
-        Status::Compiling.print(command).unwrap();
+    if let Some(watching) = watching {
+        Status::Compiling.print(command, watching).unwrap();
     }
 
     // Reset everything and ensure that the main file is still present.
@@ -48,28 +64,61 @@ pub fn compile_once(
 
     let mut tracer = Tracer::default();
 
-    let result = typst::compile(world, &mut tracer);
+    // HTML and Markdown are generated from the document's content instead of
+    // its pages, so they have their own compilation entry points.
+    let output = command.output();
+    if is_html(&output) && command.pages.is_some() {
+        bail!("cannot export selected pages to HTML, which has no pages");
+    } else if is_markdown(&output) && command.pages.is_some() {
+        bail!("cannot export selected pages to Markdown, which has no pages");
+    }
+
+    let result = if is_html(&output) {
+        typst::compile_html(world, &mut tracer).map(Output::Html)
+    } else if is_markdown(&output) {
+        typst::compile_markdown(world, &mut tracer).map(Output::Markdown)
+    } else {
+        typst::compile(world, &mut tracer).map(Output::Document)
+    };
     let duration = start.elapsed();
 
-    let warnings = tracer.warnings();
+    let (result, warnings) =
+        deny_warnings(result, tracer.warnings(), command.common.deny_warnings);
+
+    // Export the PDF / PNG / SVG / HTML / Markdown. Exporting can fail with
+    // diagnostics, for instance, when the document doesn't conform to the
+    // requested PDF standard.
+    let mut laid_out = None;
+    let result = match result {
+        Ok(Output::Document(document)) => {
+            let result = export(&document, command)?;
+            laid_out = Some(document);
+            result
+        }
+        Ok(Output::Html(html)) => export_html(&html, command).map(Ok)?,
+        Ok(Output::Markdown(markdown)) => export_markdown(&markdown, command).map(Ok)?,
+        Err(errors) => Err(errors),
+    };
 
     match result {
-        // Export the PDF / PNG.
-        Ok(document) => {
-            export(&document, command)?;
-
+        Ok(()) => {
             tracing::info!("Compilation succeeded in {duration:?}");
-            if watching {
-                if warnings.is_empty() {
-                    Status::Success(duration).print(command).unwrap();
+            if let Some(watching) = watching {
+                let status = if warnings.is_empty() {
+                    Status::Success(duration)
                 } else {
-                    Status::PartialSuccess(duration).print(command).unwrap();
-                }
+                    Status::PartialSuccess(duration)
+                };
+                status.print(command, watching).unwrap();
             }
 
-            print_diagnostics(world, &[], &warnings, command.diagnostic_format)
+            print_diagnostics(world, &[], &warnings, command.common.diagnostic_format)
                 .map_err(|_| "failed to print diagnostics")?;
 
+            if let Some(path) = &command.deps {
+                write_deps(world, command, laid_out.as_ref(), path)?;
+            }
+
             if let Some(open) = command.open.take() {
                 open_file(open.as_deref(), &command.output())?;
             }
@@ -80,64 +129,362 @@ pub fn compile_once(
             set_failed();
             tracing::info!("Compilation failed");
 
-            if watching {
-                Status::Error.print(command).unwrap();
+            if let Some(watching) = watching {
+                Status::Error.print(command, watching).unwrap();
             }
 
-            print_diagnostics(world, &errors, &warnings, command.diagnostic_format)
-                .map_err(|_| "failed to print diagnostics")?;
+            print_diagnostics(
+                world,
+                &errors,
+                &warnings,
+                command.common.diagnostic_format,
+            )
+            .map_err(|_| "failed to print diagnostics")?;
         }
     }
 
-    Ok(())
+    Ok(laid_out)
+}
+
+/// The result of a compilation.
+enum Output {
+    /// A laid out document.
+    Document(Document),
+    /// A standalone HTML page.
+    Html(String),
+    /// A Markdown document.
+    Markdown(String),
+}
+
+/// Whether the output path calls for HTML export.
+fn is_html(output: &Path) -> bool {
+    output.extension().map_or(false, |ext| {
+        ext.eq_ignore_ascii_case("html") || ext.eq_ignore_ascii_case("htm")
+    })
+}
+
+/// Whether the output path calls for Markdown export.
+fn is_markdown(output: &Path) -> bool {
+    output.extension().map_or(false, |ext| {
+        ext.eq_ignore_ascii_case("md") || ext.eq_ignore_ascii_case("markdown")
+    })
 }
 
 /// Export into the target format.
-fn export(document: &Document, command: &CompileCommand) -> StrResult<()> {
+///
+/// The outer result signals a failure to write the output, the inner one
+/// errors in the document that prevented the export.
+fn export(document: &Document, command: &CompileCommand) -> StrResult<SourceResult<()>> {
+    let total = document.pages.len();
+    if command.pages.is_some() && command.exported_pages(total).is_empty() {
+        bail!("page selection contains none of the document's {total} pages");
+    }
+
     match command.output().extension() {
-        Some(ext) if ext.eq_ignore_ascii_case("png") => export_png(document, command),
+        Some(ext) if ext.eq_ignore_ascii_case("png") => {
+            export_image(document, command, ImageExportFormat::Png).map(Ok)
+        }
+        Some(ext)
+            if ext.eq_ignore_ascii_case("jpg") || ext.eq_ignore_ascii_case("jpeg") =>
+        {
+            export_image(document, command, ImageExportFormat::Jpeg).map(Ok)
+        }
+        Some(ext) if ext.eq_ignore_ascii_case("webp") => {
+            export_image(document, command, ImageExportFormat::Webp).map(Ok)
+        }
+        Some(ext) if ext.eq_ignore_ascii_case("svg") => {
+            export_image(document, command, ImageExportFormat::Svg).map(Ok)
+        }
+        Some(ext) if ext.eq_ignore_ascii_case("txt") => {
+            export_text(document, command).map(Ok)
+        }
         _ => export_pdf(document, command),
     }
 }
 
 /// Export to a PDF.
-fn export_pdf(document: &Document, command: &CompileCommand) -> StrResult<()> {
+fn export_pdf(
+    document: &Document,
+    command: &CompileCommand,
+) -> StrResult<SourceResult<()>> {
+    if command.pdf_standard == args::PdfStandard::V1_7
+        && command.pdf_output_profile.is_some()
+    {
+        bail!("an output profile requires the `a-2b` or `x-4` PDF standard");
+    }
+
+    let options = PdfOptions {
+        standard: match command.pdf_standard {
+            args::PdfStandard::V1_7 => PdfStandard::V1_7,
+            args::PdfStandard::A2b => PdfStandard::A2b,
+            args::PdfStandard::X4 => PdfStandard::X4,
+        },
+        pages: command
+            .pages
+            .is_some()
+            .then(|| command.exported_pages(document.pages.len())),
+        heading_destinations: command.pdf_heading_destinations,
+        output_profile: command
+            .pdf_output_profile
+            .as_deref()
+            .map(|path| read_output_profile(path, command))
+            .transpose()?,
+        image_dpi: command.pdf_image_dpi,
+        jpeg_quality: command.pdf_jpeg_quality,
+    };
+
+    let buffer = match typst::export::pdf_with_options(document, &options) {
+        Ok(buffer) => buffer,
+        Err(errors) => return Ok(Err(errors)),
+    };
+
     let output = command.output();
-    let buffer = typst::export::pdf(document);
     fs::write(output, buffer).map_err(|_| "failed to write PDF file")?;
+    Ok(Ok(()))
+}
+
+/// Read the ICC profile of the PDF's output intent.
+fn read_output_profile(
+    path: &Path,
+    command: &CompileCommand,
+) -> StrResult<OutputProfile> {
+    let data = fs::read(path).map_err(|_| "failed to read output profile")?;
+    let condition = match &command.pdf_output_condition {
+        Some(condition) => condition.as_str().into(),
+        None => path.file_stem().unwrap_or_default().to_string_lossy().as_ref().into(),
+    };
+
+    OutputProfile::new(data.into(), condition)
+}
+
+/// Export to an HTML file.
+fn export_html(html: &str, command: &CompileCommand) -> StrResult<()> {
+    let output = command.output();
+    fs::write(output, html).map_err(|_| "failed to write HTML file")?;
+    Ok(())
+}
+
+/// Export to a Markdown file.
+fn export_markdown(markdown: &str, command: &CompileCommand) -> StrResult<()> {
+    let output = command.output();
+    fs::write(output, markdown).map_err(|_| "failed to write Markdown file")?;
     Ok(())
 }
 
-/// Export to one or multiple PNGs.
-fn export_png(document: &Document, command: &CompileCommand) -> StrResult<()> {
+/// Export the text of the selected pages to a plain text file.
+fn export_text(document: &Document, command: &CompileCommand) -> StrResult<()> {
+    let output = command.output();
+    let mut selected = document.clone();
+    selected.pages = command
+        .exported_pages(document.pages.len())
+        .into_iter()
+        .map(|i| document.pages[i].clone())
+        .collect();
+    let text = typst::export::text(&selected);
+    fs::write(output, text).map_err(|_| "failed to write text file")?;
+    Ok(())
+}
+
+/// An image format to export in.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum ImageExportFormat {
+    Png,
+    Jpeg,
+    Webp,
+    Svg,
+}
+
+/// Export to one or multiple PNGs, JPEGs, WebPs or SVGs.
+fn export_image(
+    document: &Document,
+    command: &CompileCommand,
+    fmt: ImageExportFormat,
+) -> StrResult<()> {
+    if command.transparent && fmt == ImageExportFormat::Jpeg {
+        bail!("JPEG images cannot have a transparent background");
+    }
+
     // Determine whether we have a `{n}` numbering.
     let output = command.output();
     let string = output.to_str().unwrap_or_default();
     let numbered = string.contains("{n}");
-    if !numbered && document.pages.len() > 1 {
-        bail!("cannot export multiple PNGs without `{{n}}` in output path");
+    let pages = command.exported_pages(document.pages.len());
+    if !numbered && pages.len() > 1 {
+        bail!("cannot export multiple images without `{{n}}` in output path");
     }
 
-    // Find a number width that accommodates all pages. For instance, the
-    // first page should be numbered "001" if there are between 100 and
-    // 999 pages.
-    let width = 1 + document.pages.len().checked_ilog10().unwrap_or(0) as usize;
     let mut storage;
 
-    for (i, frame) in document.pages.iter().enumerate() {
-        let pixmap = typst::export::render(frame, command.ppi / 72.0, Color::WHITE);
+    let fill = if command.transparent {
+        Color::Rgba(RgbaColor::new(0, 0, 0, 0))
+    } else {
+        Color::WHITE
+    };
+
+    for i in pages {
+        let frame = &document.pages[i];
         let path = if numbered {
-            storage = string.replace("{n}", &format!("{:0width$}", i + 1));
+            storage = numbered_path(string, i, document.pages.len());
             Path::new(&storage)
         } else {
             output.as_path()
         };
-        pixmap.save_png(path).map_err(|_| "failed to write PNG file")?;
+        match fmt {
+            ImageExportFormat::Png => {
+                let pixmap =
+                    typst::export::render(frame, command.ppi / 72.0, fill.clone());
+                pixmap.save_png(path).map_err(|_| "failed to write PNG file")?;
+            }
+            ImageExportFormat::Jpeg => {
+                let pixmap =
+                    typst::export::render(frame, command.ppi / 72.0, fill.clone());
+                let image = to_rgb_image(&pixmap);
+                let file = File::create(path).map_err(|_| "failed to write JPEG file")?;
+                JpegEncoder::new_with_quality(BufWriter::new(file), command.quality)
+                    .encode_image(&image)
+                    .map_err(|_| "failed to write JPEG file")?;
+            }
+            #[cfg(feature = "webp")]
+            ImageExportFormat::Webp => {
+                let pixmap =
+                    typst::export::render(frame, command.ppi / 72.0, fill.clone());
+                let image = to_rgba_image(&pixmap);
+                let file = File::create(path).map_err(|_| "failed to write WebP file")?;
+                WebPEncoder::new_with_quality(
+                    BufWriter::new(file),
+                    WebPQuality::lossy(command.quality),
+                )
+                .encode(image.as_raw(), image.width(), image.height(), ColorType::Rgba8)
+                .map_err(|_| "failed to write WebP file")?;
+            }
+            #[cfg(not(feature = "webp"))]
+            ImageExportFormat::Webp => {
+                bail!("WebP export requires building Typst with the `webp` feature")
+            }
+            ImageExportFormat::Svg => {
+                let svg = typst::export::svg(frame);
+                fs::write(path, svg).map_err(|_| "failed to write SVG file")?;
+            }
+        }
     }
 
     Ok(())
 }
 
+/// The path of the page with the given index for an output path with a `{n}`
+/// numbering.
+fn numbered_path(template: &str, i: usize, total: usize) -> String {
+    // Find a number width that accommodates all pages. For instance, the
+    // first page should be numbered "001" if there are between 100 and
+    // 999 pages.
+    let width = 1 + total.checked_ilog10().unwrap_or(0) as usize;
+    template.replace("{n}", &format!("{:0width$}", i + 1))
+}
+
+/// Write the files that the output depends on to a dependency file.
+///
+/// These are all files that were read during compilation, that is sources,
+/// images, data and bibliography files, including those of packages.
+fn write_deps(
+    world: &mut SystemWorld,
+    command: &CompileCommand,
+    document: Option<&Document>,
+    path: &Path,
+) -> StrResult<()> {
+    // Numbered image exports produce one file per exported page.
+    let output = command.output();
+    let string = output.to_string_lossy();
+    let outputs: Vec<PathBuf> = match document {
+        Some(document) if string.contains("{n}") => command
+            .exported_pages(document.pages.len())
+            .into_iter()
+            .map(|i| numbered_path(&string, i, document.pages.len()).into())
+            .collect(),
+        _ => vec![output.clone()],
+    };
+
+    let mut inputs: Vec<PathBuf> = world
+        .dependencies()
+        .filter(|path| path.is_file())
+        .map(Path::to_path_buf)
+        .collect();
+    inputs.extend(command.pdf_output_profile.clone());
+    inputs.sort();
+    inputs.dedup();
+
+    let deps = match command.deps_format {
+        DepsFormat::Make => {
+            let escaped = |paths: &[PathBuf]| -> Vec<String> {
+                paths
+                    .iter()
+                    .map(|path| escape_make(&path.to_string_lossy()))
+                    .collect()
+            };
+
+            let inputs = escaped(&inputs);
+            let mut deps =
+                format!("{}: {}\n", escaped(&outputs).join(" "), inputs.join(" "));
+
+            // Like with `-MP` in C compilers, each input gets a rule without
+            // prerequisites so that make doesn't fail when an input is
+            // deleted.
+            for input in inputs {
+                deps.push_str(&format!("\n{input}:\n"));
+            }
+
+            deps
+        }
+        DepsFormat::Json => {
+            let strings = |paths: &[PathBuf]| -> Vec<String> {
+                paths.iter().map(|path| path.to_string_lossy().into()).collect()
+            };
+            let json =
+                json!({ "outputs": strings(&outputs), "inputs": strings(&inputs) });
+            format!("{json:#}\n")
+        }
+    };
+
+    fs::write(path, deps).map_err(|_| "failed to write dependency file")?;
+    Ok(())
+}
+
+/// Escape a path for use in a Makefile rule.
+fn escape_make(path: &str) -> String {
+    let mut escaped = String::with_capacity(path.len());
+    for c in path.chars() {
+        match c {
+            ' ' | '#' | ':' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '$' => escaped.push_str("$$"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Convert a rendered page into an RGB image, dropping the alpha channel.
+fn to_rgb_image(pixmap: &Pixmap) -> RgbImage {
+    let mut image = RgbImage::new(pixmap.width(), pixmap.height());
+    for (pixel, &color) in image.pixels_mut().zip(pixmap.pixels()) {
+        let color = color.demultiply();
+        *pixel = Rgb([color.red(), color.green(), color.blue()]);
+    }
+    image
+}
+
+/// Convert a rendered page into an RGBA image with straight alpha.
+#[cfg(feature = "webp")]
+fn to_rgba_image(pixmap: &Pixmap) -> RgbaImage {
+    let mut image = RgbaImage::new(pixmap.width(), pixmap.height());
+    for (pixel, &color) in image.pixels_mut().zip(pixmap.pixels()) {
+        let color = color.demultiply();
+        *pixel = Rgba([color.red(), color.green(), color.blue(), color.alpha()]);
+    }
+    image
+}
+
 /// Opens the given file using:
 /// - The default file viewer if `open` is `None`.
 /// - The given viewer provided by `open` if it is `Some`.
@@ -152,7 +499,7 @@ fn open_file(open: Option<&str>, path: &Path) -> StrResult<()> {
 }
 
 /// Print diagnostic messages to the terminal.
-fn print_diagnostics(
+pub fn print_diagnostics(
     world: &SystemWorld,
     errors: &[SourceDiagnostic],
     warnings: &[SourceDiagnostic],
@@ -161,6 +508,8 @@ fn print_diagnostics(
     let mut w = match diagnostic_format {
         DiagnosticFormat::Human => color_stream(),
         DiagnosticFormat::Short => StandardStream::stderr(ColorChoice::Never),
+        DiagnosticFormat::Json => return print_json(world, errors, warnings),
+        DiagnosticFormat::Sarif => return print_sarif(world, errors, warnings),
     };
 
     let mut config = term::Config { tab_width: 2, ..Default::default() };
@@ -181,19 +530,16 @@ fn print_diagnostics(
                 .map(|e| (eco_format!("hint: {e}")).into())
                 .collect(),
         )
-        .with_labels(vec![Label::primary(
-            diagnostic.span.id(),
-            world.range(diagnostic.span),
-        )]);
+        .with_labels(label(world, diagnostic.span).into_iter().collect());
 
         term::emit(&mut w, &config, world, &diag)?;
 
         // Stacktrace-like helper diagnostics.
         for point in &diagnostic.trace {
             let message = point.v.to_string();
-            let help = Diagnostic::help().with_message(message).with_labels(vec![
-                Label::primary(point.span.id(), world.range(point.span)),
-            ]);
+            let help = Diagnostic::help()
+                .with_message(message)
+                .with_labels(label(world, point.span).into_iter().collect());
 
             term::emit(&mut w, &config, world, &help)?;
         }
@@ -202,6 +548,243 @@ fn print_diagnostics(
     Ok(())
 }
 
+/// Print diagnostics as JSON, one object per line.
+fn print_json(
+    world: &SystemWorld,
+    errors: &[SourceDiagnostic],
+    warnings: &[SourceDiagnostic],
+) -> Result<(), codespan_reporting::files::Error> {
+    let mut w = io::stderr().lock();
+    for diagnostic in warnings.iter().chain(errors.iter()) {
+        let (file, range) = location(world, diagnostic.span);
+        let trace: Vec<_> = diagnostic
+            .trace
+            .iter()
+            .map(|point| {
+                let (file, range) = location(world, point.span);
+                let (kind, name) = match &point.v {
+                    Tracepoint::Call(name) => ("call", name.clone()),
+                    Tracepoint::Show(name) => ("show", Some(name.clone())),
+                    Tracepoint::Import => ("import", None),
+                };
+                json!({
+                    "kind": kind,
+                    "name": name,
+                    "message": point.v.to_string(),
+                    "file": file,
+                    "range": range,
+                })
+            })
+            .collect();
+
+        let object = json!({
+            "severity": severity(diagnostic.severity),
+            "message": diagnostic.message,
+            "hints": diagnostic.hints,
+            "file": file,
+            "range": range,
+            "trace": trace,
+        });
+
+        writeln!(w, "{object}")?;
+    }
+
+    Ok(())
+}
+
+/// Print diagnostics as a SARIF log for code scanning tools.
+///
+/// Local files are referenced relative to the project root and files from
+/// packages by their package specification.
+fn print_sarif(
+    world: &SystemWorld,
+    errors: &[SourceDiagnostic],
+    warnings: &[SourceDiagnostic],
+) -> Result<(), codespan_reporting::files::Error> {
+    let sarif_location = |span: Span, message: Option<String>| -> Option<Value> {
+        let (Some(file), Some(range)) = location(world, span) else { return None };
+        let mut location = json!({
+            "physicalLocation": {
+                "artifactLocation": artifact(span.id(), file),
+                "region": {
+                    "startLine": range["start"]["line"],
+                    "startColumn": range["start"]["column"],
+                    "endLine": range["end"]["line"],
+                    "endColumn": range["end"]["column"],
+                },
+            },
+        });
+        if let Some(message) = message {
+            location["message"] = json!({ "text": message });
+        }
+        Some(location)
+    };
+
+    let results: Vec<_> = warnings
+        .iter()
+        .chain(errors.iter())
+        .map(|diagnostic| {
+            let mut text = diagnostic.message.to_string();
+            for hint in &diagnostic.hints {
+                text.push_str(&format!("\nhint: {hint}"));
+            }
+
+            let locations: Vec<_> =
+                sarif_location(diagnostic.span, None).into_iter().collect();
+            let related: Vec<_> = diagnostic
+                .trace
+                .iter()
+                .filter_map(|point| sarif_location(point.span, Some(point.v.to_string())))
+                .collect();
+
+            let (rule, _) = SARIF_RULES[rule_index(diagnostic.severity)];
+            json!({
+                "ruleId": rule,
+                "ruleIndex": rule_index(diagnostic.severity),
+                "level": severity(diagnostic.severity),
+                "message": { "text": text },
+                "locations": locations,
+                "relatedLocations": related,
+            })
+        })
+        .collect();
+
+    let rules: Vec<_> = [Severity::Error, Severity::Warning]
+        .into_iter()
+        .map(|level| {
+            let (id, description) = SARIF_RULES[rule_index(level)];
+            json!({
+                "id": id,
+                "shortDescription": { "text": description },
+                "defaultConfiguration": { "level": severity(level) },
+            })
+        })
+        .collect();
+
+    let log = json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "typst",
+                    "version": crate::typst_version(),
+                    "informationUri": "https://typst.app",
+                    "rules": rules,
+                },
+            },
+            "originalUriBaseIds": {
+                "PROJECTROOT": { "uri": directory_uri(world.root()) },
+            },
+            "columnKind": "unicodeCodePoints",
+            "results": results,
+        }],
+    });
+
+    writeln!(io::stderr().lock(), "{log}")?;
+    Ok(())
+}
+
+/// The SARIF rules that results refer to, by their id and description.
+const SARIF_RULES: [(&str, &str); 2] = [
+    ("typst/error", "An error that prevents compilation"),
+    ("typst/warning", "A problem that doesn't prevent compilation"),
+];
+
+/// The index of the SARIF rule for diagnostics of a severity.
+fn rule_index(severity: Severity) -> usize {
+    match severity {
+        Severity::Error => 0,
+        Severity::Warning => 1,
+    }
+}
+
+/// The name of a severity in JSON and SARIF output.
+fn severity(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+    }
+}
+
+/// The file and the one-based line and column range of a span in JSON
+/// output.
+///
+/// Both are `null` for detached spans. Columns count characters.
+fn location(world: &SystemWorld, span: Span) -> (Option<String>, Option<Value>) {
+    if span.is_detached() {
+        return (None, None);
+    }
+
+    let source = world.lookup(span.id());
+    let range = world.range(span);
+    let position = |byte: usize| {
+        json!({
+            "line": source.byte_to_line(byte).map(|line| line + 1),
+            "column": source.byte_to_column(byte).map(|column| column + 1),
+        })
+    };
+
+    let range = json!({ "start": position(range.start), "end": position(range.end) });
+    (Some(span.id().to_string()), Some(range))
+}
+
+/// A SARIF artifact location for a file.
+fn artifact(id: FileId, file: String) -> Value {
+    if id.package().is_some() {
+        return json!({ "uri": file });
+    }
+
+    let path = encode_uri_path(&id.path().to_string_lossy());
+    json!({
+        "uri": path.trim_start_matches('/'),
+        "uriBaseId": "PROJECTROOT",
+    })
+}
+
+/// A `file` URI for a directory, ending in a slash.
+fn directory_uri(path: &Path) -> String {
+    let mut uri = path_to_uri(path);
+    if !uri.ends_with('/') {
+        uri.push('/');
+    }
+    uri
+}
+
+/// Fail with the warnings as errors if `--deny-warnings` is given and there
+/// are any.
+pub fn deny_warnings<T>(
+    result: SourceResult<T>,
+    warnings: Vec<SourceDiagnostic>,
+    deny: bool,
+) -> (SourceResult<T>, Vec<SourceDiagnostic>) {
+    if !deny || warnings.is_empty() {
+        return (result, warnings);
+    }
+
+    let mut errors: Vec<_> = warnings
+        .into_iter()
+        .map(|mut warning| {
+            warning.severity = Severity::Error;
+            warning.hints.push("warnings are denied by `--deny-warnings`".into());
+            warning
+        })
+        .collect();
+
+    if let Err(others) = result {
+        errors.extend(*others);
+    }
+
+    (Err(Box::new(errors)), vec![])
+}
+
+/// Create a label for a span.
+///
+/// Returns `None` for detached spans, which don't point into any file.
+fn label(world: &SystemWorld, span: Span) -> Option<Label<FileId>> {
+    (!span.is_detached()).then(|| Label::primary(span.id(), world.range(span)))
+}
+
 impl<'a> codespan_reporting::files::Files<'a> for SystemWorld {
     type FileId = FileId;
     type Name = FileId;
@@ -253,3 +836,161 @@ impl<'a> codespan_reporting::files::Files<'a> for SystemWorld {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use typst::doc::Frame;
+    use typst::geom::{Abs, Size};
+
+    use super::*;
+
+    fn parse(args: &[&str]) -> CompileCommand {
+        CompileCommand::try_parse_from(["compile"].iter().chain(args)).unwrap()
+    }
+
+    fn document(pages: usize) -> Document {
+        let page = Frame::new(Size::splat(Abs::pt(10.0)));
+        Document { pages: vec![page; pages], ..Default::default() }
+    }
+
+    #[test]
+    fn test_exported_pages() {
+        assert_eq!(parse(&["in.typ"]).exported_pages(3), [0, 1, 2]);
+        assert_eq!(parse(&["in.typ", "--pages", "1,3-"]).exported_pages(5), [0, 2, 3, 4]);
+        assert_eq!(parse(&["in.typ", "--pages", "-2,2"]).exported_pages(5), [0, 1]);
+        assert_eq!(parse(&["in.typ", "--pages", "4-9"]).exported_pages(5), [3, 4]);
+
+        for invalid in ["0", "3-1", "x", ""] {
+            let args = ["compile", "in.typ", "--pages", invalid];
+            assert!(CompileCommand::try_parse_from(args).is_err());
+        }
+    }
+
+    #[test]
+    fn test_export_rejects_empty_page_selection() {
+        let command = parse(&["in.typ", "out.pdf", "--pages", "3-"]);
+        assert_eq!(
+            export(&document(2), &command).unwrap_err(),
+            "page selection contains none of the document's 2 pages"
+        );
+    }
+
+    #[test]
+    fn test_export_jpeg() {
+        let dir = tempfile::tempdir().unwrap();
+        let jpeg = dir.path().join("page-{n}.jpg").to_string_lossy().into_owned();
+        let document = document(3);
+
+        let command = parse(&["in.typ", &jpeg, "--pages", "2-"]);
+        export_image(&document, &command, ImageExportFormat::Jpeg).unwrap();
+        assert!(!dir.path().join("page-1.jpg").exists());
+        let data = fs::read(dir.path().join("page-2.jpg")).unwrap();
+        assert!(data.starts_with(&[0xFF, 0xD8, 0xFF]));
+
+        let command = parse(&["in.typ", &jpeg, "--transparent"]);
+        assert_eq!(
+            export_image(&document, &command, ImageExportFormat::Jpeg).unwrap_err(),
+            "JPEG images cannot have a transparent background"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "webp")]
+    fn test_export_webp() {
+        let dir = tempfile::tempdir().unwrap();
+        let webp = dir.path().join("page.webp").to_string_lossy().into_owned();
+        let command = parse(&["in.typ", &webp, "--pages", "3", "--transparent"]);
+        export_image(&document(3), &command, ImageExportFormat::Webp).unwrap();
+        let data = fs::read(&webp).unwrap();
+        assert_eq!((&data[..4], &data[8..12]), (&b"RIFF"[..], &b"WEBP"[..]));
+    }
+
+    #[test]
+    #[cfg(not(feature = "webp"))]
+    fn test_export_webp_requires_feature() {
+        let command = parse(&["in.typ", "page.webp"]);
+        assert_eq!(
+            export_image(&document(1), &command, ImageExportFormat::Webp).unwrap_err(),
+            "WebP export requires building Typst with the `webp` feature"
+        );
+    }
+
+    #[test]
+    fn test_numbered_path() {
+        assert_eq!(numbered_path("page-{n}.png", 0, 1), "page-1.png");
+        assert_eq!(numbered_path("page-{n}.png", 8, 10), "page-09.png");
+        assert_eq!(numbered_path("page-{n}.png", 9, 10), "page-10.png");
+        assert_eq!(numbered_path("{n}/{n}.svg", 41, 100), "042/042.svg");
+        assert_eq!(numbered_path("page-{n}.png", 0, 0), "page-1.png");
+        assert_eq!(numbered_path("page.png", 3, 5), "page.png");
+    }
+
+    #[test]
+    fn test_escape_make() {
+        assert_eq!(escape_make("main.typ"), "main.typ");
+        assert_eq!(escape_make("my file.typ"), "my\\ file.typ");
+        assert_eq!(escape_make("#1.typ"), "\\#1.typ");
+        assert_eq!(escape_make("$HOME.typ"), "$$HOME.typ");
+        assert_eq!(escape_make("a:b.typ"), "a\\:b.typ");
+    }
+
+    fn messages(diagnostics: &[SourceDiagnostic]) -> Vec<(Severity, &str)> {
+        diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.severity, diagnostic.message.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_deny_warnings() {
+        let warning = || vec![SourceDiagnostic::warning(Span::detached(), "unused")];
+
+        let (result, warnings) = deny_warnings(Ok(()), warning(), false);
+        assert!(result.is_ok());
+        assert_eq!(messages(&warnings), [(Severity::Warning, "unused")]);
+
+        let (result, warnings) = deny_warnings(Ok(()), vec![], true);
+        assert!(result.is_ok());
+        assert!(warnings.is_empty());
+
+        let (result, warnings) = deny_warnings(Ok(()), warning(), true);
+        let errors = result.unwrap_err();
+        assert_eq!(messages(&errors), [(Severity::Error, "unused")]);
+        let hint = errors[0].hints.last().map(|hint| hint.as_str());
+        assert_eq!(hint, Some("warnings are denied by `--deny-warnings`"));
+        assert!(warnings.is_empty());
+
+        let failed =
+            Err(Box::new(vec![SourceDiagnostic::error(Span::detached(), "bad")]));
+        let (result, _) = deny_warnings::<()>(failed, warning(), true);
+        assert_eq!(
+            messages(&result.unwrap_err()),
+            [(Severity::Error, "unused"), (Severity::Error, "bad")]
+        );
+    }
+
+    #[test]
+    fn test_artifact() {
+        let local = FileId::new(None, Path::new("/chapters/my intro.typ"));
+        assert_eq!(
+            artifact(local, local.to_string()),
+            json!({ "uri": "chapters/my%20intro.typ", "uriBaseId": "PROJECTROOT" })
+        );
+
+        let spec = "@preview/example:0.1.0".parse().unwrap();
+        let package = FileId::new(Some(spec), Path::new("/lib.typ"));
+        assert_eq!(
+            artifact(package, package.to_string()),
+            json!({ "uri": package.to_string() })
+        );
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn test_directory_uri() {
+        assert_eq!(directory_uri(Path::new("/home/user")), "file:///home/user/");
+        assert_eq!(directory_uri(Path::new("/")), "file:///");
+        assert_eq!(directory_uri(Path::new("/my docs/#1/")), "file:///my%20docs/%231/");
+    }
+}
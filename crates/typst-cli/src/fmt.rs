@@ -0,0 +1,79 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use typst::diag::StrResult;
+use typst::eval::eco_format;
+use typst::syntax;
+use walkdir::WalkDir;
+
+use crate::args::FmtCommand;
+use crate::set_failed;
+
+/// Execute a formatting command.
+pub fn fmt(command: FmtCommand) -> StrResult<()> {
+    let paths =
+        if command.paths.is_empty() { vec![PathBuf::from(".")] } else { command.paths };
+
+    for path in files(&paths)? {
+        let text = fs::read_to_string(&path)
+            .map_err(|_| eco_format!("failed to read {}", path.display()))?;
+
+        // Files with syntax errors are skipped, but fail the command.
+        let root = syntax::parse(&text);
+        let Some(formatted) = syntax::format(&root, command.width) else {
+            set_failed();
+            crate::print_error(&format!(
+                "failed to format {} (it contains syntax errors)",
+                path.display()
+            ))
+            .map_err(|_| "failed to print error")?;
+            continue;
+        };
+
+        if formatted == text {
+            continue;
+        }
+
+        if command.check {
+            set_failed();
+            println!("{}", path.display());
+        } else {
+            fs::write(&path, formatted)
+                .map_err(|_| eco_format!("failed to write {}", path.display()))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Collect the Typst files at the given paths, searching directories
+/// recursively.
+fn files(paths: &[PathBuf]) -> StrResult<Vec<PathBuf>> {
+    let mut files = vec![];
+    for path in paths {
+        if !path.exists() {
+            return Err(eco_format!("file not found (searched at {})", path.display()));
+        }
+
+        if !path.is_dir() {
+            files.push(path.clone());
+            continue;
+        }
+
+        for entry in WalkDir::new(path)
+            .sort_by(|a, b| a.file_name().cmp(b.file_name()))
+            .into_iter()
+            .filter_map(|e| e.ok())
+        {
+            if is_typst_file(entry.path()) {
+                files.push(entry.into_path());
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Whether the path points to a Typst source file.
+fn is_typst_file(path: &Path) -> bool {
+    path.is_file() && path.extension().map_or(false, |ext| ext == "typ")
+}
@@ -0,0 +1,947 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::{self, BufRead, Read, StdoutLock, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use comemo::Prehashed;
+use serde_json::{json, Value};
+use typst::diag::{FileResult, Severity, SourceDiagnostic, StrResult};
+use typst::doc::{Document, Frame};
+use typst::eval::{eco_format, Datetime, Library, Tracer};
+use typst::font::{Font, FontBook};
+use typst::ide::{autocomplete, highlight, tooltip, CompletionKind, Tag, Tooltip};
+use typst::syntax::{FileId, LinkedNode, Source, Span};
+use typst::util::{Bytes, PathExt};
+use typst::World;
+
+use crate::args::LspCommand;
+use crate::set_failed;
+use crate::world::SystemWorld;
+
+/// Error code for requests the server doesn't support.
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// Error code for requests that arrive before the server was initialized.
+const SERVER_NOT_INITIALIZED: i64 = -32002;
+
+/// Error code for requests that the client cancelled before they were
+/// answered.
+const REQUEST_CANCELLED: i64 = -32800;
+
+/// How long the client must stop editing before the project is checked.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// The semantic token types the server reports, as indexed by
+/// [`token_type`].
+const TOKEN_TYPES: [&str; 19] = [
+    "comment",
+    "punctuation",
+    "escape",
+    "strong",
+    "emph",
+    "link",
+    "raw",
+    "label",
+    "ref",
+    "heading",
+    "marker",
+    "term",
+    "delimiter",
+    "keyword",
+    "operator",
+    "number",
+    "string",
+    "function",
+    "variable",
+];
+
+/// The result of a request: Either a value or an error code with a message.
+type Response = Result<Value, (i64, String)>;
+
+/// Execute a language server command.
+pub fn lsp(command: LspCommand) -> StrResult<()> {
+    // Messages are read on their own thread so that the server can wait for
+    // the client to stop editing before it checks the project.
+    let messages = spawn_reader();
+    let mut output = io::stdout().lock();
+
+    // The world can only be set up once the client told us about the
+    // workspace.
+    let params = loop {
+        let Ok(message) = messages.recv() else { return Ok(()) };
+        let message = message?;
+        match message["method"].as_str() {
+            Some("initialize") => {
+                send(&mut output, &response(&message["id"], Ok(capabilities())))?;
+                break message["params"].clone();
+            }
+            Some("exit") => return Ok(()),
+            _ => {
+                if let Some(id) = message.get("id") {
+                    let error =
+                        (SERVER_NOT_INITIALIZED, "server is not initialized".into());
+                    send(&mut output, &response(id, Err(error)))?;
+                }
+            }
+        }
+    };
+
+    // Resolve the root directory from the command or the client's workspace.
+    let root = match &command.world.root {
+        Some(root) => root.clone(),
+        None => params["rootUri"]
+            .as_str()
+            .and_then(uri_to_path)
+            .or_else(|| params["rootPath"].as_str().map(PathBuf::from))
+            .unwrap_or_else(|| PathBuf::from(".")),
+    };
+    let root = root.canonicalize().map_err(|_| {
+        eco_format!("root directory not found (searched at {})", root.display())
+    })?;
+
+    // Resolve the project's main file within the root.
+    let main = match &command.main {
+        Some(path) => {
+            let path = path.canonicalize().map_err(|_| {
+                eco_format!("main file not found (searched at {})", path.display())
+            })?;
+            let relative = path
+                .strip_prefix(&root)
+                .map_err(|_| "main file must be contained in project root")?;
+            Some(FileId::new(None, &Path::new("/").join(relative)))
+        }
+        None => None,
+    };
+
+    // Without a main file, the world's main file changes with the edited
+    // file, so this is a placeholder.
+    let placeholder = FileId::new(None, Path::new("/main.typ"));
+    let system =
+        SystemWorld::with_main(root, main.unwrap_or(placeholder), &command.world);
+
+    let mut server = Server {
+        world: LspWorld { system, buffers: HashMap::new() },
+        main,
+        pending: None,
+        document: None,
+        published: HashSet::new(),
+        cancelled: HashSet::new(),
+        shutdown: false,
+        output,
+    };
+
+    let mut queue = VecDeque::new();
+    loop {
+        // Wait for the next message. Once the client stopped editing for a
+        // moment, the pending check runs.
+        if queue.is_empty() {
+            let message = if server.pending.is_some() {
+                match messages.recv_timeout(DEBOUNCE) {
+                    Ok(message) => message,
+                    Err(RecvTimeoutError::Timeout) => {
+                        server.check()?;
+                        continue;
+                    }
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+            } else {
+                match messages.recv() {
+                    Ok(message) => message,
+                    Err(_) => break,
+                }
+            };
+            queue.push_back(message?);
+        }
+
+        // Requests can only be cancelled before they are answered, so look
+        // ahead at the messages that already arrived.
+        for message in messages.try_iter() {
+            queue.push_back(message?);
+        }
+        for message in &queue {
+            if message["method"] == "$/cancelRequest" {
+                server.cancelled.insert(message["params"]["id"].to_string());
+            }
+        }
+
+        let Some(message) = queue.pop_front() else { continue };
+        if !server.handle(&message)? {
+            break;
+        }
+    }
+
+    // Clients must shut the server down before they make it exit.
+    if !server.shutdown {
+        set_failed();
+    }
+
+    Ok(())
+}
+
+/// Read the client's messages on a separate thread.
+///
+/// The channel disconnects once the client closed the connection or a message
+/// couldn't be read.
+fn spawn_reader() -> Receiver<StrResult<Value>> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut input = io::stdin().lock();
+        loop {
+            let message = match receive(&mut input) {
+                Ok(Some(message)) => Ok(message),
+                Ok(None) => break,
+                Err(err) => Err(err),
+            };
+            let failed = message.is_err();
+            if sender.send(message).is_err() || failed {
+                break;
+            }
+        }
+    });
+    receiver
+}
+
+/// A language server for the files that are open in an editor.
+struct Server {
+    /// The world the open files are compiled in.
+    world: LspWorld,
+    /// The project's main file, if one was configured. Otherwise, the edited
+    /// file is compiled as the main file.
+    main: Option<FileId>,
+    /// The file that was edited since the project was last checked.
+    pending: Option<FileId>,
+    /// The last successfully compiled document, whose pages improve
+    /// completions and tooltips.
+    document: Option<Document>,
+    /// The files for which diagnostics were published when the project was
+    /// last checked.
+    published: HashSet<FileId>,
+    /// The requests that the client cancelled, by their serialized id.
+    cancelled: HashSet<String>,
+    /// Whether the client asked the server to shut down.
+    shutdown: bool,
+    /// The stream to the client.
+    output: StdoutLock<'static>,
+}
+
+impl Server {
+    /// Handle a message from the client.
+    ///
+    /// Returns `false` once the client asks the server to exit.
+    fn handle(&mut self, message: &Value) -> StrResult<bool> {
+        let method = message["method"].as_str().unwrap_or_default();
+        let params = &message["params"];
+        match message.get("id") {
+            // Responses to requests of the server, which it doesn't send.
+            _ if method.is_empty() => {}
+            Some(id) => {
+                let result = if self.cancelled.remove(&id.to_string()) {
+                    Err((REQUEST_CANCELLED, "request was cancelled".into()))
+                } else {
+                    self.request(method, params)
+                };
+                send(&mut self.output, &response(id, result))?;
+            }
+            None => return self.notify(method, params),
+        }
+
+        Ok(true)
+    }
+
+    /// Answer a request.
+    fn request(&mut self, method: &str, params: &Value) -> Response {
+        let result = match method {
+            "shutdown" => {
+                self.shutdown = true;
+                None
+            }
+            "textDocument/completion" => self.completion(params),
+            "textDocument/hover" => self.hover(params),
+            "textDocument/semanticTokens/full" => self.semantic_tokens(params),
+            _ => return Err((METHOD_NOT_FOUND, format!("unsupported method {method}"))),
+        };
+
+        Ok(result.unwrap_or(Value::Null))
+    }
+
+    /// Handle a notification.
+    ///
+    /// Returns `false` once the client asks the server to exit.
+    fn notify(&mut self, method: &str, params: &Value) -> StrResult<bool> {
+        let document = &params["textDocument"];
+        match (method, self.world.id(&document["uri"])) {
+            ("exit", _) => return Ok(false),
+            // The cancelled request was answered before this notification.
+            ("$/cancelRequest", _) => {
+                self.cancelled.remove(&params["id"].to_string());
+            }
+            ("textDocument/didOpen", Some(id)) => {
+                let text = document["text"].as_str().unwrap_or_default();
+                self.world.buffers.insert(id, Source::new(id, text.into()));
+                self.pending = Some(id);
+            }
+            ("textDocument/didOpen", None) => {
+                tracing::warn!("ignoring file outside of the project root");
+            }
+            ("textDocument/didChange", Some(id)) => {
+                let Some(source) = self.world.buffers.get_mut(&id) else {
+                    return Ok(true);
+                };
+                for change in params["contentChanges"].as_array().into_iter().flatten() {
+                    apply_change(source, change);
+                }
+                self.pending = Some(id);
+            }
+            ("textDocument/didSave", Some(id))
+                if self.world.buffers.contains_key(&id) =>
+            {
+                self.pending = Some(id);
+            }
+            ("textDocument/didClose", Some(id)) => {
+                self.world.buffers.remove(&id);
+                match self.main {
+                    // The project is checked again with the file from disk.
+                    Some(main) => self.pending = Some(main),
+                    // The file's problems disappear along with it.
+                    None => {
+                        if self.pending == Some(id) {
+                            self.pending = None;
+                        }
+                        if self.published.remove(&id) {
+                            self.publish(id, vec![])?;
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        Ok(true)
+    }
+
+    /// The file that is compiled as the main file when the given file was
+    /// edited.
+    fn main(&self, edited: FileId) -> FileId {
+        self.main.unwrap_or(edited)
+    }
+
+    /// Compile the project after an edit and publish the resulting
+    /// diagnostics.
+    fn check(&mut self) -> StrResult<()> {
+        let Some(edited) = self.pending.take() else { return Ok(()) };
+        let main = self.main(edited);
+        self.world.system.reset();
+        self.world.system.set_main(main);
+
+        // A missing main file is reported at the edited file.
+        let mut diagnostics = vec![];
+        let mut tracer = Tracer::default();
+        if let Err(err) = self.world.source(main) {
+            diagnostics.push(SourceDiagnostic::error(Span::detached(), err.to_string()));
+        } else {
+            let result = typst::compile(&self.world, &mut tracer);
+            diagnostics.extend(tracer.warnings());
+            match result {
+                Ok(document) => self.document = Some(document),
+                Err(errors) => diagnostics.extend(*errors),
+            }
+        }
+
+        // Group the diagnostics by file. The main and the edited file always
+        // get an entry so that their fixed problems disappear.
+        let mut files: HashMap<FileId, Vec<Value>> = HashMap::new();
+        files.insert(main, vec![]);
+        files.insert(edited, vec![]);
+        for diagnostic in diagnostics {
+            // Problems that can't be shown where they occurred, for instance
+            // in packages, are shown at the start of the edited file.
+            let (file, range) = self.world.locate(diagnostic.span).unwrap_or_else(|| {
+                let start = json!({ "line": 0, "character": 0 });
+                (edited, json!({ "start": start, "end": start }))
+            });
+
+            let mut message = diagnostic.message.to_string();
+            for hint in &diagnostic.hints {
+                message.push_str("\nhint: ");
+                message.push_str(hint);
+            }
+
+            let severity = match diagnostic.severity {
+                Severity::Error => 1,
+                Severity::Warning => 2,
+            };
+
+            files.entry(file).or_default().push(json!({
+                "range": range,
+                "severity": severity,
+                "source": "typst",
+                "message": message,
+            }));
+        }
+
+        // Clear the diagnostics of files that had problems before but don't
+        // anymore.
+        let previous =
+            std::mem::replace(&mut self.published, files.keys().copied().collect());
+        for file in previous {
+            files.entry(file).or_default();
+        }
+
+        for (file, diagnostics) in files {
+            self.publish(file, diagnostics)?;
+        }
+
+        comemo::evict(10);
+        Ok(())
+    }
+
+    /// Replace the diagnostics the client shows for a file.
+    fn publish(&mut self, file: FileId, diagnostics: Vec<Value>) -> StrResult<()> {
+        let Some(uri) = self.world.uri(file) else { return Ok(()) };
+        let notification = json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": { "uri": uri, "diagnostics": diagnostics },
+        });
+        send(&mut self.output, &notification)
+    }
+
+    /// Complete the code at the cursor.
+    fn completion(&mut self, params: &Value) -> Option<Value> {
+        let (id, source, cursor) = self.cursor(params)?;
+        self.world.system.set_main(self.main(id));
+
+        // Typing a trigger character doesn't count as requesting completions
+        // explicitly.
+        let explicit = params["context"]["triggerKind"].as_u64() == Some(1);
+        let (from, completions) =
+            autocomplete(&self.world, self.frames(), &source, cursor, explicit)?;
+
+        let range = to_range(&source, from..cursor);
+        let items: Vec<_> = completions
+            .into_iter()
+            .map(|completion| {
+                let kind = match completion.kind {
+                    CompletionKind::Syntax => 15,
+                    CompletionKind::Func => 3,
+                    CompletionKind::Param => 6,
+                    CompletionKind::Constant => 21,
+                    CompletionKind::Symbol(_) => 1,
+                };
+
+                let apply = completion.apply.as_deref().unwrap_or(&completion.label);
+                let mut item = json!({
+                    "label": completion.label.as_str(),
+                    "kind": kind,
+                    "textEdit": { "range": range, "newText": to_snippet(apply) },
+                    "insertTextFormat": 2,
+                });
+
+                let detail = match completion.kind {
+                    CompletionKind::Symbol(c) => Some(eco_format!("{c}")),
+                    _ => completion.detail,
+                };
+                if let Some(detail) = detail {
+                    item["detail"] = detail.as_str().into();
+                }
+
+                item
+            })
+            .collect();
+
+        Some(json!(items))
+    }
+
+    /// Describe the code at the cursor.
+    fn hover(&mut self, params: &Value) -> Option<Value> {
+        let (id, source, cursor) = self.cursor(params)?;
+        self.world.system.set_main(self.main(id));
+
+        let value = match tooltip(&self.world, self.frames(), &source, cursor)? {
+            Tooltip::Text(text) => text.to_string(),
+            Tooltip::Code(code) => format!("```typst\n{code}\n```"),
+        };
+
+        Some(json!({ "contents": { "kind": "markdown", "value": value } }))
+    }
+
+    /// Highlight a whole file.
+    fn semantic_tokens(&mut self, params: &Value) -> Option<Value> {
+        let id = self.world.id(&params["textDocument"]["uri"])?;
+        let source = self.world.buffers.get(&id)?;
+
+        let mut tokens = vec![];
+        collect_tokens(&LinkedNode::new(source.root()), None, &mut tokens);
+
+        // Tokens are encoded relative to the previous one and must not span
+        // multiple lines.
+        let utf16 = |offset| source.byte_to_utf16(offset).unwrap_or_default();
+        let mut data = vec![];
+        let (mut prev_line, mut prev_column) = (0, 0);
+        for (range, kind) in tokens {
+            let mut start = range.start;
+            while start < range.end {
+                let line = source.byte_to_line(start)?;
+                let line_range = source.line_to_range(line)?;
+                let end = line_range.end.min(range.end);
+                let text = source.get(start..end)?.trim_end_matches(['\r', '\n']);
+
+                let column = utf16(start) - utf16(line_range.start);
+                let length = utf16(start + text.len()) - utf16(start);
+                if length > 0 {
+                    let delta_column =
+                        if line == prev_line { column - prev_column } else { column };
+                    data.extend([line - prev_line, delta_column, length, kind, 0]);
+                    (prev_line, prev_column) = (line, column);
+                }
+
+                start = end.max(start + 1);
+            }
+        }
+
+        Some(json!({ "data": data }))
+    }
+
+    /// Find the open file and the offset in it that a request points to.
+    fn cursor(&self, params: &Value) -> Option<(FileId, Source, usize)> {
+        let id = self.world.id(&params["textDocument"]["uri"])?;
+        let source = self.world.buffers.get(&id)?.clone();
+        let cursor = to_offset(&source, &params["position"])?;
+        Some((id, source, cursor))
+    }
+
+    /// The pages of the last successfully compiled document.
+    fn frames(&self) -> &[Frame] {
+        self.document.as_ref().map_or(&[], |document| &document.pages)
+    }
+}
+
+/// A world that serves the files that are open in the editor from memory and
+/// all other files from disk.
+struct LspWorld {
+    /// The world that accesses the file system.
+    system: SystemWorld,
+    /// The editor's buffers of the open files.
+    buffers: HashMap<FileId, Source>,
+}
+
+impl LspWorld {
+    /// Determine the id of the file a URI points to.
+    ///
+    /// Returns `None` for files outside of the project root.
+    fn id(&self, uri: &Value) -> Option<FileId> {
+        let path = uri_to_path(uri.as_str()?)?;
+        let path = path.canonicalize().unwrap_or(path);
+        let relative = path.strip_prefix(self.system.root()).ok()?;
+        Some(FileId::new(None, &Path::new("/").join(relative)))
+    }
+
+    /// Determine the URI of a file.
+    ///
+    /// Returns `None` for files in packages, which the editor doesn't know.
+    fn uri(&self, id: FileId) -> Option<String> {
+        if id.package().is_some() {
+            return None;
+        }
+
+        let path = self.system.root().join_rooted(id.path())?;
+        Some(path_to_uri(&path))
+    }
+
+    /// Find the file and the range in it that a span points to.
+    fn locate(&self, span: Span) -> Option<(FileId, Value)> {
+        if span.is_detached() || span.id().package().is_some() {
+            return None;
+        }
+
+        let source = self.source(span.id()).ok()?;
+        Some((span.id(), to_range(&source, source.range(span))))
+    }
+}
+
+impl World for LspWorld {
+    fn library(&self) -> &Prehashed<Library> {
+        self.system.library()
+    }
+
+    fn book(&self) -> &Prehashed<FontBook> {
+        self.system.book()
+    }
+
+    fn main(&self) -> Source {
+        // The server makes sure that the main file exists before compiling.
+        self.source(self.system.main())
+            .unwrap_or_else(|_| Source::detached(""))
+    }
+
+    fn source(&self, id: FileId) -> FileResult<Source> {
+        match self.buffers.get(&id) {
+            Some(source) => Ok(source.clone()),
+            None => self.system.source(id),
+        }
+    }
+
+    fn file(&self, id: FileId) -> FileResult<Bytes> {
+        match self.buffers.get(&id) {
+            Some(source) => Ok(source.text().as_bytes().into()),
+            None => self.system.file(id),
+        }
+    }
+
+    fn font(&self, index: usize) -> Option<Font> {
+        self.system.font(index)
+    }
+
+    fn today(&self, offset: Option<i64>) -> Option<Datetime> {
+        self.system.today(offset)
+    }
+}
+
+/// The server's capabilities and information about it.
+fn capabilities() -> Value {
+    json!({
+        "capabilities": {
+            // Open files are synchronized incrementally.
+            "textDocumentSync": { "openClose": true, "change": 2, "save": true },
+            "completionProvider": { "triggerCharacters": ["#", ".", "@"] },
+            "hoverProvider": true,
+            "semanticTokensProvider": {
+                "legend": { "tokenTypes": TOKEN_TYPES, "tokenModifiers": [] },
+                "full": true,
+            },
+        },
+        "serverInfo": { "name": "typst", "version": crate::typst_version() },
+    })
+}
+
+/// Create the response to a request.
+fn response(id: &Value, result: Response) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err((code, message)) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": code, "message": message },
+        }),
+    }
+}
+
+/// Read a message from the client.
+///
+/// Returns `None` once the client closed the connection.
+fn receive(input: &mut impl BufRead) -> StrResult<Option<Value>> {
+    // Read the header, of which only the content length is of interest.
+    let mut length = None;
+    loop {
+        let mut line = String::new();
+        if input
+            .read_line(&mut line)
+            .map_err(|_| "failed to read message from client")?
+            == 0
+        {
+            return Ok(None);
+        }
+
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse().ok();
+            }
+        }
+    }
+
+    let length = length.ok_or("message from client has no content length")?;
+    let mut body = vec![0; length];
+    input
+        .read_exact(&mut body)
+        .map_err(|_| "failed to read message from client")?;
+
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(|_| "message from client is not valid JSON".into())
+}
+
+/// Write a message to the client.
+fn send(output: &mut impl Write, message: &Value) -> StrResult<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{body}", body.len())
+        .and_then(|_| output.flush())
+        .map_err(|_| "failed to write message to client".into())
+}
+
+/// Apply a change the client made to an open file.
+fn apply_change(source: &mut Source, change: &Value) {
+    let Some(text) = change["text"].as_str() else { return };
+    let range = &change["range"];
+    if range.is_null() {
+        source.replace(text.into());
+    } else if let (Some(start), Some(end)) =
+        (to_offset(source, &range["start"]), to_offset(source, &range["end"]))
+    {
+        // Only the edited part of the syntax tree is reparsed.
+        source.edit(start..end, text);
+    }
+}
+
+/// Convert a position, whose column counts UTF-16 code units, into a byte
+/// offset.
+fn to_offset(source: &Source, position: &Value) -> Option<usize> {
+    let line = usize::try_from(position["line"].as_u64()?).ok()?;
+    let column = usize::try_from(position["character"].as_u64()?).ok()?;
+    let start = source.line_to_byte(line)?;
+    source.utf16_to_byte(source.byte_to_utf16(start)? + column)
+}
+
+/// Convert a byte offset into a position.
+fn to_position(source: &Source, offset: usize) -> Value {
+    let line = source.byte_to_line(offset).unwrap_or_default();
+    let start = source.line_to_byte(line).unwrap_or_default();
+    let column = source
+        .byte_to_utf16(offset)
+        .zip(source.byte_to_utf16(start))
+        .map_or(0, |(offset, start)| offset - start);
+    json!({ "line": line, "character": column })
+}
+
+/// Convert a byte range into a range of positions.
+fn to_range(source: &Source, range: Range<usize>) -> Value {
+    json!({
+        "start": to_position(source, range.start),
+        "end": to_position(source, range.end),
+    })
+}
+
+/// Collect the highlighted leaves of a syntax tree with their token types.
+///
+/// Leaves without a highlighting tag of their own take the one of their
+/// closest highlighted ancestor.
+fn collect_tokens(
+    node: &LinkedNode,
+    tag: Option<Tag>,
+    tokens: &mut Vec<(Range<usize>, usize)>,
+) {
+    let tag = highlight(node).or(tag);
+    if !node.get().children().as_slice().is_empty() {
+        for child in node.children() {
+            collect_tokens(&child, tag, tokens);
+        }
+    } else if let Some(kind) = tag.and_then(token_type) {
+        if !node.text().trim().is_empty() {
+            tokens.push((node.range(), kind));
+        }
+    }
+}
+
+/// The index of a highlighting tag's token type in [`TOKEN_TYPES`].
+///
+/// Returns `None` for syntax errors, which are reported as diagnostics.
+fn token_type(tag: Tag) -> Option<usize> {
+    Some(match tag {
+        Tag::Comment => 0,
+        Tag::Punctuation => 1,
+        Tag::Escape => 2,
+        Tag::Strong => 3,
+        Tag::Emph => 4,
+        Tag::Link => 5,
+        Tag::Raw => 6,
+        Tag::Label => 7,
+        Tag::Ref => 8,
+        Tag::Heading => 9,
+        Tag::ListMarker => 10,
+        Tag::ListTerm => 11,
+        Tag::MathDelimiter => 12,
+        Tag::Keyword => 13,
+        Tag::Operator | Tag::MathOperator => 14,
+        Tag::Number => 15,
+        Tag::String => 16,
+        Tag::Function => 17,
+        Tag::Interpolated => 18,
+        Tag::Error => return None,
+    })
+}
+
+/// Convert Typst's snippet syntax, in which `${name}` is a placeholder, into
+/// the protocol's, which numbers placeholders and escapes other dollar signs.
+fn to_snippet(apply: &str) -> String {
+    let escape = |text: &str, snippet: &mut String| {
+        for c in text.chars() {
+            if matches!(c, '$' | '}' | '\\') {
+                snippet.push('\\');
+            }
+            snippet.push(c);
+        }
+    };
+
+    let mut snippet = String::new();
+    let mut rest = apply;
+    let mut index = 0;
+    while let Some((before, after)) = rest.split_once("${") {
+        let Some((name, after)) = after.split_once('}') else { break };
+        escape(before, &mut snippet);
+        index += 1;
+        if name.is_empty() {
+            snippet.push_str(&format!("${{{index}}}"));
+        } else {
+            snippet.push_str(&format!("${{{index}:{name}}}"));
+        }
+        rest = after;
+    }
+
+    escape(rest, &mut snippet);
+    snippet
+}
+
+/// Convert a `file` URI into a path.
+fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    // Skip the authority, which is empty for local files.
+    let rest = uri.strip_prefix("file://")?;
+    let encoded = &rest[rest.find('/')?..];
+
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut iter = encoded.bytes();
+    while let Some(byte) = iter.next() {
+        if byte == b'%' {
+            let hex = [iter.next()?, iter.next()?];
+            bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            bytes.push(byte);
+        }
+    }
+
+    let path = String::from_utf8(bytes).ok()?;
+
+    // Windows paths start with a drive letter, as in `/c:/Users`.
+    if cfg!(windows) {
+        return Some(PathBuf::from(path.strip_prefix('/').unwrap_or(&path)));
+    }
+
+    Some(PathBuf::from(path))
+}
+
+/// Convert a path into a `file` URI.
+pub fn path_to_uri(path: &Path) -> String {
+    let lossy = path.to_string_lossy();
+    let mut path: &str = &lossy;
+    let mut uri = String::from("file://");
+    if cfg!(windows) {
+        path = path.strip_prefix(r"\\?\").unwrap_or(path);
+        uri.push('/');
+    }
+
+    uri.push_str(&encode_uri_path(path));
+    uri
+}
+
+/// Percent-encode a path for use in a URI, keeping its slashes.
+pub fn encode_uri_path(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        match byte {
+            b'\\' if cfg!(windows) => encoded.push('/'),
+            b'A'..=b'Z'
+            | b'a'..=b'z'
+            | b'0'..=b'9'
+            | b'-'
+            | b'.'
+            | b'_'
+            | b'~'
+            | b'/' => encoded.push(byte as char),
+            b':' if cfg!(windows) => encoded.push(':'),
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_offset() {
+        let source = Source::detached("ab\n😀c\n");
+        let offset = |line: u64, character: u64| {
+            to_offset(&source, &json!({ "line": line, "character": character }))
+        };
+
+        assert_eq!(offset(0, 0), Some(0));
+        assert_eq!(offset(0, 2), Some(2));
+        assert_eq!(offset(1, 0), Some(3));
+
+        // The emoji takes two UTF-16 code units and four bytes.
+        assert_eq!(offset(1, 2), Some(7));
+        assert_eq!(offset(1, 3), Some(8));
+        assert_eq!(offset(3, 0), None);
+        assert_eq!(to_offset(&source, &json!({ "line": 0 })), None);
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn test_uri_to_path() {
+        assert_eq!(
+            uri_to_path("file:///home/user/doc.typ"),
+            Some(PathBuf::from("/home/user/doc.typ"))
+        );
+        assert_eq!(
+            uri_to_path("file:///my%20docs/%C3%A4.typ"),
+            Some(PathBuf::from("/my docs/ä.typ"))
+        );
+        assert_eq!(
+            uri_to_path("file://localhost/doc.typ"),
+            Some(PathBuf::from("/doc.typ"))
+        );
+        assert_eq!(uri_to_path("https://typst.app/doc.typ"), None);
+        assert_eq!(uri_to_path("file:///broken%2"), None);
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn test_path_to_uri() {
+        assert_eq!(
+            path_to_uri(Path::new("/home/user/doc.typ")),
+            "file:///home/user/doc.typ"
+        );
+        assert_eq!(
+            path_to_uri(Path::new("/my docs/ä.typ")),
+            "file:///my%20docs/%C3%A4.typ"
+        );
+
+        let path = Path::new("/a#b/c:d.typ");
+        assert_eq!(uri_to_path(&path_to_uri(path)).as_deref(), Some(path));
+    }
+
+    #[test]
+    fn test_to_snippet() {
+        assert_eq!(to_snippet("plain"), "plain");
+        assert_eq!(to_snippet("rect(${})"), "rect(${1})");
+        assert_eq!(to_snippet("f(${a}, ${b})"), "f(${1:a}, ${2:b})");
+        assert_eq!(to_snippet("$x$ {y}"), "\\$x\\$ {y\\}");
+        assert_eq!(to_snippet("${"), "\\${");
+    }
+
+    #[test]
+    fn test_receive() {
+        let body = r#"{"jsonrpc":"2.0","method":"exit"}"#;
+        let raw = format!(
+            "Content-Length: {}\r\nContent-Type: text/plain\r\n\r\n{body}",
+            body.len()
+        );
+        let mut input = raw.as_bytes();
+        let message = receive(&mut input).unwrap().unwrap();
+        assert_eq!(message["method"], "exit");
+        assert_eq!(receive(&mut input), Ok(None));
+
+        let mut missing = "Content-Type: text/plain\r\n\r\n{}".as_bytes();
+        assert!(receive(&mut missing).is_err());
+
+        let mut invalid = "Content-Length: 3\r\n\r\n{]}".as_bytes();
+        assert!(receive(&mut invalid).is_err());
+    }
+}
@@ -1,7 +1,11 @@
 mod args;
 mod compile;
+mod fmt;
 mod fonts;
+mod lsp;
 mod package;
+mod query;
+mod serve;
 mod tracing;
 mod watch;
 mod world;
@@ -36,7 +40,10 @@ fn main() -> ExitCode {
     let res = match arguments.command {
         Command::Compile(command) => crate::compile::compile(command),
         Command::Watch(command) => crate::watch::watch(command),
+        Command::Query(command) => crate::query::query(command),
         Command::Fonts(command) => crate::fonts::fonts(command),
+        Command::Lsp(command) => crate::lsp::lsp(command),
+        Command::Fmt(command) => crate::fmt::fmt(command),
     };
 
     if let Err(msg) = res {
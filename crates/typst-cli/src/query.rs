@@ -0,0 +1,176 @@
+use comemo::Track;
+use typst::diag::{bail, StrResult};
+use typst::doc::Document;
+use typst::eval::{
+    eco_format, eval_string, Dict, EvalMode, IntoValue, Scope, Tracer, Value,
+};
+use typst::model::{Content, Introspector, LocatableSelector};
+use typst::syntax::Span;
+use typst::World;
+
+use crate::args::{QueryCommand, SerializationFormat};
+use crate::compile::{deny_warnings, print_diagnostics};
+use crate::set_failed;
+use crate::world::SystemWorld;
+
+/// Execute a query command.
+pub fn query(command: QueryCommand) -> StrResult<()> {
+    let mut world = SystemWorld::new(&command.common)?;
+    tracing::info!("Starting querying");
+
+    // Reset everything and ensure that the main file is present.
+    world.reset();
+    world.source(world.main()).map_err(|err| err.to_string())?;
+
+    let mut tracer = Tracer::default();
+    let result = typst::compile(&world, &mut tracer);
+    let (result, warnings) =
+        deny_warnings(result, tracer.warnings(), command.common.deny_warnings);
+
+    match result {
+        // Retrieve and print query results.
+        Ok(document) => {
+            let data = retrieve(&world, &command, &document)?;
+            let serialized = format(data, &command)?;
+            println!("{serialized}");
+            print_diagnostics(&world, &[], &warnings, command.common.diagnostic_format)
+                .map_err(|_| "failed to print diagnostics")?;
+        }
+
+        // Print diagnostics.
+        Err(errors) => {
+            set_failed();
+            print_diagnostics(
+                &world,
+                &errors,
+                &warnings,
+                command.common.diagnostic_format,
+            )
+            .map_err(|_| "failed to print diagnostics")?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Retrieve the matches for the selector.
+fn retrieve(
+    world: &dyn World,
+    command: &QueryCommand,
+    document: &Document,
+) -> StrResult<Vec<Value>> {
+    let selector = eval_string(
+        world.track(),
+        &command.selector,
+        Span::detached(),
+        EvalMode::Code,
+        Scope::default(),
+    )
+    .map_err(|errors| {
+        let mut message = eco_format!("failed to evaluate selector");
+        for (i, error) in errors.into_iter().enumerate() {
+            message.push_str(if i == 0 { ": " } else { ", " });
+            message.push_str(&error.message);
+        }
+        message
+    })?
+    .cast::<LocatableSelector>()?;
+
+    let introspector = Introspector::new(&document.pages);
+    introspector
+        .query(&selector.0)
+        .into_iter()
+        .map(|elem| match &command.field {
+            Some(field) => field_value(&elem, field),
+            None => Ok(element(&introspector, &elem)),
+        })
+        .collect()
+}
+
+/// Retrieve a field of a matched element.
+fn field_value(elem: &Content, field: &str) -> StrResult<Value> {
+    elem.field(field).ok_or_else(|| {
+        eco_format!("`{}` element does not contain field `{field}`", elem.func().name())
+    })
+}
+
+/// Describe a matched element as a dictionary of its function, fields and
+/// location.
+fn element(introspector: &Introspector, elem: &Content) -> Value {
+    let mut dict = Dict::new();
+    dict.insert("func".into(), elem.func().name().into_value());
+    for (key, value) in elem.fields() {
+        dict.insert(key.clone().into(), value);
+    }
+
+    if let Some(location) = elem.location() {
+        let position = introspector.position(location);
+        let mut loc = Dict::new();
+        loc.insert("page".into(), (position.page.get() as i64).into_value());
+        loc.insert("x".into(), position.point.x.to_pt().into_value());
+        loc.insert("y".into(), position.point.y.to_pt().into_value());
+        dict.insert("location".into(), loc.into_value());
+    }
+
+    dict.into_value()
+}
+
+/// Serialize the retrieved data in the requested format.
+fn format(elements: Vec<Value>, command: &QueryCommand) -> StrResult<String> {
+    if command.one && elements.len() != 1 {
+        bail!("expected exactly one element, found {}", elements.len())
+    }
+
+    if command.one {
+        serialize(&elements[0], command.format)
+    } else {
+        serialize(&elements, command.format)
+    }
+}
+
+/// Serialize data to the output format.
+fn serialize(
+    data: &impl serde::Serialize,
+    format: SerializationFormat,
+) -> StrResult<String> {
+    match format {
+        SerializationFormat::Json => {
+            serde_json::to_string_pretty(data).map_err(|e| eco_format!("{e}"))
+        }
+        SerializationFormat::Yaml => {
+            serde_yaml::to_string(data).map_err(|e| eco_format!("{e}"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use typst_library::meta::HeadingElem;
+    use typst_library::text::TextElem;
+
+    use super::*;
+
+    #[test]
+    fn test_field_value() {
+        let heading = HeadingElem::new(TextElem::packed("Intro")).pack();
+        assert_eq!(
+            field_value(&heading, "body"),
+            Ok(TextElem::packed("Intro").into_value())
+        );
+        assert_eq!(
+            field_value(&heading, "colour").unwrap_err().as_str(),
+            "`heading` element does not contain field `colour`"
+        );
+    }
+
+    #[test]
+    fn test_element() {
+        let heading = HeadingElem::new(TextElem::packed("Intro")).pack();
+        let Value::Dict(dict) = element(&Introspector::new(&[]), &heading) else {
+            panic!("expected a dictionary");
+        };
+        assert_eq!(dict.at("func", None), Ok(&"heading".into_value()));
+        assert_eq!(dict.at("body", None), Ok(&TextElem::packed("Intro").into_value()));
+        assert!(!dict.contains("location"));
+    }
+}
@@ -0,0 +1,507 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use base64::Engine;
+use serde_json::{json, Value};
+use typst::diag::StrResult;
+use typst::doc::{Document, Frame};
+use typst::eval::eco_format;
+use typst::geom::{Abs, Color, Point};
+use typst::ide::{jump_from_click, Jump};
+use typst::util::{hash128, PathExt};
+use typst::World;
+
+use crate::watch::Trigger;
+use crate::world::SystemWorld;
+
+/// The page that shows the preview.
+const PREVIEW_HTML: &str = include_str!("preview.html");
+
+/// The key that is appended to a WebSocket handshake's key before hashing.
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// WebSocket opcodes.
+const TEXT: u8 = 0x1;
+const CLOSE: u8 = 0x8;
+const PING: u8 = 0x9;
+const PONG: u8 = 0xA;
+
+/// The largest WebSocket message a client may send.
+const MAX_MESSAGE_LEN: u64 = 1 << 16;
+
+/// How long writing to a preview may block before it is disconnected.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A live preview of the watched document, served over HTTP.
+///
+/// Browsers load the pages as SVG and get the pages that changed pushed over
+/// a WebSocket after every compilation. Clicks on a page are sent back and
+/// resolved on the watching thread, which owns the world. The answer only goes
+/// to the preview that was clicked.
+pub struct Preview {
+    /// The state shared with the server's threads.
+    shared: Arc<Mutex<Shared>>,
+    /// The last laid out document.
+    document: Option<Document>,
+}
+
+/// The part of the preview that the server's threads access.
+struct Shared {
+    /// The rendered pages of the last laid out document.
+    pages: Vec<RenderedPage>,
+    /// The resolution of the pages' PNG renderings.
+    pixel_per_pt: f32,
+    /// The queues of WebSocket frames to send to the open previews, by id.
+    /// Each preview has a thread that writes its frames, so that a slow
+    /// preview doesn't hold up the others.
+    clients: HashMap<usize, Sender<(u8, Vec<u8>)>>,
+    /// The id of the next client that connects.
+    next_id: usize,
+}
+
+/// A page rendered for the preview.
+struct RenderedPage {
+    /// The hash of the page's frame.
+    hash: u128,
+    /// The page's frame, to render it as PNG on request.
+    frame: Frame,
+    /// The page as SVG.
+    svg: String,
+    /// The page as PNG, once it was requested.
+    png: Option<Arc<Vec<u8>>>,
+}
+
+impl Preview {
+    /// Start serving a preview at the given address.
+    ///
+    /// Clicks on the pages are sent through `trigger`.
+    pub fn serve(
+        addr: SocketAddr,
+        ppi: f32,
+        trigger: Sender<Trigger>,
+    ) -> StrResult<Self> {
+        let listener = TcpListener::bind(addr)
+            .map_err(|_| eco_format!("failed to start preview server at {addr}"))?;
+
+        let shared = Arc::new(Mutex::new(Shared {
+            pages: vec![],
+            pixel_per_pt: ppi / 72.0,
+            clients: HashMap::new(),
+            next_id: 0,
+        }));
+        let state = Arc::clone(&shared);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let shared = Arc::clone(&state);
+                let trigger = trigger.clone();
+                thread::spawn(move || {
+                    if let Err(err) = respond(stream, &shared, &trigger) {
+                        tracing::info!("Preview connection failed: {err}");
+                    }
+                });
+            }
+        });
+
+        Ok(Self { shared, document: None })
+    }
+
+    /// Show a newly laid out document.
+    ///
+    /// Only the pages whose frames changed are rendered and sent again. PNGs
+    /// are only rendered when they are requested.
+    pub fn update(&mut self, document: Document) {
+        let mut shared = self.shared.lock().unwrap();
+        shared.pages.truncate(document.pages.len());
+
+        let mut changed = vec![];
+        for (i, frame) in document.pages.iter().enumerate() {
+            let hash = hash128(frame);
+            if shared.pages.get(i).is_some_and(|page| page.hash == hash) {
+                continue;
+            }
+
+            let svg = typst::export::svg(frame);
+            let page = RenderedPage { hash, frame: frame.clone(), svg, png: None };
+            if i < shared.pages.len() {
+                shared.pages[i] = page;
+            } else {
+                shared.pages.push(page);
+            }
+
+            changed.push(i);
+        }
+
+        let message = shared.pages_message(&changed);
+        shared.broadcast(&message);
+        self.document = Some(document);
+    }
+
+    /// Handle a click of a preview at a point on a page.
+    pub fn click(&self, world: &SystemWorld, client: usize, page: usize, click: Point) {
+        let Some(document) = &self.document else { return };
+        let Some(frame) = document.pages.get(page) else { return };
+
+        let message = match jump_from_click(world, &document.pages, frame, click) {
+            // Show where the clicked content is defined.
+            Some(Jump::Source(id, offset)) => {
+                let Ok(source) = world.source(id) else { return };
+                let path = match id.package() {
+                    Some(spec) => eco_format!("{spec}{}", id.path().display()),
+                    None => match world.root().join_rooted(id.path()) {
+                        Some(path) => eco_format!("{}", path.display()),
+                        None => return,
+                    },
+                };
+
+                let line = source.byte_to_line(offset).unwrap_or_default() + 1;
+                let column = source.byte_to_column(offset).unwrap_or_default() + 1;
+                json!({
+                    "type": "source",
+                    "path": path.as_str(),
+                    "line": line,
+                    "column": column,
+                })
+            }
+            Some(Jump::Url(url)) => json!({ "type": "url", "url": url.as_str() }),
+            Some(Jump::Position(position)) => json!({
+                "type": "position",
+                "page": position.page.get() - 1,
+                "x": position.point.x.to_pt(),
+                "y": position.point.y.to_pt(),
+            }),
+            None => return,
+        };
+
+        self.shared.lock().unwrap().send(client, &message);
+    }
+}
+
+impl Shared {
+    /// Create a message with the number of pages and the given pages' SVGs.
+    fn pages_message(&self, indices: &[usize]) -> Value {
+        let pages: Vec<_> = indices
+            .iter()
+            .map(|&index| json!({ "index": index, "svg": self.pages[index].svg }))
+            .collect();
+        json!({ "type": "pages", "count": self.pages.len(), "pages": pages })
+    }
+
+    /// Send a message to all open previews and drop those that disconnected.
+    fn broadcast(&mut self, message: &Value) {
+        let text = message.to_string().into_bytes();
+        self.clients
+            .retain(|_, queue| queue.send((TEXT, text.clone())).is_ok());
+    }
+
+    /// Send a message to one preview and drop it if it disconnected.
+    fn send(&mut self, id: usize, message: &Value) {
+        let Some(queue) = self.clients.get(&id) else { return };
+        if queue.send((TEXT, message.to_string().into_bytes())).is_err() {
+            self.clients.remove(&id);
+        }
+    }
+}
+
+/// Respond to an HTTP request.
+fn respond(
+    mut stream: TcpStream,
+    shared: &Mutex<Shared>,
+    trigger: &Sender<Trigger>,
+) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    // Read the request line and the headers, of which only the WebSocket key
+    // is of interest.
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let target = parts.next().unwrap_or_default();
+    let path = target.split('?').next().unwrap_or_default();
+
+    let mut key = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            break;
+        }
+
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("sec-websocket-key") {
+                key = Some(value.trim().to_owned());
+            }
+        }
+    }
+
+    if method != "GET" {
+        return write_response(&mut stream, "405 Method Not Allowed", "text/plain", b"");
+    }
+
+    if path == "/" {
+        return write_response(
+            &mut stream,
+            "200 OK",
+            "text/html; charset=utf-8",
+            PREVIEW_HTML.as_bytes(),
+        );
+    }
+
+    if path == "/ws" {
+        return match key {
+            Some(key) => connect(stream, reader, &key, shared, trigger),
+            None => write_response(&mut stream, "400 Bad Request", "text/plain", b""),
+        };
+    }
+
+    // Serve single pages at `/page/{n}.svg` and `/page/{n}.png`, starting at
+    // one.
+    let page = path.strip_prefix("/page/").and_then(|file| file.split_once('.'));
+    if let Some((number, extension)) = page {
+        let index = number.parse::<usize>().ok().and_then(|n| n.checked_sub(1));
+        match (index, extension) {
+            (Some(index), "svg") => {
+                let svg =
+                    shared.lock().unwrap().pages.get(index).map(|page| page.svg.clone());
+                if let Some(svg) = svg {
+                    let body = svg.as_bytes();
+                    return write_response(&mut stream, "200 OK", "image/svg+xml", body);
+                }
+            }
+            (Some(index), "png") => {
+                if let Some(png) = render_png(shared, index) {
+                    return write_response(&mut stream, "200 OK", "image/png", &png);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    write_response(&mut stream, "404 Not Found", "text/plain", b"")
+}
+
+/// Render a page as PNG, or reuse its last rendering if it didn't change.
+///
+/// The rendering happens outside of the lock so that it doesn't hold up
+/// updates and other requests.
+fn render_png(shared: &Mutex<Shared>, index: usize) -> Option<Arc<Vec<u8>>> {
+    let (hash, frame, pixel_per_pt) = {
+        let shared = shared.lock().unwrap();
+        let page = shared.pages.get(index)?;
+        if let Some(png) = &page.png {
+            return Some(Arc::clone(png));
+        }
+        (page.hash, page.frame.clone(), shared.pixel_per_pt)
+    };
+
+    let png = typst::export::render(&frame, pixel_per_pt, Color::WHITE)
+        .encode_png()
+        .ok()?;
+    let png = Arc::new(png);
+
+    // Only keep the rendering if the page didn't change in the meantime.
+    let mut shared = shared.lock().unwrap();
+    if let Some(page) = shared.pages.get_mut(index).filter(|page| page.hash == hash) {
+        page.png = Some(Arc::clone(&png));
+    }
+
+    Some(png)
+}
+
+/// Write an HTTP response.
+fn write_response(
+    stream: &mut TcpStream,
+    status: &str,
+    content_type: &str,
+    body: &[u8],
+) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\n\
+         Content-Type: {content_type}\r\n\
+         Content-Length: {}\r\n\
+         Cache-Control: no-cache\r\n\
+         Connection: close\r\n\r\n",
+        body.len(),
+    )?;
+    stream.write_all(body)?;
+    stream.flush()
+}
+
+/// Upgrade a connection to a WebSocket and serve a preview over it until the
+/// browser disconnects.
+fn connect(
+    mut stream: TcpStream,
+    mut reader: BufReader<TcpStream>,
+    key: &str,
+    shared: &Mutex<Shared>,
+    trigger: &Sender<Trigger>,
+) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 101 Switching Protocols\r\n\
+         Upgrade: websocket\r\n\
+         Connection: Upgrade\r\n\
+         Sec-WebSocket-Accept: {}\r\n\r\n",
+        accept_key(key),
+    )?;
+
+    // A preview that doesn't read its frames for too long is disconnected.
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+
+    // Register the preview for updates, starting with all pages.
+    let (queue, frames) = mpsc::channel::<(u8, Vec<u8>)>();
+    let id = {
+        let mut shared = shared.lock().unwrap();
+        let indices: Vec<_> = (0..shared.pages.len()).collect();
+        let message = shared.pages_message(&indices).to_string();
+        queue.send((TEXT, message.into_bytes())).ok();
+
+        let id = shared.next_id;
+        shared.next_id += 1;
+        shared.clients.insert(id, queue.clone());
+        id
+    };
+
+    // Write the queued frames until the preview disconnects. Shutting the
+    // stream down on failure also ends the reading loop below.
+    thread::spawn(move || {
+        for (opcode, payload) in frames {
+            if write_frame(&mut stream, opcode, &payload).is_err() {
+                break;
+            }
+        }
+        stream.shutdown(Shutdown::Both).ok();
+    });
+
+    // Replies go through the queue so that they don't interleave with
+    // updates.
+    let reply = |opcode, payload: Vec<u8>| {
+        queue
+            .send((opcode, payload))
+            .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))
+    };
+
+    let result = loop {
+        let (opcode, payload) = match read_frame(&mut reader) {
+            Ok(frame) => frame,
+            Err(err) => break Err(err),
+        };
+
+        match opcode {
+            TEXT => {
+                let Some((page, click)) = parse_click(&payload) else { continue };
+                if trigger.send(Trigger::Click(id, page, click)).is_err() {
+                    break Ok(());
+                }
+            }
+            PING => {
+                if let Err(err) = reply(PONG, payload) {
+                    break Err(err);
+                }
+            }
+            CLOSE => break reply(CLOSE, payload),
+            _ => {}
+        }
+    };
+
+    shared.lock().unwrap().clients.remove(&id);
+    result
+}
+
+/// Parse a click on a page sent by a preview.
+fn parse_click(payload: &[u8]) -> Option<(usize, Point)> {
+    let click: Value = serde_json::from_slice(payload).ok()?;
+    let page = usize::try_from(click["page"].as_u64()?).ok()?;
+    let x = Abs::pt(click["x"].as_f64()?);
+    let y = Abs::pt(click["y"].as_f64()?);
+    Some((page, Point::new(x, y)))
+}
+
+/// Read a WebSocket frame and return its opcode and unmasked payload.
+fn read_frame(reader: &mut impl Read) -> io::Result<(u8, Vec<u8>)> {
+    let mut head = [0; 2];
+    reader.read_exact(&mut head)?;
+    let opcode = head[0] & 0x0F;
+    let masked = head[1] & 0x80 != 0;
+
+    let len = match head[1] & 0x7F {
+        126 => {
+            let mut buf = [0; 2];
+            reader.read_exact(&mut buf)?;
+            u64::from(u16::from_be_bytes(buf))
+        }
+        127 => {
+            let mut buf = [0; 8];
+            reader.read_exact(&mut buf)?;
+            u64::from_be_bytes(buf)
+        }
+        len => u64::from(len),
+    };
+
+    if len > MAX_MESSAGE_LEN {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "message is too large"));
+    }
+
+    let mut mask = [0; 4];
+    if masked {
+        reader.read_exact(&mut mask)?;
+    }
+
+    let mut payload = vec![0; len as usize];
+    reader.read_exact(&mut payload)?;
+    for (i, byte) in payload.iter_mut().enumerate() {
+        *byte ^= mask[i % 4];
+    }
+
+    Ok((opcode, payload))
+}
+
+/// Write an unfragmented, unmasked WebSocket frame.
+fn write_frame(stream: &mut impl Write, opcode: u8, payload: &[u8]) -> io::Result<()> {
+    let mut frame = vec![0x80 | opcode];
+    match payload.len() {
+        len @ 0..=125 => frame.push(len as u8),
+        len @ 126..=0xFFFF => {
+            frame.push(126);
+            frame.extend((len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(127);
+            frame.extend((len as u64).to_be_bytes());
+        }
+    }
+
+    frame.extend_from_slice(payload);
+    stream.write_all(&frame)?;
+    stream.flush()
+}
+
+/// Compute the key with which the server accepts a WebSocket handshake.
+fn accept_key(key: &str) -> String {
+    let hash = sha1_smol::Sha1::from(format!("{key}{WEBSOCKET_GUID}")).digest();
+    base64::engine::general_purpose::STANDARD.encode(hash.bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accept_key() {
+        // The example handshake from RFC 6455, section 1.3.
+        assert_eq!(
+            accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+    }
+}
@@ -16,7 +16,7 @@ use crate::args::{CliArguments, Command};
 pub fn setup_tracing(args: &CliArguments) -> io::Result<Option<impl Drop>> {
     let flamegraph = match &args.command {
         Command::Compile(command) => command.flamegraph.as_ref(),
-        Command::Watch(command) if command.flamegraph.is_some() => {
+        Command::Watch(command) if command.compile.flamegraph.is_some() => {
             return Err(io::Error::new(
                 io::ErrorKind::InvalidInput,
                 "cannot use --flamegraph with watch command",
@@ -30,13 +30,18 @@ pub fn setup_tracing(args: &CliArguments) -> io::Result<Option<impl Drop>> {
         tracing_subscriber::fmt()
             .without_time()
             .with_max_level(level_filter(args))
+            .with_writer(io::stderr)
             .init();
 
         return Ok(None);
     }
 
-    // Build the FMT layer printing to the console.
-    let fmt_layer = fmt::Layer::default().without_time().with_filter(level_filter(args));
+    // Build the FMT layer printing to the console. It writes to stderr to keep
+    // stdout free for the language server's messages.
+    let fmt_layer = fmt::Layer::default()
+        .without_time()
+        .with_writer(io::stderr)
+        .with_filter(level_filter(args));
 
     // Error layer for building backtraces
     let error_layer = ErrorLayer::default();
@@ -1,5 +1,6 @@
 use std::collections::HashSet;
 use std::io::{self, IsTerminal, Write};
+use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
 
 use codespan_reporting::term::{self, termcolor};
@@ -8,24 +9,42 @@ use same_file::is_same_file;
 use termcolor::WriteColor;
 use typst::diag::StrResult;
 use typst::eval::eco_format;
+use typst::geom::Point;
 
-use crate::args::CompileCommand;
+use crate::args::{CompileCommand, WatchCommand};
 use crate::color_stream;
 use crate::compile::compile_once;
+use crate::serve::Preview;
 use crate::world::SystemWorld;
 
 /// Execute a watching compilation command.
-pub fn watch(mut command: CompileCommand) -> StrResult<()> {
+pub fn watch(command: WatchCommand) -> StrResult<()> {
+    let WatchCommand { compile: mut command, serve } = command;
+    let watching = Watching { serve };
+
     // Create the world that serves sources, files, and fonts.
-    let mut world = SystemWorld::new(&command)?;
+    let mut world = SystemWorld::new(&command.common)?;
+
+    // Start the preview server if requested.
+    let (tx, rx) = std::sync::mpsc::channel();
+    let mut preview = serve
+        .map(|addr| Preview::serve(addr, command.ppi, tx.clone()))
+        .transpose()?;
 
     // Perform initial compilation.
-    compile_once(&mut world, &mut command, true)?;
+    let document = compile_once(&mut world, &mut command, Some(watching))?;
+    if let (Some(preview), Some(document)) = (&mut preview, document) {
+        preview.update(document);
+    }
 
     // Setup file watching.
-    let (tx, rx) = std::sync::mpsc::channel();
-    let mut watcher = RecommendedWatcher::new(tx, notify::Config::default())
-        .map_err(|_| "failed to setup file watching")?;
+    let mut watcher = RecommendedWatcher::new(
+        move |event| {
+            tx.send(Trigger::File(event)).ok();
+        },
+        notify::Config::default(),
+    )
+    .map_err(|_| "failed to setup file watching")?;
 
     // Watch all the files that are used by the input file and its dependencies.
     watch_dependencies(&mut world, &mut watcher, HashSet::new())?;
@@ -36,12 +55,20 @@ pub fn watch(mut command: CompileCommand) -> StrResult<()> {
     loop {
         let mut removed = HashSet::new();
         let mut recompile = false;
-        for event in rx
+        for trigger in rx
             .recv()
             .into_iter()
             .chain(std::iter::from_fn(|| rx.recv_timeout(timeout).ok()))
         {
-            let event = event.map_err(|_| "failed to watch directory")?;
+            let event = match trigger {
+                Trigger::File(event) => event.map_err(|_| "failed to watch directory")?,
+                Trigger::Click(client, page, click) => {
+                    if let Some(preview) = &preview {
+                        preview.click(&world, client, page, click);
+                    }
+                    continue;
+                }
+            };
 
             // Workaround for notify-rs' implicit unwatch on remove/rename
             // (triggered by some editors when saving files) with the inotify
@@ -70,7 +97,10 @@ pub fn watch(mut command: CompileCommand) -> StrResult<()> {
                 .collect();
 
             // Recompile.
-            compile_once(&mut world, &mut command, true)?;
+            let document = compile_once(&mut world, &mut command, Some(watching))?;
+            if let (Some(preview), Some(document)) = (&mut preview, document) {
+                preview.update(document);
+            }
             comemo::evict(10);
 
             // Adjust the watching.
@@ -79,6 +109,14 @@ pub fn watch(mut command: CompileCommand) -> StrResult<()> {
     }
 }
 
+/// Something that happened while watching.
+pub enum Trigger {
+    /// A watched file changed.
+    File(notify::Result<notify::Event>),
+    /// A preview, identified by its id, was clicked at a point on a page.
+    Click(usize, usize, Point),
+}
+
 /// Adjust the file watching. Watches all new dependencies and unwatches
 /// all `previous` dependencies that are not relevant anymore.
 #[tracing::instrument(skip_all)]
@@ -134,6 +172,13 @@ fn is_event_relevant(event: &notify::Event, output: &Path) -> bool {
     }
 }
 
+/// What a watching compilation shows along with its status.
+#[derive(Debug, Copy, Clone)]
+pub struct Watching {
+    /// The address at which the preview is served, if it is.
+    pub serve: Option<SocketAddr>,
+}
+
 /// The status in which the watcher can be.
 pub enum Status {
     Compiling,
@@ -144,7 +189,7 @@ pub enum Status {
 
 impl Status {
     /// Clear the terminal and render the status message.
-    pub fn print(&self, command: &CompileCommand) -> io::Result<()> {
+    pub fn print(&self, command: &CompileCommand, watching: Watching) -> io::Result<()> {
         let output = command.output();
         let timestamp = chrono::offset::Local::now().format("%H:%M:%S");
         let color = self.color();
@@ -159,13 +204,20 @@ impl Status {
         w.set_color(&color)?;
         write!(w, "watching")?;
         w.reset()?;
-        writeln!(w, " {}", command.input.display())?;
+        writeln!(w, " {}", command.common.input.display())?;
 
         w.set_color(&color)?;
         write!(w, "writing to")?;
         w.reset()?;
         writeln!(w, " {}", output.display())?;
 
+        if let Some(addr) = watching.serve {
+            w.set_color(&color)?;
+            write!(w, "serving at")?;
+            w.reset()?;
+            writeln!(w, " http://{addr}")?;
+        }
+
         writeln!(w)?;
         writeln!(w, "[{timestamp}] {}", self.message())?;
         writeln!(w)?;
@@ -9,13 +9,13 @@ use comemo::Prehashed;
 use same_file::Handle;
 use siphasher::sip128::{Hasher128, SipHasher13};
 use typst::diag::{FileError, FileResult, StrResult};
-use typst::eval::{eco_format, Datetime, Library};
+use typst::eval::{eco_format, Datetime, Dict, Library, Value};
 use typst::font::{Font, FontBook};
 use typst::syntax::{FileId, Source};
 use typst::util::{Bytes, PathExt};
 use typst::World;
 
-use crate::args::CompileCommand;
+use crate::args::{SharedArgs, WorldArgs};
 use crate::fonts::{FontSearcher, FontSlot};
 use crate::package::prepare_package;
 
@@ -44,10 +44,7 @@ pub struct SystemWorld {
 
 impl SystemWorld {
     /// Create a new system world.
-    pub fn new(command: &CompileCommand) -> StrResult<Self> {
-        let mut searcher = FontSearcher::new();
-        searcher.search(&command.font_paths);
-
+    pub fn new(command: &SharedArgs) -> StrResult<Self> {
         // Resolve the system-global input path.
         let system_input = command.input.canonicalize().map_err(|_| {
             eco_format!("input file not found (searched at {})", command.input.display())
@@ -56,6 +53,7 @@ impl SystemWorld {
         // Resolve the system-global root directory.
         let root = {
             let path = command
+                .world
                 .root
                 .as_deref()
                 .or_else(|| system_input.parent())
@@ -71,16 +69,38 @@ impl SystemWorld {
             .map(|path| Path::new("/").join(path))
             .map_err(|_| "input file must be contained in project root")?;
 
-        Ok(Self {
+        let main = FileId::new(None, &project_input);
+        Ok(Self::with_main(root, main, &command.world))
+    }
+
+    /// Create a system world for a canonical project root and a main file
+    /// within it.
+    pub fn with_main(root: PathBuf, main: FileId, args: &WorldArgs) -> Self {
+        let mut searcher = FontSearcher::new();
+        searcher.search(&args.font_paths);
+
+        // Collect the inputs that are passed to the document.
+        let inputs: Dict = args
+            .inputs
+            .iter()
+            .map(|(key, value)| (key.as_str().into(), Value::Str(value.as_str().into())))
+            .collect();
+
+        Self {
             root,
-            main: FileId::new(None, &project_input),
-            library: Prehashed::new(typst_library::build()),
+            main,
+            library: Prehashed::new(typst_library::build_with_inputs(inputs)),
             book: Prehashed::new(searcher.book),
             fonts: searcher.fonts,
             hashes: RefCell::default(),
             paths: RefCell::default(),
             today: OnceCell::new(),
-        })
+        }
+    }
+
+    /// The root relative to which absolute paths are resolved.
+    pub fn root(&self) -> &Path {
+        &self.root
     }
 
     /// The id of the main source file.
@@ -88,6 +108,11 @@ impl SystemWorld {
         self.main
     }
 
+    /// Change the main source file.
+    pub fn set_main(&mut self, main: FileId) {
+        self.main = main;
+    }
+
     /// Return all paths the last compilation depended on.
     pub fn dependencies(&mut self) -> impl Iterator<Item = &Path> {
         self.paths.get_mut().values().map(|slot| slot.system_path.as_path())
@@ -134,9 +159,14 @@ impl World for SystemWorld {
 
     fn today(&self, offset: Option<i64>) -> Option<Datetime> {
         *self.today.get_or_init(|| {
-            let naive = match offset {
-                None => chrono::Local::now().naive_local(),
-                Some(o) => (chrono::Utc::now() + chrono::Duration::hours(o)).naive_utc(),
+            let naive = match (source_date_epoch(), offset) {
+                (Some(now), o) => {
+                    (now + chrono::Duration::hours(o.unwrap_or(0))).naive_utc()
+                }
+                (None, None) => chrono::Local::now().naive_local(),
+                (None, Some(o)) => {
+                    (chrono::Utc::now() + chrono::Duration::hours(o)).naive_utc()
+                }
             };
 
             Datetime::from_ymd(
@@ -148,6 +178,16 @@ impl World for SystemWorld {
     }
 }
 
+/// The time given by the `SOURCE_DATE_EPOCH` environment variable in seconds
+/// since the Unix epoch.
+///
+/// Build systems set it to get reproducible output, so it is used instead of
+/// the current time, in UTC.
+fn source_date_epoch() -> Option<chrono::DateTime<chrono::Utc>> {
+    let seconds = std::env::var("SOURCE_DATE_EPOCH").ok()?.trim().parse().ok()?;
+    chrono::TimeZone::timestamp_opt(&chrono::Utc, seconds, 0).single()
+}
+
 impl SystemWorld {
     /// Access the canonical slot for the given file id.
     #[tracing::instrument(skip_all)]
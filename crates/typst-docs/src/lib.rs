@@ -40,11 +40,14 @@ static FONTS: Lazy<(Prehashed<FontBook>, Vec<Font>)> = Lazy::new(|| {
 
 static LIBRARY: Lazy<Prehashed<Library>> = Lazy::new(|| {
     let mut lib = typst_library::build();
-    // Hack for documenting the `mix` function in the color module.
+    // Hack for documenting the `mix` function in the color module and the
+    // constructors in the gradient module.
     // Will be superseded by proper associated functions.
-    lib.global
-        .scope_mut()
-        .define("mix", typst_library::compute::mix_func());
+    let scope = lib.global.scope_mut();
+    scope.define("mix", typst_library::compute::mix_func());
+    scope.define("linear", typst_library::compute::linear_func());
+    scope.define("radial", typst_library::compute::radial_func());
+    scope.define("conic", typst_library::compute::conic_func());
     lib.styles
         .set(PageElem::set_width(Smart::Custom(Abs::pt(240.0).into())));
     lib.styles.set(PageElem::set_height(Smart::Auto));
@@ -244,13 +247,27 @@ fn category_page(resolver: &dyn Resolver, category: &str) -> PageModel {
         _ => &[],
     };
 
+    // Modules whose functions belong to this category, but which live
+    // outside of the focused scope.
+    let submodules: &[&'static str] = match category {
+        "meta" => &["form", "html", "pdf"],
+        _ => &[],
+    };
+
     let grouped = match category {
         "math" => GROUPS.as_slice(),
         _ => &[],
     };
 
     // Add functions.
-    for (_, value) in focus.scope().iter() {
+    let scopes = std::iter::once((focus, parents)).chain(submodules.iter().map(|name| {
+        let module = module(&LIBRARY.global, name).unwrap();
+        (module, std::slice::from_ref(name))
+    }));
+
+    for (value, parents) in scopes.flat_map(|(module, parents)| {
+        module.scope().iter().map(move |(_, value)| (value, parents))
+    }) {
         let Value::Func(func) = value else { continue };
         let Some(info) = func.info() else { continue };
         if info.category != category {
@@ -960,6 +977,8 @@ const TYPE_ORDER: &[&str] = &[
     "relative length",
     "fraction",
     "color",
+    "gradient",
+    "pattern",
     "datetime",
     "string",
     "regex",
@@ -367,6 +367,49 @@ pub fn cmyk(
     CmykColor::new(cyan.0, magenta.0, yellow.0, key.0).into()
 }
 
+/// Creates a spot color.
+///
+/// Spot colors are premixed inks, like the colors of a brand, that a printer
+/// applies on a plate of their own instead of mixing them from the process
+/// colors. In PDF export, they are kept as separate inks. Other export formats
+/// and viewers without the ink show the alternate color instead.
+///
+/// Lightening and darkening a spot color changes its tint, that is how much
+/// of the ink is applied.
+///
+/// ## Example { #example }
+/// ```example
+/// #let brand = color.spot("Brand Blue", cmyk(100%, 60%, 0%, 10%))
+/// #square(fill: brand)
+/// #square(fill: color.spot(
+///   "Brand Blue",
+///   cmyk(100%, 60%, 0%, 10%),
+///   tint: 40%,
+/// ))
+/// ```
+///
+/// _Note:_ This function must be specified as `color.spot`, not just `spot`.
+///
+/// Display: Spot
+/// Category: construct
+#[func]
+pub fn spot(
+    /// The name of the ink, which the printer uses to identify it.
+    name: Spanned<EcoString>,
+    /// An RGB or CMYK color that approximates the ink at full strength.
+    alternate: SpotAlternate,
+    /// How much of the ink is applied.
+    #[named]
+    #[default(RatioComponent(u8::MAX))]
+    tint: RatioComponent,
+) -> SourceResult<Color> {
+    if name.v.is_empty() {
+        bail!(name.span, "spot color name must not be empty");
+    }
+
+    Ok(SpotColor::new(name.v, alternate, tint.0).into())
+}
+
 /// A component that must be a ratio.
 pub struct RatioComponent(u8);
 
@@ -383,6 +426,7 @@ cast! {
 pub fn color_module() -> Module {
     let mut scope = Scope::new();
     scope.define("mix", mix_func());
+    scope.define("spot", spot_func());
     Module::new("color").with_scope(scope)
 }
 
@@ -417,6 +461,138 @@ pub fn mix(
     Color::mix(colors, space)
 }
 
+/// A module with functions for creating gradients.
+pub fn gradient_module() -> Module {
+    let mut scope = Scope::new();
+    scope.define("linear", linear_func());
+    scope.define("radial", radial_func());
+    scope.define("conic", conic_func());
+    Module::new("gradient").with_scope(scope)
+}
+
+/// Create a linear gradient.
+///
+/// A linear gradient transitions between its colors along a straight line
+/// through the center of the filled element. Gradients can be used wherever
+/// a color is accepted, e.g. as the fill of a shape, as a stroke or as the
+/// fill of text. They are always relative to the bounding box of the
+/// element they are applied to.
+///
+/// ## Example { #example }
+/// ```example
+/// #rect(width: 100%, fill: gradient.linear(red, blue))
+/// #rect(width: 100%, fill: gradient.linear(
+///   (red, 0%), (yellow, 30%), (blue, 100%),
+///   angle: 45deg,
+/// ))
+/// #text(fill: gradient.linear(red, blue))[Gradient text]
+/// ```
+///
+/// _Note:_ This function must be specified as `gradient.linear`.
+///
+/// Display: Linear Gradient
+/// Category: construct
+#[func]
+pub fn linear(
+    /// The color stops of the gradient. Each stop is either a color or a
+    /// pair (array of length two) of a color and an offset (ratio). Either
+    /// all stops or none of them must have offsets. Stops without offsets
+    /// are distributed evenly.
+    #[variadic]
+    stops: Vec<GradientStop>,
+    /// The direction of the gradient. An angle of `{0deg}` goes from left
+    /// to right and `{90deg}` goes from top to bottom.
+    #[named]
+    #[default(Angle::zero())]
+    angle: Angle,
+    /// The color space to interpolate in. By default, this happens in a
+    /// perceptual color space (Oklab).
+    #[named]
+    #[default(ColorSpace::Oklab)]
+    space: ColorSpace,
+) -> StrResult<Gradient> {
+    Gradient::linear(stops, angle, space)
+}
+
+/// Create a radial gradient.
+///
+/// A radial gradient transitions between its colors in circles around its
+/// center. The center and radius are relative to the bounding box of the
+/// filled element, so the circles become ellipses on non-square elements.
+///
+/// ## Example { #example }
+/// ```example
+/// #circle(radius: 20pt, fill: gradient.radial(white, blue))
+/// #rect(fill: gradient.radial(
+///   red, yellow,
+///   center: (30%, 30%),
+///   radius: 70%,
+/// ))
+/// ```
+///
+/// _Note:_ This function must be specified as `gradient.radial`.
+///
+/// Display: Radial Gradient
+/// Category: construct
+#[func]
+pub fn radial(
+    /// The color stops of the gradient. See the
+    /// [linear gradient]($func/linear) for details.
+    #[variadic]
+    stops: Vec<GradientStop>,
+    /// The center of the circles, relative to the bounding box.
+    #[named]
+    #[default(Axes::splat(Ratio::new(0.5)))]
+    center: Axes<Ratio>,
+    /// The radius of the last circle, relative to the bounding box.
+    #[named]
+    #[default(Ratio::new(0.5))]
+    radius: Ratio,
+    /// The color space to interpolate in.
+    #[named]
+    #[default(ColorSpace::Oklab)]
+    space: ColorSpace,
+) -> StrResult<Gradient> {
+    Gradient::radial(stops, center, radius, space)
+}
+
+/// Create a conic gradient.
+///
+/// A conic gradient transitions between its colors by rotating around its
+/// center, starting at the given angle and going clockwise.
+///
+/// ## Example { #example }
+/// ```example
+/// #circle(radius: 20pt, fill: gradient.conic(red, yellow, green, blue, red))
+/// ```
+///
+/// _Note:_ This function must be specified as `gradient.conic`.
+///
+/// Display: Conic Gradient
+/// Category: construct
+#[func]
+pub fn conic(
+    /// The color stops of the gradient. See the
+    /// [linear gradient]($func/linear) for details.
+    #[variadic]
+    stops: Vec<GradientStop>,
+    /// The center of the rotation, relative to the bounding box.
+    #[named]
+    #[default(Axes::splat(Ratio::new(0.5)))]
+    center: Axes<Ratio>,
+    /// The angle at which the gradient starts. An angle of `{0deg}` starts
+    /// on the right of the center.
+    #[named]
+    #[default(Angle::zero())]
+    angle: Angle,
+    /// The color space to interpolate in.
+    #[named]
+    #[default(ColorSpace::Oklab)]
+    space: ColorSpace,
+) -> StrResult<Gradient> {
+    Gradient::conic(stops, center, angle, space)
+}
+
 /// Creates a custom symbol with modifiers.
 ///
 /// ## Example { #example }
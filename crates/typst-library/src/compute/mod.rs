@@ -24,6 +24,7 @@ pub(super) fn define(global: &mut Scope) {
     global.define("rgb", rgb_func());
     global.define("cmyk", cmyk_func());
     global.define("color", color_module());
+    global.define("gradient", gradient_module());
     global.define("datetime", datetime_func());
     global.define("symbol", symbol_func());
     global.define("str", str_func());
@@ -0,0 +1,157 @@
+use super::{boxed, layout_field};
+use crate::prelude::*;
+use crate::text::TextElem;
+
+/// A checkbox of an interactive form.
+///
+/// In PDF export, the reader can check and uncheck the box. Other export
+/// formats show its initial state.
+///
+/// ## Example { #example }
+/// ```example
+/// #form.checkbox("terms") I accept the terms \
+/// #form.checkbox("news", checked: true) Send me news
+/// ```
+///
+/// Display: Checkbox
+/// Category: meta
+#[element(Show, Layout)]
+pub struct CheckboxElem {
+    /// The name of the field, which identifies its value in the filled-in
+    /// form.
+    #[required]
+    pub name: EcoString,
+
+    /// Whether the box is initially checked.
+    #[default(false)]
+    pub checked: bool,
+
+    /// The width and height of the box.
+    #[resolve]
+    #[default(Em::new(0.9).into())]
+    pub size: Length,
+
+    /// The box's background color. See the
+    /// [rectangle's documentation]($func/rect.fill) for more details.
+    pub fill: Option<Paint>,
+
+    /// The box's border and the stroke of its check mark. See the
+    /// [rectangle's documentation]($func/rect.stroke) for more details.
+    #[resolve]
+    #[fold]
+    #[default(Some(PartialStroke::default()))]
+    pub stroke: Option<PartialStroke>,
+}
+
+impl Show for CheckboxElem {
+    #[tracing::instrument(name = "CheckboxElem::show", skip_all)]
+    fn show(&self, _: &mut Vt, _: StyleChain) -> SourceResult<Content> {
+        Ok(boxed(self.clone().pack(), Self::func()))
+    }
+}
+
+impl Layout for CheckboxElem {
+    #[tracing::instrument(name = "CheckboxElem::layout", skip_all)]
+    fn layout(
+        &self,
+        vt: &mut Vt,
+        styles: StyleChain,
+        _: Regions,
+    ) -> SourceResult<Fragment> {
+        let field = FormField {
+            name: self.name(),
+            kind: FormFieldKind::Checkbox { checked: self.checked(styles) },
+            fill: self.fill(styles),
+            stroke: self.stroke(styles).map(PartialStroke::unwrap_or_default),
+            text_size: TextElem::size_in(styles),
+            font: None,
+            span: self.span(),
+        };
+
+        layout_field(vt, styles, field, Size::splat(self.size(styles)))
+    }
+}
+
+/// A radio button of an interactive form.
+///
+/// Radio buttons with the same name form a group in which at most one button
+/// is selected. The group's value is the value of its selected button. In PDF
+/// export, the reader can select a different button. Other export formats
+/// show the initial selection.
+///
+/// ## Example { #example }
+/// ```example
+/// #form.radio("size", "s") Small \
+/// #form.radio("size", "m", checked: true) Medium \
+/// #form.radio("size", "l") Large
+/// ```
+///
+/// Display: Radio Button
+/// Category: meta
+#[element(Show, Layout)]
+pub struct RadioElem {
+    /// The name of the group the button belongs to.
+    #[required]
+    pub name: EcoString,
+
+    /// The value the group takes when this button is selected.
+    #[required]
+    pub value: EcoString,
+
+    /// Whether the button is initially selected. If multiple buttons of a
+    /// group are, the first one is selected.
+    #[default(false)]
+    pub checked: bool,
+
+    /// The diameter of the button.
+    #[resolve]
+    #[default(Em::new(0.9).into())]
+    pub size: Length,
+
+    /// The button's background color. See the
+    /// [rectangle's documentation]($func/rect.fill) for more details.
+    pub fill: Option<Paint>,
+
+    /// The button's border. Its paint is also used for the dot of a selected
+    /// button. See the [rectangle's documentation]($func/rect.stroke) for
+    /// more details.
+    #[resolve]
+    #[fold]
+    #[default(Some(PartialStroke::default()))]
+    pub stroke: Option<PartialStroke>,
+}
+
+impl Show for RadioElem {
+    #[tracing::instrument(name = "RadioElem::show", skip_all)]
+    fn show(&self, _: &mut Vt, _: StyleChain) -> SourceResult<Content> {
+        Ok(boxed(self.clone().pack(), Self::func()))
+    }
+}
+
+impl Layout for RadioElem {
+    #[tracing::instrument(name = "RadioElem::layout", skip_all)]
+    fn layout(
+        &self,
+        vt: &mut Vt,
+        styles: StyleChain,
+        _: Regions,
+    ) -> SourceResult<Fragment> {
+        // In PDF, `Off` names the appearance of a button that isn't selected.
+        let value = self.value();
+        if value == "Off" {
+            bail!(self.span(), "radio button value must not be `Off`");
+        }
+
+        let field = FormField {
+            name: self.name(),
+            kind: FormFieldKind::Radio { value, checked: self.checked(styles) },
+            fill: self.fill(styles),
+            stroke: self.stroke(styles).map(PartialStroke::unwrap_or_default),
+            text_size: TextElem::size_in(styles),
+            font: None,
+            span: self.span(),
+        };
+
+        layout_field(vt, styles, field, Size::splat(self.size(styles)))
+    }
+}
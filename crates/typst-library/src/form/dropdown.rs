@@ -0,0 +1,96 @@
+use super::{boxed, layout_field};
+use crate::prelude::*;
+use crate::text::TextElem;
+
+/// A dropdown of an interactive form, from which one of multiple options can
+/// be selected.
+///
+/// In PDF export, the reader can select a different option. Other export
+/// formats show the initially selected option in a box.
+///
+/// ## Example { #example }
+/// ```example
+/// Country: #form.dropdown(
+///   "country",
+///   ("Germany", "France", "Italy"),
+///   selected: "France",
+/// )
+/// ```
+///
+/// Display: Dropdown
+/// Category: meta
+#[element(Show, Layout)]
+pub struct DropdownElem {
+    /// The name of the field, which identifies its value in the filled-in
+    /// form.
+    #[required]
+    pub name: EcoString,
+
+    /// The options to choose from.
+    #[required]
+    pub options: Vec<EcoString>,
+
+    /// The initially selected option. Must be one of the `options`.
+    pub selected: Option<EcoString>,
+
+    /// The field's width, relative to its parent container.
+    #[resolve]
+    #[default(Em::new(8.0).into())]
+    pub width: Rel<Length>,
+
+    /// The field's height, relative to its parent container.
+    #[resolve]
+    #[default(Em::new(1.4).into())]
+    pub height: Rel<Length>,
+
+    /// The field's background color. See the
+    /// [rectangle's documentation]($func/rect.fill) for more details.
+    pub fill: Option<Paint>,
+
+    /// The field's border. See the
+    /// [rectangle's documentation]($func/rect.stroke) for more details.
+    #[resolve]
+    #[fold]
+    #[default(Some(PartialStroke::default()))]
+    pub stroke: Option<PartialStroke>,
+}
+
+impl Show for DropdownElem {
+    #[tracing::instrument(name = "DropdownElem::show", skip_all)]
+    fn show(&self, _: &mut Vt, _: StyleChain) -> SourceResult<Content> {
+        Ok(boxed(self.clone().pack(), Self::func()))
+    }
+}
+
+impl Layout for DropdownElem {
+    #[tracing::instrument(name = "DropdownElem::layout", skip_all)]
+    fn layout(
+        &self,
+        vt: &mut Vt,
+        styles: StyleChain,
+        regions: Regions,
+    ) -> SourceResult<Fragment> {
+        let options = self.options();
+        let selected = self.selected(styles);
+        if let Some(selected) = &selected {
+            if !options.contains(selected) {
+                bail!(self.span(), "selected option is not one of the options");
+            }
+        }
+
+        let field = FormField {
+            name: self.name(),
+            kind: FormFieldKind::Dropdown { options, selected },
+            fill: self.fill(styles),
+            stroke: self.stroke(styles).map(PartialStroke::unwrap_or_default),
+            text_size: TextElem::size_in(styles),
+            font: None,
+            span: self.span(),
+        };
+
+        let size = Axes::new(self.width(styles), self.height(styles))
+            .zip(regions.base())
+            .map(|(s, b)| s.relative_to(b));
+        layout_field(vt, styles, field, size)
+    }
+}
@@ -0,0 +1,89 @@
+//! Interactive form fields.
+
+mod button;
+mod dropdown;
+mod text;
+
+pub use self::button::*;
+pub use self::dropdown::*;
+pub use self::text::*;
+
+use typst::model::Guard;
+
+use crate::layout::BoxElem;
+use crate::prelude::*;
+use crate::text::{families, variant, TextElem};
+
+/// Hook up all form definitions.
+pub fn module() -> Module {
+    let mut scope = Scope::deduplicating();
+    scope.define("text-field", TextFieldElem::func());
+    scope.define("checkbox", CheckboxElem::func());
+    scope.define("radio", RadioElem::func());
+    scope.define("dropdown", DropdownElem::func());
+    Module::new("form").with_scope(scope)
+}
+
+/// The padding between a field's box and its text.
+const INSET: Em = Em::new(0.25);
+
+/// Wrap a form field into a box so that it flows with the surrounding text.
+fn boxed(elem: Content, func: ElemFunc) -> Content {
+    BoxElem::new().with_body(Some(elem.guarded(Guard::Base(func)))).pack()
+}
+
+/// Lay out a form field of the given size that shows its kind's text, if
+/// any.
+fn layout_field(
+    vt: &mut Vt,
+    styles: StyleChain,
+    mut field: FormField,
+    size: Size,
+) -> SourceResult<Fragment> {
+    let (checked, text, multiline) = match &field.kind {
+        FormFieldKind::Text { value, multiline } => {
+            (false, Some(value.clone()), *multiline)
+        }
+        FormFieldKind::Checkbox { checked } => (*checked, None, false),
+        FormFieldKind::Radio { checked, .. } => (*checked, None, false),
+        FormFieldKind::Dropdown { selected, .. } => (false, selected.clone(), false),
+    };
+
+    let mut inner = field.appearance(size, checked);
+    if let Some(text) = text {
+        // Viewers show the text of edited fields in this font.
+        let variant = variant(styles);
+        let world = vt.world;
+        field.font = families(styles).find_map(|family| {
+            let id = world.book().select(family.as_str(), variant)?;
+            world.font(id)
+        });
+
+        let inset = INSET.at(field.text_size);
+        let available = Size::new(size.x - inset * 2.0, Abs::inf());
+        let pod = Regions::one(available, Axes::splat(false));
+        let frame = TextElem::packed(text).layout(vt, styles, pod)?.into_frame();
+
+        // Single-line fields center the text's first line vertically and
+        // share its baseline with the surrounding text.
+        let baseline = if multiline {
+            inset + frame.baseline()
+        } else {
+            (size.y + Em::new(0.7).at(field.text_size)) / 2.0
+        };
+
+        inner.push_frame(Point::new(inset, baseline - frame.baseline()), frame);
+        inner.set_baseline(baseline);
+    }
+
+    inner.clip();
+    inner.meta_iter([Meta::Field(field)]);
+
+    // The field stays in a group of its own so that exporters can replace it
+    // with an interactive widget.
+    let mut frame = Frame::new(size);
+    frame.set_baseline(inner.baseline());
+    frame.push(Point::zero(), FrameItem::Group(GroupItem::new(inner)));
+
+    Ok(Fragment::frame(frame))
+}
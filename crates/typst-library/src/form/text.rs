@@ -0,0 +1,93 @@
+use super::{boxed, layout_field};
+use crate::prelude::*;
+use crate::text::TextElem;
+
+/// A text input of an interactive form.
+///
+/// In PDF export, the field can be filled in by the reader. Other export
+/// formats show the field's initial value in a box.
+///
+/// ## Example { #example }
+/// ```example
+/// Name: #form.text-field("name", value: "Jane Doe")
+///
+/// #form.text-field(
+///   "comments",
+///   multiline: true,
+///   width: 100%,
+///   height: 3em,
+/// )
+/// ```
+///
+/// Display: Text Field
+/// Category: meta
+#[element(Show, Layout)]
+pub struct TextFieldElem {
+    /// The name of the field, which identifies its value in the filled-in
+    /// form.
+    #[required]
+    pub name: EcoString,
+
+    /// The initial text of the field.
+    pub value: EcoString,
+
+    /// Whether the field accepts multiple lines of text.
+    #[default(false)]
+    pub multiline: bool,
+
+    /// The field's width, relative to its parent container.
+    #[resolve]
+    #[default(Em::new(8.0).into())]
+    pub width: Rel<Length>,
+
+    /// The field's height, relative to its parent container.
+    #[resolve]
+    #[default(Em::new(1.4).into())]
+    pub height: Rel<Length>,
+
+    /// The field's background color. See the
+    /// [rectangle's documentation]($func/rect.fill) for more details.
+    pub fill: Option<Paint>,
+
+    /// The field's border. See the
+    /// [rectangle's documentation]($func/rect.stroke) for more details.
+    #[resolve]
+    #[fold]
+    #[default(Some(PartialStroke::default()))]
+    pub stroke: Option<PartialStroke>,
+}
+
+impl Show for TextFieldElem {
+    #[tracing::instrument(name = "TextFieldElem::show", skip_all)]
+    fn show(&self, _: &mut Vt, _: StyleChain) -> SourceResult<Content> {
+        Ok(boxed(self.clone().pack(), Self::func()))
+    }
+}
+
+impl Layout for TextFieldElem {
+    #[tracing::instrument(name = "TextFieldElem::layout", skip_all)]
+    fn layout(
+        &self,
+        vt: &mut Vt,
+        styles: StyleChain,
+        regions: Regions,
+    ) -> SourceResult<Fragment> {
+        let field = FormField {
+            name: self.name(),
+            kind: FormFieldKind::Text {
+                value: self.value(styles),
+                multiline: self.multiline(styles),
+            },
+            fill: self.fill(styles),
+            stroke: self.stroke(styles).map(PartialStroke::unwrap_or_default),
+            text_size: TextElem::size_in(styles),
+            font: None,
+            span: self.span(),
+        };
+
+        let size = Axes::new(self.width(styles), self.height(styles))
+            .zip(regions.base())
+            .map(|(s, b)| s.relative_to(b));
+        layout_field(vt, styles, field, size)
+    }
+}
@@ -0,0 +1,662 @@
+use std::fmt::{Display, Write};
+
+use base64::Engine;
+use syntect::highlighting as synt;
+use typst::util::hash128;
+
+use super::{FrameElem, FrameFormat};
+use crate::layout::{
+    BlockElem, BoxElem, EnumElem, EnumItem, ListElem, ListItem, PageElem, TableElem,
+    TermItem, TermsElem,
+};
+use crate::math::EquationElem;
+use crate::meta::{Counter, FigureElem, FootnoteElem, HeadingElem};
+use crate::prelude::*;
+use crate::shared::reflow::{Block, Converter, Flow, ListKind, Markup};
+use crate::text::{
+    EmphElem, LinebreakElem, RawElem, StrikeElem, StrongElem, SubElem, SuperElem,
+    TextElem, UnderlineElem, SYNTAXES, THEME,
+};
+use crate::visualize::ImageElem;
+
+/// How many pixels per point frames that are embedded as PNG are rendered at.
+const PNG_PIXEL_PER_PT: f32 = 2.0;
+
+/// Styles for the classes of highlighted Typst code, matching the default
+/// theme of raw text.
+const STYLESHEET: &str = "\
+.typ-comment { color: #8a8a8a; }
+.typ-escape { color: #1d6c76; }
+.typ-strong { font-weight: bold; }
+.typ-emph { font-style: italic; }
+.typ-link { text-decoration: underline; }
+.typ-raw { color: #818181; }
+.typ-label, .typ-ref { color: #1d6c76; }
+.typ-heading { font-weight: bold; text-decoration: underline; }
+.typ-marker { color: #8b41b1; }
+.typ-term { font-weight: bold; }
+.typ-math-delim { color: #298e0d; }
+.typ-math-op { color: #1d6c76; }
+.typ-key, .typ-op { color: #d73a49; }
+.typ-num { color: #b60157; }
+.typ-str { color: #298e0d; }
+.typ-func { color: #4b69c6; }
+.typ-pol { color: #8b41b1; }
+.equation { display: flex; align-items: center; }
+.equation > math { flex: 1; }
+";
+
+/// Convert content into a standalone HTML document.
+///
+/// Elements with an HTML counterpart, like headings, lists, tables and
+/// figures, are mapped onto it once user-defined show rules were applied.
+/// Equations are converted into MathML. Other elements are realized through
+/// their base recipe and what can only be laid out is embedded as an image.
+///
+/// The document is needed for its metadata and should be the result of laying
+/// out the same content, so that introspections resolve.
+#[tracing::instrument(skip_all)]
+pub fn html(
+    vt: &mut Vt,
+    content: &Content,
+    styles: StyleChain,
+    document: &Document,
+) -> SourceResult<String> {
+    let mut converter = Converter::new(vt, Html::default());
+    let body = join(converter.blocks(content, styles)?);
+    let Html { footnotes, lang: first } = converter.markup;
+    let lang = first.unwrap_or_else(|| lang(styles));
+
+    let mut html = String::from("<!DOCTYPE html>\n");
+    writeln!(html, "<html lang=\"{}\">", Escaped(&lang)).unwrap();
+    html.push_str("<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(
+        "<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n",
+    );
+
+    if let Some(title) = &document.title {
+        writeln!(html, "<title>{}</title>", Escaped(title)).unwrap();
+    }
+
+    let mut meta = |name: &str, content: &str| {
+        if !content.is_empty() {
+            writeln!(html, "<meta name=\"{name}\" content=\"{}\">", Escaped(content))
+                .unwrap();
+        }
+    };
+
+    meta("author", &document.author.join(", "));
+    meta("description", document.description.as_deref().unwrap_or_default());
+    meta("keywords", &document.keywords.join(", "));
+
+    writeln!(html, "<style>\n{STYLESHEET}</style>\n</head>\n<body>").unwrap();
+    html.push_str(&body);
+
+    if !footnotes.is_empty() {
+        html.push_str("<section class=\"footnotes\" role=\"doc-endnotes\">\n");
+        for footnote in &footnotes {
+            html.push_str(footnote);
+            html.push('\n');
+        }
+        html.push_str("</section>\n");
+    }
+
+    html.push_str("</body>\n</html>\n");
+    Ok(html)
+}
+
+/// The state of HTML conversion.
+#[derive(Default)]
+struct Html {
+    /// The converted footnotes, which are written at the end of the document.
+    footnotes: Vec<String>,
+    /// The language of the document's first text.
+    lang: Option<EcoString>,
+}
+
+impl Markup for Html {
+    fn convert(
+        cv: &mut Converter<Self>,
+        flow: &mut Flow<Self>,
+        content: &Content,
+        styles: StyleChain,
+    ) -> SourceResult<bool> {
+        if let Some(elem) = content.to::<HeadingElem>() {
+            cv.heading(flow, content, elem, styles)?;
+        } else if let Some(elem) = content.to::<ListItem>() {
+            let html = cv.list_item(&elem.body(), styles)?;
+            flow.item(ListKind::Bullet, None, html);
+        } else if let Some(elem) = content.to::<EnumItem>() {
+            let html = cv.enum_item(elem, styles)?;
+            flow.item(ListKind::Numbered, None, html);
+        } else if let Some(elem) = content.to::<TermItem>() {
+            let html = cv.term_item(elem, styles)?;
+            flow.item(ListKind::Terms, None, html);
+        } else if let Some(elem) = content.to::<ListElem>() {
+            let mut html = format!("<ul{}>\n", cv.id_attr(content));
+            for item in elem.children() {
+                html.push_str(&cv.list_item(&item.body(), styles)?);
+            }
+            html.push_str("</ul>");
+            flow.block(html);
+        } else if let Some(elem) = content.to::<EnumElem>() {
+            let mut html = format!("<ol{}", cv.id_attr(content));
+            let start = elem.start(styles);
+            if start != 1 {
+                write!(html, " start=\"{start}\"").unwrap();
+            }
+            html.push_str(">\n");
+            for item in elem.children() {
+                html.push_str(&cv.enum_item(&item, styles)?);
+            }
+            html.push_str("</ol>");
+            flow.block(html);
+        } else if let Some(elem) = content.to::<TermsElem>() {
+            let mut html = format!("<dl{}>\n", cv.id_attr(content));
+            for item in elem.children() {
+                html.push_str(&cv.term_item(&item, styles)?);
+            }
+            html.push_str("</dl>");
+            flow.block(html);
+        } else if let Some(elem) = content.to::<TableElem>() {
+            cv.table(flow, content, elem, styles)?;
+        } else if let Some(elem) = content.to::<FigureElem>() {
+            cv.figure(flow, content, elem, styles)?;
+        } else if let Some(elem) = content.to::<FootnoteElem>() {
+            cv.footnote(flow, content, elem, styles)?;
+        } else if let Some(elem) = content.to::<RawElem>() {
+            raw(flow, elem, styles);
+        } else if let Some(elem) = content.to::<EquationElem>() {
+            cv.equation(flow, content, elem, styles)?;
+        } else if let Some(elem) = content.to::<ImageElem>() {
+            let frame = cv.layout(elem, styles)?;
+            let alt = elem.alt(styles);
+            cv.embed(flow, &frame, content.span(), true, FrameFormat::Svg, alt)?;
+        } else if let Some(elem) = content.to::<FrameElem>() {
+            let frame = cv.layout(&elem.body(), styles)?;
+            let (format, alt) = (elem.format(styles), elem.alt(styles));
+            cv.embed(flow, &frame, content.span(), false, format, alt)?;
+        } else if let Some(elem) = content.to::<StrongElem>() {
+            cv.wrap(flow, "strong", &elem.body(), styles)?;
+        } else if let Some(elem) = content.to::<EmphElem>() {
+            cv.wrap(flow, "em", &elem.body(), styles)?;
+        } else if let Some(elem) = content.to::<SubElem>() {
+            cv.wrap(flow, "sub", &elem.body(), styles)?;
+        } else if let Some(elem) = content.to::<SuperElem>() {
+            cv.wrap(flow, "sup", &elem.body(), styles)?;
+        } else if let Some(elem) = content.to::<UnderlineElem>() {
+            cv.wrap(flow, "u", &elem.body(), styles)?;
+        } else if let Some(elem) = content.to::<StrikeElem>() {
+            cv.wrap(flow, "s", &elem.body(), styles)?;
+        } else if let Some(elem) = content.to::<BlockElem>() {
+            if let Some(body) = elem.body(styles) {
+                let html = join(cv.blocks(&body, styles)?);
+                flow.block(format!("<div{}>\n{html}</div>", cv.id_attr(content)));
+            }
+        } else if let Some(elem) = content.to::<BoxElem>() {
+            if let Some(body) = elem.body(styles) {
+                let html = cv.inline(&body, styles)?;
+                flow.inline(&html);
+            }
+        } else {
+            return Ok(false);
+        }
+
+        Ok(true)
+    }
+
+    fn leaf(
+        cv: &mut Converter<Self>,
+        flow: &mut Flow<Self>,
+        content: &Content,
+        styles: StyleChain,
+    ) -> SourceResult<()> {
+        if content.is::<LinebreakElem>() {
+            flow.inline("<br>");
+        } else if let Some(elem) = content.with::<dyn Layout>() {
+            // Elements that can only be laid out are embedded as images.
+            let frame = cv.layout(elem, styles)?;
+            cv.embed(flow, &frame, content.span(), true, FrameFormat::Svg, None)?;
+        }
+
+        // Everything else, like spacing and breaks, has no representation in
+        // HTML.
+        Ok(())
+    }
+
+    fn link(cv: &Converter<Self>, dest: &Destination) -> Option<(EcoString, EcoString)> {
+        let href = cv.href(dest)?;
+        Some((eco_format!("<a href=\"{}\">", Escaped(&href)), "</a>".into()))
+    }
+
+    fn escape(text: &str, html: &mut String) {
+        write!(html, "{}", Escaped(text)).unwrap();
+    }
+
+    fn list(kind: ListKind, items: Vec<(usize, String)>) -> String {
+        let tag = match kind {
+            ListKind::Bullet => "ul",
+            ListKind::Numbered => "ol",
+            ListKind::Terms => "dl",
+        };
+
+        let mut html = format!("<{tag}>\n");
+        for (_, item) in items {
+            html.push_str(&item);
+        }
+        write!(html, "</{tag}>").unwrap();
+        html
+    }
+
+    fn text(&mut self, styles: StyleChain) {
+        self.lang.get_or_insert_with(|| lang(styles));
+    }
+}
+
+impl Converter<'_, '_, Html> {
+    /// Convert a heading.
+    fn heading(
+        &mut self,
+        flow: &mut Flow<Html>,
+        content: &Content,
+        elem: &HeadingElem,
+        styles: StyleChain,
+    ) -> SourceResult<()> {
+        let level = elem.level(styles).get().min(6);
+        let body = self.heading_body(content, elem, styles)?;
+        let body = self.inline(&body, styles)?;
+        let id = self.id_attr(content);
+        flow.block(format!("<h{level}{id}>{body}</h{level}>"));
+        Ok(())
+    }
+
+    /// Convert an equation into MathML.
+    fn equation(
+        &mut self,
+        flow: &mut Flow<Html>,
+        content: &Content,
+        elem: &EquationElem,
+        styles: StyleChain,
+    ) -> SourceResult<()> {
+        let mathml = crate::math::mathml(self.vt, elem, styles)?;
+        if !elem.block(styles) {
+            flow.inline(&mathml);
+            return Ok(());
+        }
+
+        let mut html =
+            format!("<div class=\"equation\"{}>{mathml}", self.id_attr(content));
+        if let (Some(numbering), Some(loc)) = (elem.numbering(styles), content.location())
+        {
+            let number = Counter::of(EquationElem::func())
+                .at(self.vt, loc)?
+                .display(self.vt, &numbering)?;
+            let number = self.inline(&number, styles)?;
+            write!(html, "<span class=\"number\">{number}</span>").unwrap();
+        }
+
+        html.push_str("</div>");
+        flow.block(html);
+        Ok(())
+    }
+
+    /// Convert the body of a bullet list item.
+    fn list_item(&mut self, body: &Content, styles: StyleChain) -> SourceResult<String> {
+        Ok(format!("<li>{}</li>\n", self.inline(body, styles)?))
+    }
+
+    /// Convert a numbered list item.
+    fn enum_item(&mut self, item: &EnumItem, styles: StyleChain) -> SourceResult<String> {
+        let body = self.inline(&item.body(), styles)?;
+        Ok(match item.number(styles) {
+            Some(number) => format!("<li value=\"{number}\">{body}</li>\n"),
+            None => format!("<li>{body}</li>\n"),
+        })
+    }
+
+    /// Convert a term list item.
+    fn term_item(&mut self, item: &TermItem, styles: StyleChain) -> SourceResult<String> {
+        let term = self.inline(&item.term(), styles)?;
+        let description = self.inline(&item.description(), styles)?;
+        Ok(format!("<dt>{term}</dt>\n<dd>{description}</dd>\n"))
+    }
+
+    /// Convert a table, filling its cells into rows.
+    fn table(
+        &mut self,
+        flow: &mut Flow<Html>,
+        content: &Content,
+        elem: &TableElem,
+        styles: StyleChain,
+    ) -> SourceResult<()> {
+        let columns = elem.columns(styles).0.len().max(1);
+        let cells = elem.children();
+        let mut html = format!("<table{}>\n", self.id_attr(content));
+        for row in cells.chunks(columns) {
+            html.push_str("<tr>");
+            for cell in row {
+                write!(html, "<td>{}</td>", self.inline(cell, styles)?).unwrap();
+            }
+            html.push_str("</tr>\n");
+        }
+        html.push_str("</table>");
+        flow.block(html);
+        Ok(())
+    }
+
+    /// Convert a figure and its caption.
+    fn figure(
+        &mut self,
+        flow: &mut Flow<Html>,
+        content: &Content,
+        elem: &FigureElem,
+        styles: StyleChain,
+    ) -> SourceResult<()> {
+        let body = join(self.blocks(&elem.body(), styles)?);
+        let caption = match elem.full_caption(self.vt)? {
+            Some(caption) => {
+                format!("<figcaption>{}</figcaption>\n", self.inline(&caption, styles)?)
+            }
+            None => String::new(),
+        };
+
+        let id = self.id_attr(content);
+        flow.block(match elem.caption_pos(styles) {
+            VerticalAlign(GenAlign::Specific(Align::Top)) => {
+                format!("<figure{id}>\n{caption}{body}</figure>")
+            }
+            _ => format!("<figure{id}>\n{body}{caption}</figure>"),
+        });
+
+        Ok(())
+    }
+
+    /// Convert a footnote into a reference to its note, which is written at
+    /// the end of the document.
+    fn footnote(
+        &mut self,
+        flow: &mut Flow<Html>,
+        content: &Content,
+        elem: &FootnoteElem,
+        styles: StyleChain,
+    ) -> SourceResult<()> {
+        let Some(loc) = content.location() else { return Ok(()) };
+        let declaration = elem.declaration_location(self.vt).at(content.span())?;
+        let number = Counter::of(FootnoteElem::func())
+            .at(self.vt, declaration)?
+            .display(self.vt, &elem.numbering(styles))?;
+        let number = self.inline(&number, styles)?;
+
+        let note = Escaped(&self.anchor(declaration)).to_string();
+        let backlink = Escaped(&self.anchor(loc.variant(1))).to_string();
+        flow.inline(&format!(
+            "<sup><a id=\"{backlink}\" href=\"#{note}\" role=\"doc-noteref\">\
+             {number}</a></sup>"
+        ));
+
+        if let Some(body) = elem.body_content() {
+            let body = self.inline(&body, styles)?;
+            self.markup.footnotes.push(format!(
+                "<div id=\"{note}\" role=\"doc-footnote\">\
+                 <a href=\"#{backlink}\" role=\"doc-backlink\">{number}</a> {body}</div>"
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Convert content that is placed within an element, wrapping it into a
+    /// tag.
+    fn wrap(
+        &mut self,
+        flow: &mut Flow<Html>,
+        name: &str,
+        body: &Content,
+        styles: StyleChain,
+    ) -> SourceResult<()> {
+        let (open, close) = (eco_format!("<{name}>"), eco_format!("</{name}>"));
+        self.tagged(flow, open, close, body, styles)
+    }
+
+    /// Convert content that is placed within a line or an element like a list
+    /// item. If it consists of a single paragraph, that one is unwrapped.
+    fn inline(&mut self, content: &Content, styles: StyleChain) -> SourceResult<String> {
+        Ok(unwrap(self.flow(true, content, styles)?))
+    }
+
+    /// Lay out an element into a single frame.
+    ///
+    /// Frames are as wide as the text area of a page with default margins
+    /// and have unlimited height.
+    fn layout(&mut self, elem: &dyn Layout, styles: StyleChain) -> SourceResult<Frame> {
+        let pod = Regions::one(region(styles), Axes::splat(false));
+        Ok(elem.layout(self.vt, styles, pod)?.into_frame())
+    }
+
+    /// Embed a frame as an image.
+    fn embed(
+        &mut self,
+        flow: &mut Flow<Html>,
+        frame: &Frame,
+        span: Span,
+        block: bool,
+        format: FrameFormat,
+        alt: Option<EcoString>,
+    ) -> SourceResult<()> {
+        let (mime, data) = match format {
+            FrameFormat::Svg => ("image/svg+xml", typst::export::svg(frame).into_bytes()),
+            FrameFormat::Png => {
+                let fill = Color::Rgba(RgbaColor::new(0, 0, 0, 0));
+                let pixmap = typst::export::render(frame, PNG_PIXEL_PER_PT, fill);
+                let png = pixmap
+                    .encode_png()
+                    .map_err(|_| "failed to encode frame as PNG")
+                    .at(span)?;
+                ("image/png", png)
+            }
+        };
+
+        let data = base64::engine::general_purpose::STANDARD.encode(data);
+        let size = frame.size();
+        let mut html = format!(
+            "<img src=\"data:{mime};base64,{data}\" style=\"width: {}pt; height: {}pt",
+            round(size.x),
+            round(size.y),
+        );
+
+        // Within a line, the frame's baseline is aligned with the text's.
+        let block = block && !flow.inline;
+        if !block {
+            let shift = round(frame.baseline() - size.y);
+            write!(html, "; vertical-align: {shift}pt").unwrap();
+        }
+
+        html.push('"');
+        if let Some(alt) = alt {
+            write!(html, " alt=\"{}\"", Escaped(&alt)).unwrap();
+        }
+        html.push('>');
+
+        if block {
+            flow.block(format!("<div class=\"frame\">{html}</div>"));
+        } else {
+            flow.inline(&html);
+        }
+
+        Ok(())
+    }
+
+    /// The URL of a link's destination.
+    fn href(&self, dest: &Destination) -> Option<EcoString> {
+        match dest {
+            Destination::Url(url) => Some(url.clone()),
+            Destination::Location(loc) => Some(eco_format!("#{}", self.anchor(*loc))),
+            Destination::Remote(dest) => Some(eco_format!("{}#{}", dest.file, dest.name)),
+            // Positions on pages have no counterpart in HTML.
+            Destination::Position(_) => None,
+        }
+    }
+
+    /// The `id` attribute of an element, if it has a label or location.
+    fn id_attr(&self, content: &Content) -> String {
+        let id = match content.label() {
+            Some(label) => Some(label.0.clone()),
+            None => content.location().map(|loc| self.anchor(loc)),
+        };
+
+        id.map(|id| format!(" id=\"{}\"", Escaped(&id))).unwrap_or_default()
+    }
+
+    /// The id of the element at a location: Its label if it has one and
+    /// otherwise one derived from the location.
+    fn anchor(&self, loc: Location) -> EcoString {
+        self.vt
+            .introspector
+            .query_first(&Selector::Location(loc))
+            .and_then(|elem| elem.label().map(|label| label.0.clone()))
+            .unwrap_or_else(|| eco_format!("loc-{:x}", hash128(&loc)))
+    }
+}
+
+/// Join blocks into HTML.
+fn join(blocks: Vec<Block>) -> String {
+    let mut html = String::new();
+    for block in blocks {
+        match block {
+            Block::Par(par) => writeln!(html, "<p>{par}</p>").unwrap(),
+            Block::Other(other) => writeln!(html, "{other}").unwrap(),
+        }
+    }
+    html
+}
+
+/// Join blocks into HTML, unwrapping a single paragraph.
+fn unwrap(mut blocks: Vec<Block>) -> String {
+    if let [Block::Par(_)] = blocks.as_slice() {
+        if let Some(Block::Par(par)) = blocks.pop() {
+            return par;
+        }
+    }
+    join(blocks)
+}
+
+/// Convert raw text, highlighting it.
+///
+/// Typst code is highlighted with the CSS classes from the stylesheet, other
+/// languages with inline styles from the default theme.
+fn raw(flow: &mut Flow<Html>, elem: &RawElem, styles: StyleChain) {
+    let text = elem.text();
+    let lang = elem.lang(styles).map(|lang| lang.to_lowercase());
+    let code = match lang.as_deref() {
+        Some("typ" | "typst") => typst::ide::highlight_html(&typst::syntax::parse(&text)),
+        Some("typc") => typst::ide::highlight_html(&typst::syntax::parse_code(&text)),
+        lang => highlight(&text, lang),
+    };
+
+    if elem.block(styles) {
+        flow.block(format!("<pre>{code}</pre>"));
+    } else {
+        flow.inline(&code);
+    }
+}
+
+/// Highlight code in a language other than Typst.
+fn highlight(text: &str, lang: Option<&str>) -> String {
+    let mut html = String::from("<code>");
+    match lang.and_then(|token| SYNTAXES.find_syntax_by_token(token)) {
+        Some(syntax) => {
+            let foreground = THEME.settings.foreground.unwrap_or(synt::Color::BLACK);
+            let mut highlighter = syntect::easy::HighlightLines::new(syntax, &THEME);
+            for (i, line) in text.lines().enumerate() {
+                if i != 0 {
+                    html.push('\n');
+                }
+
+                for (style, piece) in
+                    highlighter.highlight_line(line, &SYNTAXES).into_iter().flatten()
+                {
+                    styled(&mut html, piece, style, foreground);
+                }
+            }
+        }
+        None => write!(html, "{}", Escaped(text)).unwrap(),
+    }
+    html.push_str("</code>");
+    html
+}
+
+/// Write a piece of highlighted code with a syntect style.
+fn styled(html: &mut String, piece: &str, style: synt::Style, foreground: synt::Color) {
+    let mut css = String::new();
+    if style.foreground != foreground {
+        let synt::Color { r, g, b, .. } = style.foreground;
+        write!(css, "color: #{r:02x}{g:02x}{b:02x};").unwrap();
+    }
+
+    if style.font_style.contains(synt::FontStyle::BOLD) {
+        css.push_str("font-weight: bold;");
+    }
+
+    if style.font_style.contains(synt::FontStyle::ITALIC) {
+        css.push_str("font-style: italic;");
+    }
+
+    if style.font_style.contains(synt::FontStyle::UNDERLINE) {
+        css.push_str("text-decoration: underline;");
+    }
+
+    if css.is_empty() {
+        write!(html, "{}", Escaped(piece)).unwrap();
+    } else {
+        write!(html, "<span style=\"{css}\">{}</span>", Escaped(piece)).unwrap();
+    }
+}
+
+/// The size that frames are laid out in.
+fn region(styles: StyleChain) -> Size {
+    let resolve = |length: Smart<Length>| {
+        length.map(|length| length.resolve(styles)).unwrap_or(Abs::inf())
+    };
+
+    // Subtract the default margins from the page's width.
+    let mut width = resolve(PageElem::width_in(styles));
+    let height = resolve(PageElem::height_in(styles));
+    if width.is_finite() {
+        width -= 2.0 * (2.5 / 21.0) * width.min(height);
+    }
+
+    Size::new(width, Abs::inf())
+}
+
+/// The value of the `lang` attribute for the text language.
+fn lang(styles: StyleChain) -> EcoString {
+    let mut lang = EcoString::from(TextElem::lang_in(styles).as_str());
+    if let Some(region) = TextElem::region_in(styles) {
+        lang.push('-');
+        lang.push_str(region.as_str());
+    }
+    lang
+}
+
+/// Round a length to hundredths of a point.
+fn round(length: Abs) -> f64 {
+    (length.to_pt() * 100.0).round() / 100.0
+}
+
+/// Escapes text for use in HTML content and attribute values.
+pub(crate) struct Escaped<'a>(pub &'a str);
+
+impl Display for Escaped<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '&' => f.write_str("&amp;")?,
+                '<' => f.write_str("&lt;")?,
+                '>' => f.write_str("&gt;")?,
+                '"' => f.write_str("&quot;")?,
+                _ => f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,66 @@
+//! HTML export.
+
+mod convert;
+
+pub use self::convert::html;
+pub(crate) use self::convert::Escaped;
+
+use crate::prelude::*;
+
+/// Hook up all HTML definitions.
+pub fn module() -> Module {
+    let mut scope = Scope::deduplicating();
+    scope.define("frame", FrameElem::func());
+    Module::new("html").with_scope(scope)
+}
+
+/// Embeds content into HTML output as an image.
+///
+/// HTML export maps elements like headings, lists and tables to their HTML
+/// counterparts and equations to MathML. Content without such a counterpart,
+/// like a drawing, can be wrapped in this function to lay it out and embed it
+/// as an SVG or PNG image instead. Equations wrapped in it are embedded as
+/// they look in the other export formats instead of being converted into
+/// MathML.
+///
+/// ## Example { #example }
+/// ```example
+/// #html.frame(alt: "A blue circle")[
+///   #circle(fill: blue, radius: 8pt)
+/// ]
+/// ```
+///
+/// Display: Frame
+/// Category: meta
+#[element(Show)]
+pub struct FrameElem {
+    /// The image format the frame is embedded in.
+    ///
+    /// SVG keeps text and shapes sharp at every zoom level while PNG is
+    /// supported everywhere.
+    #[default(FrameFormat::Svg)]
+    pub format: FrameFormat,
+
+    /// A text describing the frame's content.
+    pub alt: Option<EcoString>,
+
+    /// The content to embed.
+    #[required]
+    pub body: Content,
+}
+
+impl Show for FrameElem {
+    #[tracing::instrument(name = "FrameElem::show", skip(self))]
+    fn show(&self, _: &mut Vt, _: StyleChain) -> SourceResult<Content> {
+        Ok(self.body())
+    }
+}
+
+/// An image format that frames can be embedded in.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Cast)]
+pub enum FrameFormat {
+    /// Scalable vector graphics.
+    Svg,
+    /// A raster image with a resolution of 144 pixels per inch.
+    Png,
+}
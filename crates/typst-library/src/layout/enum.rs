@@ -61,7 +61,7 @@ use super::GridLayouter;
 ///
 /// Display: Numbered List
 /// Category: layout
-#[element(Layout)]
+#[element(Layout, Tagged)]
 #[scope(
     scope.define("item", EnumItem::func());
     scope
@@ -226,7 +226,7 @@ impl Layout for EnumElem {
         let number_align: Axes<Option<GenAlign>> =
             Axes::new(self.number_align(styles).into(), Align::Top.into()).map(Some);
 
-        for item in self.children() {
+        for (i, item) in self.children().into_iter().enumerate() {
             number = item.number(styles).unwrap_or(number);
 
             let resolved = if full {
@@ -245,13 +245,21 @@ impl Layout for EnumElem {
 
             // Disable overhang as a workaround to end-aligned dots glitching
             // and decreasing spacing between numbers and items.
-            let resolved =
+            let mut resolved =
                 resolved.aligned(number_align).styled(TextElem::set_overhang(false));
+            let mut body = item.body().styled(Self::set_parents(Parent(number)));
+
+            // Mark the item's cells so that exporters can find the item.
+            if let Some(loc) = self.0.location() {
+                let item = item.pack();
+                resolved = resolved.tagged(item.clone(), loc.variant(i + 1));
+                body = body.tagged(item, loc.variant(i + 1));
+            }
 
             cells.push(Content::empty());
             cells.push(resolved);
             cells.push(Content::empty());
-            cells.push(item.body().styled(Self::set_parents(Parent(number))));
+            cells.push(body);
             number = number.saturating_add(1);
         }
 
@@ -36,7 +36,7 @@ use super::GridLayouter;
 ///
 /// Display: Bullet List
 /// Category: layout
-#[element(Layout)]
+#[element(Layout, Tagged)]
 #[scope(
     scope.define("item", ListItem::func());
     scope
@@ -143,11 +143,21 @@ impl Layout for ListElem {
             .aligned(Align::LEFT_TOP.into());
 
         let mut cells = vec![];
-        for item in self.children() {
+        for (i, item) in self.children().into_iter().enumerate() {
+            let mut marker = marker.clone();
+            let mut body = item.body().styled(Self::set_depth(Depth));
+
+            // Mark the item's cells so that exporters can find the item.
+            if let Some(loc) = self.0.location() {
+                let item = item.pack();
+                marker = marker.tagged(item.clone(), loc.variant(i + 1));
+                body = body.tagged(item, loc.variant(i + 1));
+            }
+
             cells.push(Content::empty());
-            cells.push(marker.clone());
+            cells.push(marker);
             cells.push(Content::empty());
-            cells.push(item.body().styled(Self::set_depth(Depth)));
+            cells.push(body);
         }
 
         let layouter = GridLayouter::new(
@@ -169,6 +169,58 @@ pub struct PageElem {
     /// ```
     pub fill: Option<Paint>,
 
+    /// How far the page's fill and background extend beyond its edges.
+    ///
+    /// Printers print onto larger sheets and cut them down to the page's size
+    /// afterwards. Since the cut is never perfectly precise, content that
+    /// should reach the edge of the page must extend a bit beyond it. The page's
+    /// size and margins stay the same. PDF export enlarges the exported page by
+    /// the bleed on each side and its trim and bleed boxes tell the printer
+    /// where to cut. The other export formats only show the trimmed page.
+    ///
+    /// ```example
+    /// #set page(
+    ///   width: 120pt,
+    ///   height: 80pt,
+    ///   bleed: 8pt,
+    ///   fill: aqua,
+    /// )
+    ///
+    /// The fill reaches into the bleed.
+    /// ```
+    #[parse(
+        let bleed = args.named::<Spanned<Length>>("bleed")?;
+        if let Some(Spanned { v, span }) = bleed {
+            if v.abs < Abs::zero() || v.em < Em::zero() {
+                bail!(span, "page bleed must not be negative");
+            }
+        }
+        bleed.map(|bleed| bleed.v)
+    )]
+    #[resolve]
+    pub bleed: Length,
+
+    /// Whether to draw crop and registration marks outside of the bleed.
+    ///
+    /// Crop marks show where the page is cut and registration marks help the
+    /// printer to align the colors of the print. They are drawn in
+    /// registration color, so that they appear on every color separation. Only
+    /// PDF export draws them and enlarges the page further to make room for
+    /// them.
+    ///
+    /// ```example
+    /// #set page(
+    ///   width: 120pt,
+    ///   height: 80pt,
+    ///   bleed: 6pt,
+    ///   marks: true,
+    /// )
+    ///
+    /// Ready to print.
+    /// ```
+    #[default(false)]
+    pub marks: bool,
+
     /// How to [number]($func/numbering) the pages.
     ///
     /// If an explicit `footer` is given, the numbering is ignored.
@@ -364,6 +416,8 @@ impl PageElem {
         }
 
         let fill = self.fill(styles);
+        let bleed = self.bleed(styles);
+        let marks = self.marks(styles);
         let foreground = self.foreground(styles);
         let background = self.background(styles);
         let header = self.header(styles);
@@ -431,6 +485,10 @@ impl PageElem {
                     pos = Point::new(margin.left, size.y - margin.bottom + descent);
                     area = Size::new(pw, margin.bottom - descent);
                     align = Align::Top.into();
+                } else if ptr::eq(marginal, &background) {
+                    pos = Point::splat(-bleed);
+                    area = size + Size::splat(bleed * 2.0);
+                    align = Align::CENTER_HORIZON.into();
                 } else {
                     pos = Point::zero();
                     area = size;
@@ -451,8 +509,20 @@ impl PageElem {
                 }
             }
 
+            // The fill reaches into the bleed, which lies outside of the
+            // frame. Only PDF export enlarges the page to show it.
             if let Some(fill) = &fill {
-                frame.fill(fill.clone());
+                let rect = Geometry::Rect(size + Size::splat(bleed * 2.0));
+                let shape = FrameItem::Shape(rect.filled(fill.clone()), self.span());
+                frame.prepend(Point::splat(-bleed), shape);
+            }
+
+            if !bleed.is_zero() || marks {
+                let boxes = PrintBoxes { bleed, marks };
+                frame.push(
+                    Point::zero(),
+                    FrameItem::Meta(Meta::PrintBoxes(boxes), Size::zero()),
+                );
             }
 
             number = number.saturating_add(1);
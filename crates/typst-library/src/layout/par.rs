@@ -46,7 +46,7 @@ use crate::text::{
 ///
 /// Display: Paragraph
 /// Category: layout
-#[element(Construct)]
+#[element(Construct, Tagged)]
 pub struct ParElem {
     /// The spacing between lines.
     #[resolve]
@@ -37,7 +37,7 @@ use crate::prelude::*;
 ///
 /// Display: Table
 /// Category: layout
-#[element(Layout, LocalName, Figurable)]
+#[element(Layout, Tagged, LocalName, Figurable)]
 pub struct TableElem {
     /// The column sizes. See the [grid documentation]($func/grid) for more
     /// information on track sizing.
@@ -21,7 +21,7 @@ use crate::prelude::*;
 ///
 /// Display: Term List
 /// Category: layout
-#[element(Layout)]
+#[element(Layout, Tagged)]
 #[scope(
     scope.define("item", TermItem::func());
     scope
@@ -126,9 +126,19 @@ impl Layout for TermsElem {
             if !indent.is_zero() {
                 seq.push(HElem::new(indent.into()).pack());
             }
-            seq.push(child.term().strong());
-            seq.push(separator.clone());
-            seq.push(child.description());
+
+            let mut item = Content::sequence([
+                child.term().strong(),
+                separator.clone(),
+                child.description(),
+            ]);
+
+            // Mark the item so that exporters can find it.
+            if let Some(loc) = self.0.location() {
+                item = item.tagged(child.pack(), loc.variant(i + 1));
+            }
+
+            seq.push(item);
         }
 
         Content::sequence(seq)
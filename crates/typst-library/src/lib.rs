@@ -5,9 +5,13 @@
 #![allow(clippy::comparison_chain)]
 
 pub mod compute;
+pub mod form;
+pub mod html;
 pub mod layout;
+pub mod markdown;
 pub mod math;
 pub mod meta;
+pub mod pdf;
 pub mod prelude;
 pub mod shared;
 pub mod symbols;
@@ -15,7 +19,7 @@ pub mod text;
 pub mod visualize;
 
 use typst::diag::At;
-use typst::eval::{LangItems, Library, Module, Scope};
+use typst::eval::{Dict, LangItems, Library, Module, Scope};
 use typst::geom::Smart;
 use typst::model::{Element, Styles};
 
@@ -23,14 +27,20 @@ use self::layout::LayoutRoot;
 
 /// Construct the standard library.
 pub fn build() -> Library {
+    build_with_inputs(Dict::new())
+}
+
+/// Construct the standard library with a dictionary of inputs, which is made
+/// available to documents as `sys.inputs`.
+pub fn build_with_inputs(inputs: Dict) -> Library {
     let math = math::module();
-    let global = global(math.clone());
+    let global = global(math.clone(), inputs);
     Library { global, math, styles: styles(), items: items() }
 }
 
 /// Construct the module with global definitions.
 #[tracing::instrument(skip_all)]
-fn global(math: Module) -> Module {
+fn global(math: Module, inputs: Dict) -> Module {
     let mut global = Scope::deduplicating();
 
     // Categories.
@@ -41,10 +51,21 @@ fn global(math: Module) -> Module {
     compute::define(&mut global);
     symbols::define(&mut global);
     global.define("math", math);
+    global.define("form", form::module());
+    global.define("html", html::module());
+    global.define("pdf", pdf::module());
+    global.define("sys", sys(inputs));
 
     Module::new("global").with_scope(global)
 }
 
+/// Construct the module with information about the compilation environment.
+fn sys(inputs: Dict) -> Module {
+    let mut scope = Scope::new();
+    scope.define("inputs", inputs);
+    Module::new("sys").with_scope(scope)
+}
+
 /// Construct the standard style map.
 fn styles() -> Styles {
     Styles::new()
@@ -54,8 +75,11 @@ fn styles() -> Styles {
 fn items() -> LangItems {
     LangItems {
         layout: |world, content, styles| content.layout_root(world, styles),
+        html: html::html,
+        markdown: markdown::markdown,
         em: text::TextElem::size_in,
         dir: text::TextElem::dir_in,
+        tagged: meta::DocumentElem::tagged_in,
         space: || text::SpaceElem::new().pack(),
         linebreak: || text::LinebreakElem::new().pack(),
         text: |text| text::TextElem::new(text).pack(),
@@ -98,6 +122,7 @@ fn items() -> LangItems {
         rgb_func: compute::rgb_func(),
         cmyk_func: compute::cmyk_func(),
         luma_func: compute::luma_func(),
+        spot_func: compute::spot_func(),
         equation: |body, block| math::EquationElem::new(body).with_block(block).pack(),
         math_align_point: || math::AlignPointElem::new().pack(),
         math_delimited: |open, body, close| math::LrElem::new(open + body + close).pack(),
@@ -0,0 +1,360 @@
+//! Markdown export.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::layout::{
+    BlockElem, BoxElem, EnumElem, EnumItem, ListElem, ListItem, TableElem, TermItem,
+    TermsElem,
+};
+use crate::math::EquationElem;
+use crate::meta::{FigureElem, FootnoteElem, HeadingElem};
+use crate::prelude::*;
+use crate::shared::reflow::{Block, Converter, Flow, ListKind, Markup};
+use crate::text::{EmphElem, LinebreakElem, RawElem, StrikeElem, StrongElem};
+use crate::visualize::ImageElem;
+
+/// Convert content into a Markdown document.
+///
+/// Headings, emphasis, lists, links, code, tables and footnotes are mapped
+/// onto their GitHub Flavored Markdown counterparts once user-defined show
+/// rules were applied. Equations are written in Typst's math syntax between
+/// dollar signs. Other elements are realized through their base recipe and
+/// only their text is kept.
+#[tracing::instrument(skip_all)]
+pub fn markdown(
+    vt: &mut Vt,
+    content: &Content,
+    styles: StyleChain,
+    _: &Document,
+) -> SourceResult<String> {
+    let mut converter = Converter::new(vt, Markdown::default());
+    let mut markdown = converter.body(content, styles)?;
+    let footnotes = converter.markup.footnotes;
+    if !footnotes.is_empty() {
+        markdown.push_str("\n\n");
+        markdown.push_str(&footnotes.join("\n"));
+    }
+
+    if !markdown.is_empty() {
+        markdown.push('\n');
+    }
+
+    Ok(markdown)
+}
+
+/// The state of Markdown conversion.
+#[derive(Default)]
+struct Markdown {
+    /// The converted footnotes, which are written at the end of the document.
+    footnotes: Vec<String>,
+    /// Maps from the locations of footnotes to their identifiers.
+    notes: HashMap<Location, usize>,
+}
+
+impl Markup for Markdown {
+    fn convert(
+        cv: &mut Converter<Self>,
+        flow: &mut Flow<Self>,
+        content: &Content,
+        styles: StyleChain,
+    ) -> SourceResult<bool> {
+        if let Some(elem) = content.to::<HeadingElem>() {
+            cv.heading(flow, content, elem, styles)?;
+        } else if let Some(elem) = content.to::<ListItem>() {
+            let body = cv.body(&elem.body(), styles)?;
+            flow.item(ListKind::Bullet, None, body);
+        } else if let Some(elem) = content.to::<EnumItem>() {
+            let body = cv.body(&elem.body(), styles)?;
+            flow.item(ListKind::Numbered, elem.number(styles), body);
+        } else if let Some(elem) = content.to::<TermItem>() {
+            let body = cv.term_item(elem, styles)?;
+            flow.item(ListKind::Terms, None, body);
+        } else if let Some(elem) = content.to::<ListElem>() {
+            let mut list = Flow::default();
+            for item in elem.children() {
+                let body = cv.body(&item.body(), styles)?;
+                list.item(ListKind::Bullet, None, body);
+            }
+            flow.blocks(list.finish());
+        } else if let Some(elem) = content.to::<EnumElem>() {
+            let mut list = Flow::default();
+            let mut number = elem.start(styles);
+            for item in elem.children() {
+                number = item.number(styles).unwrap_or(number);
+                let body = cv.body(&item.body(), styles)?;
+                list.item(ListKind::Numbered, Some(number), body);
+                number += 1;
+            }
+            flow.blocks(list.finish());
+        } else if let Some(elem) = content.to::<TermsElem>() {
+            let mut list = Flow::default();
+            for item in elem.children() {
+                let body = cv.term_item(&item, styles)?;
+                list.item(ListKind::Terms, None, body);
+            }
+            flow.blocks(list.finish());
+        } else if let Some(elem) = content.to::<TableElem>() {
+            cv.table(flow, elem, styles)?;
+        } else if let Some(elem) = content.to::<FigureElem>() {
+            let mut blocks = cv.blocks(&elem.body(), styles)?;
+            if let Some(caption) = elem.full_caption(cv.vt)? {
+                let caption = Block::Par(cv.inline(&caption, styles)?);
+                match elem.caption_pos(styles) {
+                    VerticalAlign(GenAlign::Specific(Align::Top)) => {
+                        blocks.insert(0, caption)
+                    }
+                    _ => blocks.push(caption),
+                }
+            }
+            flow.blocks(blocks);
+        } else if let Some(elem) = content.to::<FootnoteElem>() {
+            cv.footnote(flow, content, elem, styles)?;
+        } else if let Some(elem) = content.to::<RawElem>() {
+            raw(flow, elem, styles);
+        } else if let Some(elem) = content.to::<EquationElem>() {
+            cv.equation(flow, content, elem, styles);
+        } else if let Some(elem) = content.to::<ImageElem>() {
+            let alt = elem.alt(styles).unwrap_or_default();
+            let mut markdown = String::from("![");
+            Self::escape(&alt, &mut markdown);
+            write!(markdown, "]({})", elem.path()).unwrap();
+            flow.inline(&markdown);
+        } else if let Some(elem) = content.to::<StrongElem>() {
+            cv.tagged(flow, "**".into(), "**".into(), &elem.body(), styles)?;
+        } else if let Some(elem) = content.to::<EmphElem>() {
+            cv.tagged(flow, "*".into(), "*".into(), &elem.body(), styles)?;
+        } else if let Some(elem) = content.to::<StrikeElem>() {
+            cv.tagged(flow, "~~".into(), "~~".into(), &elem.body(), styles)?;
+        } else if let Some(elem) = content.to::<BlockElem>() {
+            if let Some(body) = elem.body(styles) {
+                let blocks = cv.blocks(&body, styles)?;
+                flow.blocks(blocks);
+            }
+        } else if let Some(elem) = content.to::<BoxElem>() {
+            if let Some(body) = elem.body(styles) {
+                cv.accept(flow, &body, styles)?;
+            }
+        } else {
+            return Ok(false);
+        }
+
+        Ok(true)
+    }
+
+    fn leaf(
+        _: &mut Converter<Self>,
+        flow: &mut Flow<Self>,
+        content: &Content,
+        _: StyleChain,
+    ) -> SourceResult<()> {
+        if content.is::<LinebreakElem>() {
+            flow.inline("\\\n");
+        }
+
+        // Everything else, like spacing and drawings, has no representation
+        // in Markdown.
+        Ok(())
+    }
+
+    fn link(_: &Converter<Self>, dest: &Destination) -> Option<(EcoString, EcoString)> {
+        let url = match dest {
+            Destination::Url(url) => url.clone(),
+            Destination::Remote(dest) => eco_format!("{}#{}", dest.file, dest.name),
+            _ => return None,
+        };
+        Some(("[".into(), eco_format!("]({url})")))
+    }
+
+    /// Escape characters that have a meaning in Markdown.
+    fn escape(text: &str, markdown: &mut String) {
+        for c in text.chars() {
+            if matches!(
+                c,
+                '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|' | '~' | '$'
+            ) {
+                markdown.push('\\');
+            }
+            markdown.push(c);
+        }
+    }
+
+    fn list(kind: ListKind, items: Vec<(usize, String)>) -> String {
+        let mut list = vec![];
+        for (number, body) in items {
+            let marker = match kind {
+                ListKind::Numbered => format!("{number}. "),
+                ListKind::Bullet | ListKind::Terms => "- ".into(),
+            };
+            list.push(format!("{marker}{}", indent(&body, marker.len())));
+        }
+        list.join("\n")
+    }
+}
+
+impl Converter<'_, '_, Markdown> {
+    /// Convert a heading.
+    fn heading(
+        &mut self,
+        flow: &mut Flow<Markdown>,
+        content: &Content,
+        elem: &HeadingElem,
+        styles: StyleChain,
+    ) -> SourceResult<()> {
+        let level = elem.level(styles).get().min(6);
+        let body = self.heading_body(content, elem, styles)?;
+        let body = self.inline(&body, styles)?;
+        flow.block(format!("{} {body}", "#".repeat(level)));
+        Ok(())
+    }
+
+    /// Convert a term list item into the body of a bullet list item.
+    fn term_item(&mut self, item: &TermItem, styles: StyleChain) -> SourceResult<String> {
+        let term = self.inline(&item.term(), styles)?;
+        let description = self.body(&item.description(), styles)?;
+        Ok(format!("**{term}**: {description}"))
+    }
+
+    /// Convert a table into a pipe table whose first row is the header.
+    fn table(
+        &mut self,
+        flow: &mut Flow<Markdown>,
+        elem: &TableElem,
+        styles: StyleChain,
+    ) -> SourceResult<()> {
+        let columns = elem.columns(styles).0.len().max(1);
+        let mut table = String::new();
+        for (i, row) in elem.children().chunks(columns).enumerate() {
+            table.push('|');
+            for cell in row {
+                let cell = self.inline(cell, styles)?.replace('\n', " ");
+                write!(table, " {cell} |").unwrap();
+            }
+
+            for _ in row.len()..columns {
+                table.push_str("  |");
+            }
+
+            if i == 0 {
+                table.push_str("\n|");
+                table.push_str(&" --- |".repeat(columns));
+            }
+
+            table.push('\n');
+        }
+
+        table.pop();
+        flow.block(table);
+        Ok(())
+    }
+
+    /// Convert a footnote into a reference to its note, which is written at
+    /// the end of the document.
+    fn footnote(
+        &mut self,
+        flow: &mut Flow<Markdown>,
+        content: &Content,
+        elem: &FootnoteElem,
+        styles: StyleChain,
+    ) -> SourceResult<()> {
+        if content.location().is_none() {
+            return Ok(());
+        }
+
+        let declaration = elem.declaration_location(self.vt).at(content.span())?;
+        let next = self.markup.notes.len() + 1;
+        let id = *self.markup.notes.entry(declaration).or_insert(next);
+        flow.inline(&format!("[^{id}]"));
+
+        if let Some(body) = elem.body_content() {
+            let body = self.body(&body, styles)?;
+            self.markup.footnotes.push(format!("[^{id}]: {}", indent(&body, 4)));
+        }
+
+        Ok(())
+    }
+
+    /// Convert an equation into its source code between dollar signs.
+    fn equation(
+        &mut self,
+        flow: &mut Flow<Markdown>,
+        content: &Content,
+        elem: &EquationElem,
+        styles: StyleChain,
+    ) {
+        let span = content.span();
+        let source = (!span.is_detached())
+            .then(|| self.vt.world.source(span.id()).ok())
+            .flatten()
+            .and_then(|source| {
+                let node = source.find(span)?;
+                Some(source.text()[node.range()].to_string())
+            });
+
+        let math = match source {
+            Some(source) => source.trim_matches('$').trim().to_string(),
+            None => elem.body().plain_text().to_string(),
+        };
+
+        if elem.block(styles) {
+            flow.block(format!("$$\n{math}\n$$"));
+        } else {
+            flow.inline(&format!("${math}$"));
+        }
+    }
+
+    /// Convert content into the Markdown of separate blocks, like the body
+    /// of a list item.
+    fn body(&mut self, content: &Content, styles: StyleChain) -> SourceResult<String> {
+        Ok(join(self.blocks(content, styles)?, "\n\n"))
+    }
+
+    /// Convert content that must fit into a single line, like a heading.
+    fn inline(&mut self, content: &Content, styles: StyleChain) -> SourceResult<String> {
+        Ok(join(self.blocks(content, styles)?, " "))
+    }
+}
+
+/// Join the Markdown of blocks.
+fn join(blocks: Vec<Block>, separator: &str) -> String {
+    let blocks: Vec<_> = blocks.into_iter().map(Block::into_inner).collect();
+    blocks.join(separator)
+}
+
+/// Convert raw text into a code span or a fenced code block.
+fn raw(flow: &mut Flow<Markdown>, elem: &RawElem, styles: StyleChain) {
+    let text = elem.text();
+
+    // The fence must be longer than any run of backticks in the text.
+    let mut longest = 0;
+    let mut run = 0;
+    for c in text.chars() {
+        run = if c == '`' { run + 1 } else { 0 };
+        longest = longest.max(run);
+    }
+
+    if elem.block(styles) {
+        let fence = "`".repeat(longest.max(2) + 1);
+        let lang = elem.lang(styles).unwrap_or_default();
+        flow.block(format!("{fence}{lang}\n{text}\n{fence}"));
+    } else {
+        let fence = "`".repeat(longest + 1);
+        let pad = if text.starts_with('`') || text.ends_with('`') { " " } else { "" };
+        flow.inline(&format!("{fence}{pad}{text}{pad}{fence}"));
+    }
+}
+
+/// Indent all but the first line of text.
+fn indent(text: &str, width: usize) -> String {
+    let mut indented = String::new();
+    for (i, line) in text.lines().enumerate() {
+        if i > 0 {
+            indented.push('\n');
+            if !line.is_empty() {
+                indented.push_str(&" ".repeat(width));
+            }
+        }
+        indented.push_str(line);
+    }
+    indented
+}
@@ -119,7 +119,7 @@ fn attachment(ctx: &MathContext, id: GlyphId, italics_correction: Abs) -> Abs {
 }
 
 /// An accent character.
-pub struct Accent(char);
+pub struct Accent(pub(super) char);
 
 impl Accent {
     /// Normalize a character into an accent.
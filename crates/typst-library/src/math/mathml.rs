@@ -0,0 +1,743 @@
+use std::fmt::Write;
+
+use typst::font::FontStyle;
+
+use super::*;
+use crate::html::Escaped;
+
+/// Convert an equation into Presentation MathML.
+///
+/// The result is a single `<math>` element that mirrors the structure of the
+/// equation's body: Fractions, attachments, roots, matrices and the like are
+/// mapped onto their MathML counterparts while the positioning and stretching
+/// of glyphs is left to the MathML renderer. Show rules apply just like in
+/// layout. Content that has no MathML counterpart is included as plain text.
+#[tracing::instrument(skip_all)]
+pub fn mathml(
+    vt: &mut Vt,
+    elem: &EquationElem,
+    styles: StyleChain,
+) -> SourceResult<String> {
+    let block = elem.block(styles);
+    let variant = variant(styles);
+    let style = MathStyle {
+        variant: MathVariant::Serif,
+        size: if block { MathSize::Display } else { MathSize::Text },
+        class: Smart::Auto,
+        cramped: false,
+        bold: variant.weight >= FontWeight::BOLD,
+        italic: match variant.style {
+            FontStyle::Normal => Smart::Auto,
+            FontStyle::Italic | FontStyle::Oblique => Smart::Custom(true),
+        },
+    };
+
+    let body = Converter { vt }.group(&elem.body(), styles, style)?;
+    let display = if block { " display=\"block\"" } else { "" };
+    Ok(format!(
+        "<math xmlns=\"http://www.w3.org/1998/Math/MathML\"{display}>{body}</math>"
+    ))
+}
+
+/// The width of a space between ordinary items, which approximates the width
+/// of a space in the default math font.
+const SPACE: Em = Em::new(0.25);
+
+/// Converts math content into MathML.
+struct Converter<'v, 't> {
+    /// The virtual typesetter.
+    vt: &'v mut Vt<'t>,
+}
+
+impl Converter<'_, '_> {
+    /// Convert content into a single MathML element.
+    fn group(
+        &mut self,
+        content: &Content,
+        styles: StyleChain,
+        style: MathStyle,
+    ) -> SourceResult<String> {
+        let mut row = Row::default();
+        self.accept(&mut row, content, styles, style)?;
+        Ok(row.finish())
+    }
+
+    /// Convert content into the given row.
+    fn accept(
+        &mut self,
+        row: &mut Row,
+        content: &Content,
+        styles: StyleChain,
+        style: MathStyle,
+    ) -> SourceResult<()> {
+        // Like in layout, the bodies of nested equations are directly
+        // included.
+        if let Some(elem) = content.to::<EquationElem>() {
+            return self.accept(row, &elem.body(), styles, style);
+        }
+
+        if let Some(realized) = realize(self.vt, content, styles)? {
+            return self.accept(row, &realized, styles, style);
+        }
+
+        if let Some(children) = content.to_sequence() {
+            for child in children {
+                self.accept(row, child, styles, style)?;
+            }
+            return Ok(());
+        }
+
+        if let Some((elem, local)) = content.to_styled() {
+            return self.accept(row, elem, styles.chain(local), style);
+        }
+
+        if content.is::<SpaceElem>() {
+            row.space();
+        } else if content.is::<LinebreakElem>() {
+            row.linebreak();
+        } else if content.is::<AlignPointElem>() {
+            row.align();
+        } else if let Some(elem) = content.to::<HElem>() {
+            if let Spacing::Rel(rel) = elem.amount() {
+                if rel.rel.is_zero() {
+                    row.push(Item::Space(rel.abs));
+                }
+            }
+        } else if let Some(elem) = content.to::<TextElem>() {
+            let text = elem.text();
+            if !text.is_empty() {
+                row.push(convert_text(&text, style));
+            }
+        } else if !self.convert(row, content, styles, style)? {
+            // Content without a counterpart in MathML, like a box, is included
+            // with its plain text.
+            let text = content.plain_text();
+            if !text.is_empty() {
+                row.push(Item::Other(format!("<mtext>{}</mtext>", Escaped(&text))));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Convert a math element into its MathML counterpart.
+    ///
+    /// Returns whether the element was converted.
+    fn convert(
+        &mut self,
+        row: &mut Row,
+        content: &Content,
+        styles: StyleChain,
+        style: MathStyle,
+    ) -> SourceResult<bool> {
+        let mathml = if let Some(elem) = content.to::<FracElem>() {
+            let num = self.group(&elem.num(), styles, style.for_numerator())?;
+            let denom = self.group(&elem.denom(), styles, style.for_denominator())?;
+            format!("<mfrac>{num}{denom}</mfrac>")
+        } else if let Some(elem) = content.to::<BinomElem>() {
+            let upper = self.group(&elem.upper(), styles, style.for_numerator())?;
+            let lower = self.group(&elem.lower(), styles, style.for_denominator())?;
+            format!(
+                "<mrow><mo>(</mo><mfrac linethickness=\"0\">{upper}{lower}</mfrac>\
+                 <mo>)</mo></mrow>"
+            )
+        } else if let Some(elem) = content.to::<AttachElem>() {
+            self.attach(elem, styles, style)?
+        } else if let Some(elem) = content.to::<PrimesElem>() {
+            let primes = match elem.count() {
+                1 => "′".into(),
+                2 => "″".into(),
+                3 => "‴".into(),
+                4 => "⁗".into(),
+                count => "′".repeat(count),
+            };
+            format!("<mo>{primes}</mo>")
+        } else if let Some(elem) = content.to::<ScriptsElem>() {
+            self.group(&elem.body(), styles, style)?
+        } else if let Some(elem) = content.to::<LimitsElem>() {
+            self.group(&elem.body(), styles, style)?
+        } else if let Some(elem) = content.to::<AccentElem>() {
+            let base = self.group(&elem.base(), styles, style.with_cramped(true))?;
+            let accent =
+                Escaped(&spacing_accent(elem.accent().0).to_string()).to_string();
+            format!("<mover accent=\"true\">{base}<mo>{accent}</mo></mover>")
+        } else if let Some(elem) = content.to::<UnderlineElem>() {
+            let body = self.group(&elem.body(), styles, style)?;
+            format!("<munder accentunder=\"true\">{body}<mo>_</mo></munder>")
+        } else if let Some(elem) = content.to::<OverlineElem>() {
+            let body = self.group(&elem.body(), styles, style.with_cramped(true))?;
+            format!("<mover accent=\"true\">{body}<mo>‾</mo></mover>")
+        } else if let Some(elem) = content.to::<UnderbraceElem>() {
+            self.spreader(
+                &elem.body(),
+                elem.annotation(styles),
+                '⏟',
+                true,
+                styles,
+                style,
+            )?
+        } else if let Some(elem) = content.to::<OverbraceElem>() {
+            self.spreader(
+                &elem.body(),
+                elem.annotation(styles),
+                '⏞',
+                false,
+                styles,
+                style,
+            )?
+        } else if let Some(elem) = content.to::<UnderbracketElem>() {
+            self.spreader(
+                &elem.body(),
+                elem.annotation(styles),
+                '⎵',
+                true,
+                styles,
+                style,
+            )?
+        } else if let Some(elem) = content.to::<OverbracketElem>() {
+            self.spreader(
+                &elem.body(),
+                elem.annotation(styles),
+                '⎴',
+                false,
+                styles,
+                style,
+            )?
+        } else if let Some(elem) = content.to::<CancelElem>() {
+            let notation = match (elem.cross(styles), elem.inverted(styles)) {
+                (true, _) => "updiagonalstrike downdiagonalstrike",
+                (false, false) => "updiagonalstrike",
+                (false, true) => "downdiagonalstrike",
+            };
+            let body = self.group(&elem.body(), styles, style)?;
+            format!("<menclose notation=\"{notation}\">{body}</menclose>")
+        } else if let Some(elem) = content.to::<RootElem>() {
+            let radicand =
+                self.group(&elem.radicand(), styles, style.with_cramped(true))?;
+            match elem.index(styles) {
+                Some(index) => {
+                    let size = style.with_size(MathSize::ScriptScript);
+                    let index = self.group(&index, styles, size)?;
+                    format!("<mroot>{radicand}{index}</mroot>")
+                }
+                None => format!("<msqrt>{radicand}</msqrt>"),
+            }
+        } else if let Some(elem) = content.to::<LrElem>() {
+            let mut body = elem.body();
+            if let Some(inner) = body.to::<LrElem>() {
+                if inner.size(styles).is_auto() {
+                    body = inner.body();
+                }
+            }
+
+            // Delimiters in a row of their own stretch to its height.
+            let mut inner = Row::default();
+            self.accept(&mut inner, &body, styles, style)?;
+            inner.finish_mrow()
+        } else if let Some(elem) = content.to::<VecElem>() {
+            let rows: Vec<_> =
+                elem.children().into_iter().map(|child| vec![child]).collect();
+            let table = self.table(&rows, None, styles, style)?;
+            delimited(&table, elem.delim(styles).map(|d| (d.open(), d.close())))
+        } else if let Some(elem) = content.to::<MatElem>() {
+            let table = self.table(&elem.rows(), None, styles, style)?;
+            delimited(&table, elem.delim(styles).map(|d| (d.open(), d.close())))
+        } else if let Some(elem) = content.to::<CasesElem>() {
+            let rows: Vec<_> =
+                elem.children().into_iter().map(|child| vec![child]).collect();
+            let table = self.table(&rows, Some("left"), styles, style)?;
+            let open = Escaped(&elem.delim(styles).open().to_string()).to_string();
+            format!("<mrow><mo>{open}</mo>{table}</mrow>")
+        } else if let Some(elem) = content.to::<OpElem>() {
+            // Single letters would otherwise be italicized.
+            let text = elem.text();
+            let upright =
+                if text.chars().count() == 1 { " mathvariant=\"normal\"" } else { "" };
+            format!("<mi{upright}>{}</mi>", Escaped(&text))
+        } else if let Some(elem) = content.to::<ClassElem>() {
+            self.group(&elem.body(), styles, style.with_class(elem.class()))?
+        } else if let Some(elem) = content.to::<MathStyleElem>() {
+            let mut inner = style;
+            if let Some(variant) = elem.variant(StyleChain::default()) {
+                inner = inner.with_variant(variant);
+            }
+            if let Some(bold) = elem.bold(StyleChain::default()) {
+                inner = inner.with_bold(bold);
+            }
+            if let Some(italic) = elem.italic(StyleChain::default()) {
+                inner = inner.with_italic(italic);
+            }
+            if let Some(cramped) = elem.cramped(StyleChain::default()) {
+                inner = inner.with_cramped(cramped);
+            }
+
+            let Some(size) = elem.size(StyleChain::default()) else {
+                self.accept(row, &elem.body(), styles, inner)?;
+                return Ok(true);
+            };
+
+            let (display, level) = match size {
+                MathSize::Display => (true, 0),
+                MathSize::Text => (false, 0),
+                MathSize::Script => (false, 1),
+                MathSize::ScriptScript => (false, 2),
+            };
+
+            let mut body = Row::default();
+            self.accept(&mut body, &elem.body(), styles, inner.with_size(size))?;
+            format!(
+                "<mrow displaystyle=\"{display}\" scriptlevel=\"{level}\">{}</mrow>",
+                body.finish_items()
+            )
+        } else {
+            return Ok(false);
+        };
+
+        let operator = mathml.starts_with("<mo>");
+        row.push(if operator { Item::Operator(mathml) } else { Item::Other(mathml) });
+        Ok(true)
+    }
+
+    /// Convert a base with attachments.
+    fn attach(
+        &mut self,
+        elem: &AttachElem,
+        styles: StyleChain,
+        style: MathStyle,
+    ) -> SourceResult<String> {
+        let base = elem.base();
+        let mut attachment = |content: Option<Content>, style: MathStyle| {
+            content.map(|content| self.group(&content, styles, style)).transpose()
+        };
+
+        let sup = style.for_superscript();
+        let tl = attachment(elem.tl(styles), sup)?;
+        let tr = attachment(elem.tr(styles), sup)?;
+        let t = attachment(elem.t(styles), sup)?;
+
+        let sub = style.for_subscript();
+        let bl = attachment(elem.bl(styles), sub)?;
+        let br = attachment(elem.br(styles), sub)?;
+        let b = attachment(elem.b(styles), sub)?;
+
+        // Top and bottom attachments are placed on the right unless the base
+        // uses limits.
+        let limits = match limits(&base, styles) {
+            Limits::Always => true,
+            Limits::Display => style.size == MathSize::Display,
+            Limits::Never => false,
+        };
+        let (t, tr) = if limits || tr.is_some() { (t, tr) } else { (None, t) };
+        let (b, br) = if limits || br.is_some() { (b, br) } else { (None, b) };
+
+        let mut mathml = self.group(&base, styles, style)?;
+        mathml = match (b, t) {
+            (Some(b), Some(t)) => format!("<munderover>{mathml}{b}{t}</munderover>"),
+            (Some(b), None) => format!("<munder>{mathml}{b}</munder>"),
+            (None, Some(t)) => format!("<mover>{mathml}{t}</mover>"),
+            (None, None) => mathml,
+        };
+
+        if tl.is_some() || bl.is_some() {
+            let none = || "<none/>".to_string();
+            let [br, tr, bl, tl] =
+                [br, tr, bl, tl].map(|script| script.unwrap_or_else(none));
+            return Ok(format!(
+                "<mmultiscripts>{mathml}{br}{tr}<mprescripts/>{bl}{tl}</mmultiscripts>"
+            ));
+        }
+
+        Ok(match (br, tr) {
+            (Some(br), Some(tr)) => format!("<msubsup>{mathml}{br}{tr}</msubsup>"),
+            (Some(br), None) => format!("<msub>{mathml}{br}</msub>"),
+            (None, Some(tr)) => format!("<msup>{mathml}{tr}</msup>"),
+            (None, None) => mathml,
+        })
+    }
+
+    /// Convert a horizontal brace or bracket with an optional annotation.
+    fn spreader(
+        &mut self,
+        body: &Content,
+        annotation: Option<Content>,
+        c: char,
+        under: bool,
+        styles: StyleChain,
+        style: MathStyle,
+    ) -> SourceResult<String> {
+        let body = self.group(body, styles, style)?;
+        let (tag, accent) =
+            if under { ("munder", "accentunder") } else { ("mover", "accent") };
+
+        let spread = format!("<{tag} {accent}=\"true\">{body}<mo>{c}</mo></{tag}>");
+        Ok(match annotation {
+            Some(annotation) => {
+                let size =
+                    if under { style.for_subscript() } else { style.for_superscript() };
+                let annotation = self.group(&annotation, styles, size)?;
+                format!("<{tag}>{spread}{annotation}</{tag}>")
+            }
+            None => spread,
+        })
+    }
+
+    /// Convert the rows of a matrix-like element into a table.
+    fn table(
+        &mut self,
+        rows: &[Vec<Content>],
+        align: Option<&str>,
+        styles: StyleChain,
+        style: MathStyle,
+    ) -> SourceResult<String> {
+        let style = style.for_denominator();
+        let align = align
+            .map(|align| format!(" columnalign=\"{align}\""))
+            .unwrap_or_default();
+
+        let mut mathml = String::from("<mtable>");
+        for row in rows {
+            mathml.push_str("<mtr>");
+            for cell in row {
+                let cell = self.group(cell, styles, style)?;
+                write!(mathml, "<mtd{align}>{cell}</mtd>").unwrap();
+            }
+            mathml.push_str("</mtr>");
+        }
+        mathml.push_str("</mtable>");
+        Ok(mathml)
+    }
+}
+
+/// Collects the MathML of a row of items, which may be split into multiple
+/// lines by linebreaks and into columns by alignment points.
+struct Row {
+    /// The lines, each consisting of its cells, each consisting of its items.
+    lines: Vec<Vec<Vec<Item>>>,
+    /// Whether a space was seen since the last item.
+    space: bool,
+}
+
+/// An item in a row.
+enum Item {
+    /// An operator, around which the MathML renderer adds spacing.
+    Operator(String),
+    /// Explicit spacing.
+    Space(Length),
+    /// Any other item.
+    Other(String),
+}
+
+impl Default for Row {
+    fn default() -> Self {
+        Self { lines: vec![vec![vec![]]], space: false }
+    }
+}
+
+impl Row {
+    /// Add an item.
+    fn push(&mut self, item: Item) {
+        // Spaces between ordinary items are kept while operators bring their
+        // own spacing, just like in layout.
+        let space = std::mem::take(&mut self.space);
+        let ordinary = |item: &Item| matches!(item, Item::Other(_));
+        let items = self.cell();
+        if space && ordinary(&item) && items.last().map_or(false, ordinary) {
+            items.push(Item::Space(SPACE.into()));
+        }
+
+        items.push(item);
+    }
+
+    /// Note a space between the previous and the next item.
+    fn space(&mut self) {
+        self.space = true;
+    }
+
+    /// Start a new line.
+    fn linebreak(&mut self) {
+        self.space = false;
+        self.lines.push(vec![vec![]]);
+    }
+
+    /// Start a new column at an alignment point.
+    fn align(&mut self) {
+        self.space = false;
+        self.lines.last_mut().unwrap().push(vec![]);
+    }
+
+    /// The cell that items are added to.
+    fn cell(&mut self) -> &mut Vec<Item> {
+        self.lines.last_mut().unwrap().last_mut().unwrap()
+    }
+
+    /// Finish the row into a single element.
+    fn finish(mut self) -> String {
+        if self.lines.len() == 1 && self.lines[0].len() == 1 {
+            let mut items = self.lines.pop().unwrap().pop().unwrap();
+            if items.len() == 1 {
+                return items.pop().unwrap().into_mathml();
+            }
+        }
+        self.finish_mrow()
+    }
+
+    /// Finish the row into an `<mrow>` element.
+    fn finish_mrow(self) -> String {
+        format!("<mrow>{}</mrow>", self.finish_items())
+    }
+
+    /// Finish the row into a sequence of elements.
+    ///
+    /// Multiple lines are laid out in a table, whose columns are alternately
+    /// aligned to the right and left like in layout.
+    fn finish_items(mut self) -> String {
+        if self.lines.len() == 1 && self.lines[0].len() == 1 {
+            let items = self.lines.pop().unwrap().pop().unwrap();
+            return items.into_iter().map(Item::into_mathml).collect();
+        }
+
+        let aligned = self.lines.iter().any(|line| line.len() > 1);
+        let mut mathml = String::from("<mtable>");
+        for line in self.lines {
+            mathml.push_str("<mtr>");
+            for (i, cell) in line.into_iter().enumerate() {
+                let items: String = cell.into_iter().map(Item::into_mathml).collect();
+                let align = match (aligned, i % 2) {
+                    (false, _) => "",
+                    (true, 0) => " columnalign=\"right\"",
+                    (true, _) => " columnalign=\"left\"",
+                };
+                write!(mathml, "<mtd{align}><mrow>{items}</mrow></mtd>").unwrap();
+            }
+            mathml.push_str("</mtr>");
+        }
+        mathml.push_str("</mtable>");
+        mathml
+    }
+}
+
+impl Item {
+    /// The item's MathML.
+    fn into_mathml(self) -> String {
+        match self {
+            Self::Operator(mathml) | Self::Other(mathml) => mathml,
+            Self::Space(length) => {
+                let width = if length.abs.is_zero() {
+                    format!("{}em", length.em.get())
+                } else {
+                    format!("{}pt", length.abs.to_pt())
+                };
+                format!("<mspace width=\"{width}\"/>")
+            }
+        }
+    }
+}
+
+/// Convert text in an equation.
+///
+/// Like in layout, single characters are classified by their math class and
+/// longer text is either a number or upright text.
+fn convert_text(text: &str, style: MathStyle) -> Item {
+    let mut chars = text.chars();
+    let (Some(c), None) = (chars.next(), chars.next()) else {
+        if text.chars().all(|c| c.is_ascii_digit() || c == '.') {
+            let number: String = text.chars().map(|c| style.styled_char(c)).collect();
+            return Item::Other(format!("<mn>{}</mn>", Escaped(&number)));
+        }
+
+        let style =
+            if style.italic == Smart::Auto { style.with_italic(false) } else { style };
+        let text: String = text.chars().map(|c| style.styled_char(c)).collect();
+        return Item::Other(format!("<mtext>{}</mtext>", Escaped(&text)));
+    };
+
+    let class = style.class.as_custom().or(match c {
+        ':' => Some(MathClass::Relation),
+        '⋯' | '⋱' | '⋰' | '⋮' => Some(MathClass::Normal),
+        _ => unicode_math_class::class(c),
+    });
+
+    // In the default style, MathML renderers italicize letters themselves.
+    let plain = style.variant == MathVariant::Serif && !style.bold;
+    let styled =
+        if plain && style.italic == Smart::Auto { c } else { style.styled_char(c) };
+    let escaped = Escaped(&styled.to_string()).to_string();
+
+    match class {
+        _ if c.is_ascii_digit() => Item::Other(format!("<mn>{escaped}</mn>")),
+        None | Some(MathClass::Normal | MathClass::Alphabetic | MathClass::Special) => {
+            let upright = style.italic == Smart::Custom(false) && styled == c;
+            let variant = if upright { " mathvariant=\"normal\"" } else { "" };
+            Item::Other(format!("<mi{variant}>{escaped}</mi>"))
+        }
+        Some(_) => {
+            let attrs = match style.class {
+                Smart::Custom(MathClass::Large) => " largeop=\"true\"",
+                Smart::Custom(MathClass::Opening) => " form=\"prefix\"",
+                Smart::Custom(MathClass::Closing) => " form=\"postfix\"",
+                Smart::Custom(MathClass::Binary | MathClass::Relation) => {
+                    " form=\"infix\""
+                }
+                _ => "",
+            };
+            Item::Operator(format!("<mo{attrs}>{escaped}</mo>"))
+        }
+    }
+}
+
+/// In which situations the base of an attachment displays it as a limit.
+fn limits(base: &Content, styles: StyleChain) -> Limits {
+    if let Some(elem) = base.to::<LimitsElem>() {
+        if elem.inline(styles) {
+            Limits::Always
+        } else {
+            Limits::Display
+        }
+    } else if base.is::<ScriptsElem>() {
+        Limits::Never
+    } else if let Some(elem) = base.to::<OpElem>() {
+        if elem.limits(styles) {
+            Limits::Display
+        } else {
+            Limits::Never
+        }
+    } else if let Some(elem) = base.to::<TextElem>() {
+        let text = elem.text();
+        let mut chars = text.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Limits::for_char(c),
+            _ => Limits::Never,
+        }
+    } else if let Some((elem, _)) = base.to_styled() {
+        limits(elem, styles)
+    } else {
+        Limits::Never
+    }
+}
+
+/// The spacing counterpart of a combining accent, which MathML renderers know
+/// how to stretch.
+fn spacing_accent(c: char) -> char {
+    match c {
+        '\u{300}' => '`',
+        '\u{301}' => '´',
+        '\u{302}' => '^',
+        '\u{303}' => '~',
+        '\u{304}' => '¯',
+        '\u{306}' => '˘',
+        '\u{307}' => '˙',
+        '\u{308}' => '¨',
+        '\u{30a}' => '˚',
+        '\u{30b}' => '˝',
+        '\u{30c}' => 'ˇ',
+        '\u{20d6}' => '←',
+        '\u{20d7}' => '→',
+        _ => c,
+    }
+}
+
+/// Surround a table with delimiters.
+fn delimited(table: &str, delims: Option<(char, char)>) -> String {
+    match delims {
+        Some((open, close)) => {
+            let (open, close) = (open.to_string(), close.to_string());
+            let (open, close) = (Escaped(&open), Escaped(&close));
+            format!("<mrow><mo>{open}</mo>{table}<mo>{close}</mo></mrow>")
+        }
+        None => table.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STYLE: MathStyle = MathStyle {
+        variant: MathVariant::Serif,
+        size: MathSize::Text,
+        class: Smart::Auto,
+        cramped: false,
+        bold: false,
+        italic: Smart::Auto,
+    };
+
+    fn text(text: &str, style: MathStyle) -> String {
+        convert_text(text, style).into_mathml()
+    }
+
+    #[test]
+    fn test_convert_text() {
+        assert_eq!(text("x", STYLE), "<mi>x</mi>");
+        assert_eq!(text("1", STYLE), "<mn>1</mn>");
+        assert_eq!(text("12.5", STYLE), "<mn>12.5</mn>");
+        assert_eq!(text("sin", STYLE), "<mtext>sin</mtext>");
+        assert_eq!(text("+", STYLE), "<mo>+</mo>");
+        assert_eq!(text("<", STYLE), "<mo>&lt;</mo>");
+        assert_eq!(
+            text("x", STYLE.with_italic(false)),
+            "<mi mathvariant=\"normal\">x</mi>"
+        );
+        assert_eq!(
+            text("∑", STYLE.with_class(MathClass::Large)),
+            "<mo largeop=\"true\">∑</mo>"
+        );
+        assert!(matches!(convert_text(":", STYLE), Item::Operator(_)));
+        assert!(matches!(convert_text("x", STYLE), Item::Other(_)));
+    }
+
+    #[test]
+    fn test_row_spacing() {
+        let mut row = Row::default();
+        row.push(convert_text("a", STYLE));
+        row.space();
+        row.push(convert_text("b", STYLE));
+        row.space();
+        row.push(convert_text("+", STYLE));
+        row.space();
+        row.push(convert_text("c", STYLE));
+        assert_eq!(
+            row.finish(),
+            "<mrow><mi>a</mi><mspace width=\"0.25em\"/><mi>b</mi>\
+             <mo>+</mo><mi>c</mi></mrow>"
+        );
+
+        let mut row = Row::default();
+        row.push(convert_text("a", STYLE));
+        assert_eq!(row.finish(), "<mi>a</mi>");
+    }
+
+    #[test]
+    fn test_row_lines() {
+        let mut row = Row::default();
+        row.push(convert_text("a", STYLE));
+        row.align();
+        row.push(convert_text("=", STYLE));
+        row.push(convert_text("b", STYLE));
+        row.linebreak();
+        row.push(convert_text("c", STYLE));
+        assert_eq!(
+            row.finish(),
+            "<mrow><mtable>\
+             <mtr><mtd columnalign=\"right\"><mrow><mi>a</mi></mrow></mtd>\
+             <mtd columnalign=\"left\"><mrow><mo>=</mo><mi>b</mi></mrow></mtd></mtr>\
+             <mtr><mtd columnalign=\"right\"><mrow><mi>c</mi></mrow></mtd></mtr>\
+             </mtable></mrow>"
+        );
+    }
+
+    #[test]
+    fn test_delimited() {
+        assert_eq!(delimited("<mtable></mtable>", None), "<mtable></mtable>");
+        assert_eq!(
+            delimited("<mtable></mtable>", Some(('<', ')'))),
+            "<mrow><mo>&lt;</mo><mtable></mtable><mo>)</mo></mrow>"
+        );
+    }
+
+    #[test]
+    fn test_spacing_accent() {
+        assert_eq!(spacing_accent('\u{302}'), '^');
+        assert_eq!(spacing_accent('\u{20d7}'), '→');
+        assert_eq!(spacing_accent('x'), 'x');
+    }
+}
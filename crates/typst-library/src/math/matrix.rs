@@ -193,7 +193,7 @@ pub enum Delimiter {
 
 impl Delimiter {
     /// The delimiter's opening character.
-    fn open(self) -> char {
+    pub(super) fn open(self) -> char {
         match self {
             Self::Paren => '(',
             Self::Bracket => '[',
@@ -204,7 +204,7 @@ impl Delimiter {
     }
 
     /// The delimiter's closing character.
-    fn close(self) -> char {
+    pub(super) fn close(self) -> char {
         match self {
             Self::Paren => ')',
             Self::Bracket => ']',
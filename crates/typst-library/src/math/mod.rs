@@ -10,6 +10,7 @@ mod class;
 mod delimited;
 mod frac;
 mod fragment;
+mod mathml;
 mod matrix;
 mod op;
 mod root;
@@ -26,6 +27,7 @@ pub use self::cancel::*;
 pub use self::class::*;
 pub use self::delimited::*;
 pub use self::frac::*;
+pub use self::mathml::*;
 pub use self::matrix::*;
 pub use self::op::*;
 pub use self::root::*;
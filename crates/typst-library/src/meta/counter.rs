@@ -363,6 +363,28 @@ impl Counter {
         Ok(state)
     }
 
+    /// Get the first number of the page counter on each of the first `pages`
+    /// physical pages, after all updates on that page.
+    ///
+    /// This walks the counter's sequence just once for all pages.
+    pub fn page_numbers(&self, vt: &mut Vt, pages: usize) -> SourceResult<Vec<usize>> {
+        let sequence = self.sequence(vt)?;
+        let mut numbers = Vec::with_capacity(pages);
+        let mut offset = 0;
+        for page in 1..=pages {
+            while sequence.get(offset + 1).map_or(false, |(_, at)| at.get() <= page) {
+                offset += 1;
+            }
+
+            let (mut state, at) = sequence[offset].clone();
+            if self.is_page() {
+                state.step(NonZeroUsize::ONE, page.saturating_sub(at.get()));
+            }
+            numbers.push(state.first());
+        }
+        Ok(numbers)
+    }
+
     /// Get the current and final value of the state combined in one state.
     pub fn both(&self, vt: &mut Vt, location: Location) -> SourceResult<CounterState> {
         let sequence = self.sequence(vt)?;
@@ -1,3 +1,6 @@
+use typst::eval::Datetime;
+
+use super::{Counter, CounterKey};
 use crate::layout::{LayoutRoot, PageElem};
 use crate::prelude::*;
 
@@ -29,6 +32,47 @@ pub struct DocumentElem {
     /// The document's authors.
     pub author: Author,
 
+    /// A short description of the document's content. This is embedded as the
+    /// PDF's subject.
+    pub description: Option<EcoString>,
+
+    /// The document's keywords.
+    pub keywords: Keywords,
+
+    /// The document's creation date.
+    ///
+    /// If this is `{auto}` (default), the current date is used. Set it to
+    /// `{none}` to embed no date, for instance, to get reproducible output.
+    /// The command line interface uses the date given by the
+    /// `SOURCE_DATE_EPOCH` environment variable instead of the current one if
+    /// it is set.
+    ///
+    /// ```example
+    /// #set document(date: datetime(year: 2023, month: 7, day: 1))
+    /// ```
+    #[default(Smart::Auto)]
+    pub date: Smart<Option<Datetime>>,
+
+    /// Additional metadata as a dictionary from keys to strings. The keys may
+    /// only contain ASCII letters, digits, hyphens, and underscores.
+    ///
+    /// ```example
+    /// #set document(custom: (project: "Apollo", revision: "3"))
+    /// ```
+    pub custom: CustomMetadata,
+
+    /// Whether to tag the document with its logical structure.
+    ///
+    /// Tagged PDF files contain a structure tree that assistive technology,
+    /// like screen readers, uses to navigate the document. Headings, figures
+    /// and footnotes are always part of it. Paragraphs, lists and tables are
+    /// only tracked in tagged documents as this makes layout a bit slower.
+    ///
+    /// ```example
+    /// #set document(tagged: true)
+    /// ```
+    pub tagged: bool,
+
     /// The page runs.
     #[internal]
     #[variadic]
@@ -66,10 +110,27 @@ impl LayoutRoot for DocumentElem {
             }
         }
 
+        // Record the logical page numbers so that exporters can label the
+        // pages the way they are numbered.
+        let numbers = Counter::new(CounterKey::Page).page_numbers(vt, pages.len())?;
+        for (frame, number) in pages.iter_mut().zip(numbers) {
+            let meta = FrameItem::Meta(Meta::PageNumber(number), Size::zero());
+            frame.push(Point::zero(), meta);
+        }
+
+        let date = match self.date(styles) {
+            Smart::Auto => vt.world.today(None),
+            Smart::Custom(date) => date,
+        };
+
         Ok(Document {
             pages,
             title: self.title(styles),
             author: self.author(styles).0,
+            description: self.description(styles),
+            keywords: self.keywords(styles).0,
+            date,
+            custom: self.custom(styles).0,
         })
     }
 }
@@ -84,3 +145,39 @@ cast! {
     v: EcoString => Self(vec![v]),
     v: Array => Self(v.into_iter().map(Value::cast).collect::<StrResult<_>>()?),
 }
+
+/// A list of keywords.
+#[derive(Debug, Default, Clone, Hash)]
+pub struct Keywords(Vec<EcoString>);
+
+cast! {
+    Keywords,
+    self => self.0.into_value(),
+    v: EcoString => Self(vec![v]),
+    v: Array => Self(v.into_iter().map(Value::cast).collect::<StrResult<_>>()?),
+}
+
+/// Additional metadata entries.
+#[derive(Debug, Default, Clone, Hash)]
+pub struct CustomMetadata(Vec<(EcoString, EcoString)>);
+
+cast! {
+    CustomMetadata,
+    self => self.0
+        .into_iter()
+        .map(|(key, value)| (key.into(), value.into_value()))
+        .collect::<Dict>()
+        .into_value(),
+    v: Dict => Self(v.into_iter().map(custom_entry).collect::<StrResult<_>>()?),
+}
+
+/// Check and convert an entry of the custom metadata.
+fn custom_entry((key, value): (Str, Value)) -> StrResult<(EcoString, EcoString)> {
+    let valid = key.starts_with(|c: char| c.is_ascii_alphabetic())
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        bail!("invalid metadata key: {}", key.as_str());
+    }
+
+    Ok((key.into(), value.cast()?))
+}
@@ -279,7 +279,8 @@ impl Show for FootnoteEntry {
             sup,
             HElem::new(number_gap.into()).with_weak(true).pack(),
             note.body_content().unwrap(),
-        ]))
+        ])
+        .tagged(self.0.clone(), loc.variant(2)))
     }
 }
 
@@ -47,6 +47,11 @@ pub struct LinkElem {
     ///     counted from one, and the coordinates are relative to the page's top
     ///     left corner.
     ///
+    /// - To link to another PDF file, `dest` should be a dictionary with a
+    ///   `file` key holding the file's path and a `name` key holding the name
+    ///   of the destination within the file. In PDF export, every labelled
+    ///   element is exported as a destination named after its label.
+    ///
     /// ```example
     /// = Introduction <intro>
     /// #link("mailto:hello@typst.app") \
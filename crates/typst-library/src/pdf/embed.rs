@@ -0,0 +1,96 @@
+use std::path::Path;
+
+use typst::util::Bytes;
+
+use crate::prelude::*;
+
+/// Attaches a file to the PDF document.
+///
+/// Embedded files travel with the PDF and show up in the attachment panel of
+/// PDF viewers. This is useful to ship the data a document was generated from
+/// alongside it. The element itself is invisible and other export formats
+/// ignore it.
+///
+/// By default, the file is read from the given path. If a string is passed as
+/// a second argument, it is embedded as the file's contents instead and the
+/// path only serves as the file's name.
+///
+/// ## Example { #example }
+/// ```example
+/// #pdf.embed(
+///   "/files/data.csv",
+///   description: "The raw measurements",
+///   mime-type: "text/csv",
+/// )
+///
+/// #pdf.embed(
+///   "summary.json",
+///   "{\"total\": 42}",
+///   mime-type: "application/json",
+///   annotate: true,
+/// )
+/// ```
+///
+/// Display: Embed
+/// Category: meta
+#[element(Show)]
+pub struct EmbedElem {
+    /// Path to the file to embed. The embedded file is named after the last
+    /// component of the path.
+    #[required]
+    #[parse(
+        let Spanned { v: path, span } =
+            args.expect::<Spanned<EcoString>>("path to file")?;
+        let data = match args.eat::<EcoString>()? {
+            Some(text) => Bytes::from(text.as_bytes()),
+            None => {
+                let id = vm.location().join(&path).at(span)?;
+                vm.world().file(id).at(span)?
+            }
+        };
+        path
+    )]
+    pub path: EcoString,
+
+    /// The raw file data.
+    #[internal]
+    #[required]
+    #[parse(data)]
+    pub data: Bytes,
+
+    /// A description of the file, which viewers show next to its name.
+    pub description: Option<EcoString>,
+
+    /// The file's MIME type, like `{"text/csv"}`.
+    pub mime_type: Option<EcoString>,
+
+    /// Whether to also attach the file to its position on the page. Viewers
+    /// show an icon there that opens the file.
+    #[default(false)]
+    pub annotate: bool,
+}
+
+impl Show for EmbedElem {
+    #[tracing::instrument(name = "EmbedElem::show", skip_all)]
+    fn show(&self, _: &mut Vt, styles: StyleChain) -> SourceResult<Content> {
+        let path = self.path();
+        let name = Path::new(path.as_str())
+            .file_name()
+            .and_then(|name| name.to_str())
+            .map_or_else(|| path.clone(), EcoString::from);
+
+        let file = EmbeddedFile {
+            name,
+            data: self.data(),
+            description: self.description(styles),
+            mime_type: self.mime_type(styles),
+            annotate: self.annotate(styles),
+            span: self.span(),
+        };
+
+        Ok(MetaElem::new()
+            .pack()
+            .spanned(self.span())
+            .styled(MetaElem::set_data(vec![Meta::File(file)])))
+    }
+}
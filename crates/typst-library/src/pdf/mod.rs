@@ -0,0 +1,14 @@
+//! PDF-specific functionality.
+
+mod embed;
+
+pub use self::embed::*;
+
+use crate::prelude::*;
+
+/// Hook up all PDF definitions.
+pub fn module() -> Module {
+    let mut scope = Scope::deduplicating();
+    scope.define("embed", EmbedElem::func());
+    Module::new("pdf").with_scope(scope)
+}
@@ -25,7 +25,7 @@ pub use typst::model::{
     element, Behave, Behaviour, Construct, Content, ElemFunc, Element, Finalize, Fold,
     Introspector, Label, Locatable, LocatableSelector, Location, Locator, MetaElem,
     PlainText, Resolve, Selector, Set, Show, StyleChain, StyleVec, Styles, Synthesize,
-    Unlabellable, Vt,
+    Tagged, Unlabellable, Vt,
 };
 #[doc(no_inline)]
 pub use typst::syntax::{FileId, Span, Spanned};
@@ -23,6 +23,14 @@ pub trait ContentExt {
     /// Should be used in combination with [`Location::variant`].
     fn backlinked(self, loc: Location) -> Self;
 
+    /// Mark the frames produced by this content as belonging to the given
+    /// element, which is assigned the given location.
+    ///
+    /// This allows exporters to map parts of an element that aren't realized
+    /// on their own (like list items) back to it. Should be used in
+    /// combination with [`Location::variant`].
+    fn tagged(self, elem: Content, loc: Location) -> Self;
+
     /// Set alignments for this content.
     fn aligned(self, aligns: Axes<Option<GenAlign>>) -> Self;
 
@@ -56,6 +64,11 @@ impl ContentExt for Content {
         self.styled(MetaElem::set_data(vec![Meta::Elem(backlink)]))
     }
 
+    fn tagged(self, mut elem: Content, loc: Location) -> Self {
+        elem.set_location(loc);
+        self.styled(MetaElem::set_data(vec![Meta::Elem(elem)]))
+    }
+
     fn aligned(self, aligns: Axes<Option<GenAlign>>) -> Self {
         self.styled(AlignElem::set_alignment(aligns))
     }
@@ -2,6 +2,7 @@
 
 mod behave;
 mod ext;
+pub(crate) mod reflow;
 
 pub use behave::*;
 pub use ext::*;
@@ -0,0 +1,506 @@
+//! Conversion of content into reflowable markup like HTML and Markdown.
+
+use std::marker::PhantomData;
+
+use typst::model::{realize, realize_recipes};
+
+use crate::layout::{
+    ColumnsElem, HElem, PadElem, PageElem, ParElem, ParbreakElem, PlaceElem, RepeatElem,
+};
+use crate::math::{EquationElem, LayoutMath};
+use crate::meta::{Counter, HeadingElem};
+use crate::prelude::*;
+use crate::text::{Quoter, Quotes, SmartQuoteElem, SpaceElem, TextElem};
+
+/// The character that stands in for embedded objects when substituting smart
+/// quotes.
+const OBJ_REPLACE: char = '\u{FFFC}';
+
+/// A markup format that content can be converted into without laying it out.
+///
+/// The [`Converter`] walks the content, applies show rules and builds
+/// paragraphs and lists from the inline content and list items in it. The
+/// format maps the elements it has a counterpart for onto its markup.
+pub trait Markup: Sized {
+    /// Convert an element that has a counterpart in the format.
+    ///
+    /// Returns whether the element was converted.
+    fn convert(
+        cv: &mut Converter<Self>,
+        flow: &mut Flow<Self>,
+        content: &Content,
+        styles: StyleChain,
+    ) -> SourceResult<bool>;
+
+    /// Convert an element without a base recipe that isn't text, spacing or
+    /// a smart quote.
+    fn leaf(
+        cv: &mut Converter<Self>,
+        flow: &mut Flow<Self>,
+        content: &Content,
+        styles: StyleChain,
+    ) -> SourceResult<()>;
+
+    /// The opening and closing markup of a link to the destination, if the
+    /// format can express it.
+    fn link(cv: &Converter<Self>, dest: &Destination) -> Option<(EcoString, EcoString)>;
+
+    /// Escape text for the format.
+    fn escape(text: &str, markup: &mut String);
+
+    /// Join the markup of a list's items, each with its number.
+    fn list(kind: ListKind, items: Vec<(usize, String)>) -> String;
+
+    /// Called for each piece of text with its styles.
+    fn text(&mut self, _: StyleChain) {}
+}
+
+/// Converts content into markup.
+pub struct Converter<'v, 't, M> {
+    /// The virtual typesetter.
+    pub vt: &'v mut Vt<'t>,
+    /// The format's state.
+    pub markup: M,
+}
+
+impl<'v, 't, M: Markup> Converter<'v, 't, M> {
+    /// Create a new converter.
+    pub fn new(vt: &'v mut Vt<'t>, markup: M) -> Self {
+        Self { vt, markup }
+    }
+
+    /// Convert content into the given flow.
+    pub fn accept(
+        &mut self,
+        flow: &mut Flow<M>,
+        content: &Content,
+        styles: StyleChain,
+    ) -> SourceResult<()> {
+        // Like in layout, math outside of an equation is wrapped in one.
+        if content.can::<dyn LayoutMath>() && !content.is::<EquationElem>() {
+            let equation = EquationElem::new(content.clone()).pack();
+            return self.accept(flow, &equation, styles);
+        }
+
+        if content.needs_preparation() {
+            if let Some(prepared) = realize(self.vt, content, styles)? {
+                return self.accept(flow, &prepared, styles);
+            }
+        }
+
+        if let Some((elem, local)) = content.to_styled() {
+            return self.styled(flow, elem, local, styles);
+        }
+
+        if let Some(children) = content.to_sequence() {
+            for child in children {
+                self.accept(flow, child, styles)?;
+            }
+            return Ok(());
+        }
+
+        // User-defined show rules take precedence over the mapping to the
+        // format.
+        if let Some(realized) = realize_recipes(self.vt, content, styles)? {
+            return self.accept(flow, &realized, styles);
+        }
+
+        if self.convert(flow, content, styles)? {
+            return Ok(());
+        }
+
+        if let Some(realized) = realize(self.vt, content, styles)? {
+            return self.accept(flow, &realized, styles);
+        }
+
+        self.leaf(flow, content, styles)
+    }
+
+    /// Convert content that is placed within an element, surrounding it with
+    /// the element's opening and closing markup.
+    pub fn tagged(
+        &mut self,
+        flow: &mut Flow<M>,
+        open: EcoString,
+        close: EcoString,
+        body: &Content,
+        styles: StyleChain,
+    ) -> SourceResult<()> {
+        flow.open(open, close, false);
+        self.accept(flow, body, styles)?;
+        flow.close();
+        Ok(())
+    }
+
+    /// Convert content into a separate sequence of blocks.
+    pub fn blocks(
+        &mut self,
+        content: &Content,
+        styles: StyleChain,
+    ) -> SourceResult<Vec<Block>> {
+        self.flow(false, content, styles)
+    }
+
+    /// Convert content into a separate flow and return its blocks. Inline
+    /// flows are placed within a line or an element like a list item.
+    pub fn flow(
+        &mut self,
+        inline: bool,
+        content: &Content,
+        styles: StyleChain,
+    ) -> SourceResult<Vec<Block>> {
+        let mut flow = Flow { inline, ..Flow::default() };
+        self.accept(&mut flow, content, styles)?;
+        Ok(flow.finish())
+    }
+
+    /// The body of a heading, prefixed with its number if it is numbered.
+    pub fn heading_body(
+        &mut self,
+        content: &Content,
+        elem: &HeadingElem,
+        styles: StyleChain,
+    ) -> SourceResult<Content> {
+        let mut body = elem.body();
+        if let (Some(numbering), Some(loc)) = (elem.numbering(styles), content.location())
+        {
+            let number = Counter::of(HeadingElem::func())
+                .at(self.vt, loc)?
+                .display(self.vt, &numbering)?;
+            body = number + SpaceElem::new().pack() + body;
+        }
+        Ok(body)
+    }
+
+    /// Convert styled content, turning link metadata into links.
+    fn styled(
+        &mut self,
+        flow: &mut Flow<M>,
+        elem: &Content,
+        local: &Styles,
+        styles: StyleChain,
+    ) -> SourceResult<()> {
+        let styles = styles.chain(local);
+
+        let mut link = None;
+        for meta in MetaElem::data_in(StyleChain::new(local)) {
+            match meta {
+                // Hidden content is still laid out, so its elements are
+                // located to keep the locations of later ones in sync with
+                // the pages.
+                Meta::Hide => return self.accept(&mut Flow::default(), elem, styles),
+                Meta::Link(dest) => link = M::link(self, &dest).or(link),
+                _ => {}
+            }
+        }
+
+        // Links can't be nested, so only the outermost one is kept.
+        match link {
+            Some((open, close)) if !flow.in_link() => {
+                flow.open(open, close, true);
+                self.accept(flow, elem, styles)?;
+                flow.close();
+                Ok(())
+            }
+            _ => self.accept(flow, elem, styles),
+        }
+    }
+
+    /// Convert an element that has a counterpart in the format or that only
+    /// arranges its content on the page.
+    ///
+    /// Returns whether the element was converted.
+    fn convert(
+        &mut self,
+        flow: &mut Flow<M>,
+        content: &Content,
+        styles: StyleChain,
+    ) -> SourceResult<bool> {
+        if M::convert(self, flow, content, styles)? {
+            return Ok(true);
+        }
+
+        if let Some(elem) = content.to::<PadElem>() {
+            self.accept(flow, &elem.body(), styles)?;
+        } else if let Some(elem) = content.to::<ColumnsElem>() {
+            self.accept(flow, &elem.body(), styles)?;
+        } else if let Some(elem) = content.to::<PlaceElem>() {
+            self.accept(flow, &elem.body(), styles)?;
+        } else if let Some(elem) = content.to::<PageElem>() {
+            self.accept(flow, &elem.body(), styles)?;
+            flow.parbreak();
+        } else if let Some(elem) = content.to::<ParElem>() {
+            for child in elem.children() {
+                self.accept(flow, &child, styles)?;
+            }
+            flow.parbreak();
+        } else if content.is::<RepeatElem>() {
+            // Repetitions fill up the remaining space in a line, which
+            // doesn't exist in reflowable output.
+        } else {
+            return Ok(false);
+        }
+
+        Ok(true)
+    }
+
+    /// Convert an element without a base recipe.
+    fn leaf(
+        &mut self,
+        flow: &mut Flow<M>,
+        content: &Content,
+        styles: StyleChain,
+    ) -> SourceResult<()> {
+        if let Some(elem) = content.to::<TextElem>() {
+            self.markup.text(styles);
+            let mut text = elem.text();
+            if let Some(case) = TextElem::case_in(styles) {
+                text = case.apply(&text).into();
+            }
+            flow.text(&text);
+        } else if content.is::<SpaceElem>() {
+            flow.space();
+        } else if let Some(elem) = content.to::<HElem>() {
+            if !elem.weak(styles) && !elem.amount().is_zero() {
+                flow.space();
+            }
+        } else if let Some(elem) = content.to::<SmartQuoteElem>() {
+            let double = elem.double(styles);
+            if SmartQuoteElem::enabled_in(styles) {
+                let quotes = Quotes::from_lang(
+                    TextElem::lang_in(styles),
+                    TextElem::region_in(styles),
+                    SmartQuoteElem::alternative_in(styles),
+                );
+                flow.quote(&quotes, double);
+            } else {
+                flow.text(if double { "\"" } else { "'" });
+            }
+        } else if content.is::<ParbreakElem>() {
+            flow.parbreak();
+        } else {
+            M::leaf(self, flow, content, styles)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Collects the markup for a sequence of blocks, building paragraphs and lists
+/// from the inline content and list items between them.
+pub struct Flow<M> {
+    /// Whether the flow is placed within a line or an element like a list
+    /// item.
+    pub inline: bool,
+    /// The finished blocks.
+    blocks: Vec<Block>,
+    /// The paragraph that is currently being built.
+    par: Option<Par>,
+    /// The list that is currently being built.
+    list: Option<List>,
+    /// The inline elements that are currently open, as their opening and
+    /// closing markup and whether they are links. They are reopened in each
+    /// new paragraph.
+    tags: Vec<(EcoString, EcoString, bool)>,
+    /// The format of the markup.
+    markup: PhantomData<M>,
+}
+
+/// A finished block in a flow.
+pub enum Block {
+    /// The inner markup of a paragraph.
+    Par(String),
+    /// Any other block.
+    Other(String),
+}
+
+/// A paragraph that is being built.
+struct Par {
+    /// The inner markup of the paragraph.
+    markup: String,
+    /// Substitutes smart quotes.
+    quoter: Quoter,
+    /// Whether a space should be inserted before the next content.
+    space: bool,
+}
+
+/// A list that is being built.
+struct List {
+    /// The kind of the list.
+    kind: ListKind,
+    /// The markup of the items and their numbers.
+    items: Vec<(usize, String)>,
+    /// The number of the next item.
+    number: usize,
+}
+
+/// The kind of a list.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ListKind {
+    Bullet,
+    Numbered,
+    Terms,
+}
+
+impl<M: Markup> Flow<M> {
+    /// Add text to the current paragraph.
+    pub fn text(&mut self, text: &str) {
+        let par = self.par();
+        M::escape(text, &mut par.markup);
+        if let Some(c) = text.chars().last() {
+            par.quoter.last(c);
+        }
+    }
+
+    /// Add a smart quote to the current paragraph.
+    pub fn quote(&mut self, quotes: &Quotes, double: bool) {
+        let par = self.par();
+        let quote = par.quoter.quote(quotes, double, None);
+        M::escape(quote, &mut par.markup);
+        if let Some(c) = quote.chars().last() {
+            par.quoter.last(c);
+        }
+    }
+
+    /// Add an inline element to the current paragraph.
+    pub fn inline(&mut self, markup: &str) {
+        let par = self.par();
+        par.markup.push_str(markup);
+        par.quoter.last(OBJ_REPLACE);
+    }
+
+    /// Add a space between the content before and after it, if both end up
+    /// in the same paragraph.
+    pub fn space(&mut self) {
+        if let Some(par) = &mut self.par {
+            par.space = true;
+        }
+    }
+
+    /// Open an inline element with the given opening and closing markup.
+    pub fn open(&mut self, open: EcoString, close: EcoString, link: bool) {
+        if let Some(par) = &mut self.par {
+            if std::mem::take(&mut par.space) {
+                par.markup.push(' ');
+                par.quoter.last(' ');
+            }
+            par.markup.push_str(&open);
+        }
+        self.tags.push((open, close, link));
+    }
+
+    /// Close the innermost open inline element.
+    pub fn close(&mut self) {
+        if let Some((_, close, _)) = self.tags.pop() {
+            if let Some(par) = &mut self.par {
+                par.markup.push_str(&close);
+            }
+        }
+    }
+
+    /// Whether a link is currently open.
+    pub fn in_link(&self) -> bool {
+        self.tags.iter().any(|&(_, _, link)| link)
+    }
+
+    /// Add an item to the current list, starting a new list if the item is
+    /// of a different kind. Items without a number continue the numbering of
+    /// the previous item.
+    pub fn item(&mut self, kind: ListKind, number: Option<usize>, markup: String) {
+        self.finish_par();
+        if self.list.as_ref().map_or(false, |list| list.kind != kind) {
+            self.finish_list();
+        }
+
+        let list =
+            self.list
+                .get_or_insert_with(|| List { kind, items: vec![], number: 1 });
+        let number = number.unwrap_or(list.number);
+        list.number = number + 1;
+        list.items.push((number, markup));
+    }
+
+    /// Add a block.
+    pub fn block(&mut self, markup: String) {
+        self.finish_par();
+        self.finish_list();
+        self.blocks.push(Block::Other(markup));
+    }
+
+    /// Add multiple blocks.
+    pub fn blocks(&mut self, blocks: Vec<Block>) {
+        self.finish_par();
+        self.finish_list();
+        self.blocks.extend(blocks);
+    }
+
+    /// Finish the current paragraph. Lists are only finished by other content
+    /// as paragraph breaks may appear between their items.
+    pub fn parbreak(&mut self) {
+        if self.list.is_none() {
+            self.finish_par();
+        }
+    }
+
+    /// Finish all open paragraphs and lists and return the blocks.
+    pub fn finish(mut self) -> Vec<Block> {
+        self.finish_par();
+        self.finish_list();
+        self.blocks
+    }
+
+    /// The current paragraph, started if necessary.
+    fn par(&mut self) -> &mut Par {
+        self.finish_list();
+        let tags = &self.tags;
+        let par = self.par.get_or_insert_with(|| Par {
+            markup: tags.iter().map(|(open, _, _)| open.as_str()).collect(),
+            quoter: Quoter::new(),
+            space: false,
+        });
+
+        if std::mem::take(&mut par.space) {
+            par.markup.push(' ');
+            par.quoter.last(' ');
+        }
+
+        par
+    }
+
+    fn finish_par(&mut self) {
+        if let Some(mut par) = self.par.take() {
+            for (_, close, _) in self.tags.iter().rev() {
+                par.markup.push_str(close);
+            }
+            self.blocks.push(Block::Par(par.markup));
+        }
+    }
+
+    fn finish_list(&mut self) {
+        if let Some(list) = self.list.take() {
+            self.blocks.push(Block::Other(M::list(list.kind, list.items)));
+        }
+    }
+}
+
+impl<M> Default for Flow<M> {
+    fn default() -> Self {
+        Self {
+            inline: false,
+            blocks: vec![],
+            par: None,
+            list: None,
+            tags: vec![],
+            markup: PhantomData,
+        }
+    }
+}
+
+impl Block {
+    /// The block's markup, which is the inner markup for paragraphs.
+    pub fn into_inner(self) -> String {
+        match self {
+            Self::Par(markup) | Self::Other(markup) => markup,
+        }
+    }
+}
@@ -247,7 +247,11 @@ impl Show for RawElem {
                 vec![],
                 &highlighter,
                 &mut |node, style| {
-                    seq.push(styled(&text[node.range()], foreground.into(), style));
+                    seq.push(styled(
+                        &text[node.range()],
+                        foreground.clone().into(),
+                        style,
+                    ));
                 },
             );
 
@@ -272,7 +276,7 @@ impl Show for RawElem {
                 for (style, piece) in
                     highlighter.highlight_line(line, syntax_set).into_iter().flatten()
                 {
-                    seq.push(styled(piece, foreground.into(), style));
+                    seq.push(styled(piece, foreground.clone().into(), style));
                 }
             }
 
@@ -0,0 +1,91 @@
+use crate::prelude::*;
+
+/// Create a pattern that repeats a tile of content.
+///
+/// Patterns can be used wherever a color is accepted, e.g. as the fill of a
+/// shape or table or as the paint of a stroke. Like
+/// [gradients]($type/gradient), they are relative to the bounding box of the
+/// element they are applied to: The first tile starts in its top-left corner.
+///
+/// ## Example { #example }
+/// ```example
+/// #let hatch = pattern(size: (6pt, 6pt))[
+///   #line(start: (0%, 100%), end: (100%, 0%), stroke: 0.5pt)
+/// ]
+///
+/// #rect(width: 100%, height: 40pt, fill: hatch)
+/// #rect(
+///   width: 100%,
+///   height: 40pt,
+///   fill: pattern(spacing: (4pt, 4pt), circle(radius: 2pt, fill: teal)),
+/// )
+/// ```
+///
+/// Like with [`measure`]($func/measure), the styles that are active where the
+/// pattern is used are not known when it is created. To lay out the tile with
+/// the styles that are active around it, retrieve them with the
+/// [`style`]($func/style) function and pass them to the pattern. Without
+/// them, only set rules within the tile's body apply.
+///
+/// ```example
+/// #set text(fill: eastern)
+/// #style(styles => rect(
+///   width: 100%,
+///   height: 30pt,
+///   fill: pattern(styles: styles, size: (12pt, 12pt))[*+*],
+/// ))
+/// ```
+///
+/// Display: Pattern
+/// Category: visualize
+#[func]
+pub fn pattern(
+    /// The content of each tile.
+    body: Content,
+    /// The size of each tile. If `{auto}`, the tile is as large as its laid
+    /// out content.
+    #[named]
+    #[default]
+    size: Smart<Axes<Length>>,
+    /// The gap between neighbouring tiles.
+    #[named]
+    #[default(Axes::splat(Length::zero()))]
+    spacing: Axes<Length>,
+    /// The styles with which to lay out the tile.
+    #[named]
+    #[default]
+    styles: Option<Styles>,
+    /// The virtual machine.
+    vm: &mut Vm,
+    /// The callsite span.
+    span: Span,
+) -> SourceResult<Pattern> {
+    let world = vm.world();
+    let styles = StyleChain::new(styles.as_ref().unwrap_or(&world.library().styles));
+    let size = size.map(|size| size.resolve(styles));
+    let spacing = spacing.resolve(styles);
+
+    let region = size.unwrap_or(Axes::splat(Abs::inf()));
+    let expand = Axes::splat(size.is_custom());
+    let pod = Regions::one(region, expand);
+    let mut frame = body.measure(&mut vm.vt, styles, pod)?.into_frame();
+    let size = size.unwrap_or(frame.size());
+
+    if !size.x.is_finite()
+        || !size.y.is_finite()
+        || size.x <= Abs::zero()
+        || size.y <= Abs::zero()
+    {
+        bail!(span, "pattern tile must have a positive size");
+    }
+
+    if spacing.x < Abs::zero() || spacing.y < Abs::zero() {
+        bail!(span, "pattern spacing must not be negative");
+    }
+
+    // Content that overflows the tile is cut off.
+    frame.set_size(size);
+    frame.clip();
+
+    Ok(Pattern::new(frame, size, spacing))
+}
@@ -176,6 +176,11 @@ fn create(element: &Elem) -> TokenStream {
         .iter()
         .any(|capability| capability == "Locatable")
         .then(|| quote! { impl ::typst::model::Locatable for #ident {} });
+    let tagged_impl = element
+        .capable
+        .iter()
+        .any(|capability| capability == "Tagged")
+        .then(|| quote! { impl ::typst::model::Tagged for #ident {} });
 
     quote! {
         #[doc = #docs]
@@ -206,6 +211,7 @@ fn create(element: &Elem) -> TokenStream {
         #construct_impl
         #set_impl
         #locatable_impl
+        #tagged_impl
 
         impl ::typst::eval::IntoValue for #ident {
             fn into_value(self) -> ::typst::eval::Value {
@@ -0,0 +1,745 @@
+use ecow::EcoString;
+
+use super::{SyntaxKind, SyntaxNode};
+
+/// Format a syntax tree, returning the formatted source code.
+///
+/// Code is pretty-printed: Spacing is normalized, code blocks get one
+/// statement per line and calls, collections and parameter lists that don't
+/// fit into `width` columns are broken into one item per line. Markup and
+/// math are left alone, apart from the code that is embedded into them.
+/// Comments are kept.
+///
+/// Returns `None` if the tree contains syntax errors.
+pub fn format(root: &SyntaxNode, width: usize) -> Option<String> {
+    if root.erroneous() {
+        return None;
+    }
+
+    let mut printer = Printer { out: String::new(), pending: None, width };
+    match root.kind() {
+        SyntaxKind::Code => {
+            let (body, _) = statements(root.children());
+            printer.layout(&body, 0, width);
+            if source(root).ends_with('\n') {
+                printer.out.push('\n');
+            }
+        }
+        _ => printer.markup(root),
+    }
+
+    Some(printer.out)
+}
+
+/// A document in the pretty-printing algebra.
+#[derive(Clone)]
+enum Doc<'a> {
+    /// Text that is printed as is.
+    Text(EcoString),
+    /// A space, or a line break if the enclosing group is broken.
+    Line,
+    /// Nothing, or a line break if the enclosing group is broken.
+    Soft,
+    /// A line break that also breaks all enclosing groups.
+    Hard,
+    /// Text that depends on whether the enclosing group is flat or broken.
+    Either(&'static str, &'static str),
+    /// Increases the indentation of line breaks within.
+    Indent(Vec<Doc<'a>>),
+    /// Printed on one line if it fits and broken otherwise.
+    Group(Vec<Doc<'a>>),
+    /// A list whose last item, typically a block, is hugged by the list's
+    /// parentheses if the list doesn't fit on one line. Holds the hugging
+    /// variant and the contents of the ordinary group.
+    Hug(Vec<Doc<'a>>, Vec<Doc<'a>>),
+    /// Markup that is printed as is, except for embedded code.
+    Markup(&'a SyntaxNode),
+}
+
+impl Doc<'_> {
+    /// Create a text document from a string.
+    fn text(text: impl Into<EcoString>) -> Self {
+        Self::Text(text.into())
+    }
+
+    /// Whether the document contains a forced line break.
+    fn has_hard(&self) -> bool {
+        match self {
+            Self::Text(text) => text.contains('\n'),
+            Self::Line | Self::Soft | Self::Either(..) => false,
+            Self::Hard => true,
+            Self::Indent(docs) | Self::Group(docs) => docs.iter().any(Self::has_hard),
+            Self::Hug(_, docs) => docs.iter().any(Self::has_hard),
+            Self::Markup(node) => source(node).contains('\n'),
+        }
+    }
+}
+
+/// How the line breaks of a group are printed.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Mode {
+    Flat,
+    Break,
+}
+
+/// A document that is yet to be printed.
+type Command<'a, 'b> = (usize, Mode, &'b Doc<'a>);
+
+/// Prints documents and markup.
+struct Printer {
+    /// The formatted source code.
+    out: String,
+    /// Indentation that is yet to be written because nothing followed the
+    /// last line break so far.
+    pending: Option<usize>,
+    /// The maximum line width.
+    width: usize,
+}
+
+impl Printer {
+    /// Print markup as is, but format the code that is embedded into it.
+    fn markup(&mut self, node: &SyntaxNode) {
+        let mut embedded = false;
+        for child in node.children() {
+            match child.kind() {
+                SyntaxKind::Hashtag => {
+                    self.text("#");
+                    embedded = true;
+                }
+                kind if embedded && !kind.is_trivia() => {
+                    self.embedded(child);
+                    embedded = false;
+                }
+                SyntaxKind::Equation => self.text(&source(child)),
+                _ if !child.is_leaf() => self.markup(child),
+                _ => self.text(child.text()),
+            }
+        }
+    }
+
+    /// Print code that is embedded into markup.
+    fn embedded(&mut self, node: &SyntaxNode) {
+        // Breaking code that is embedded into a line of text, like a link,
+        // rarely makes it more readable. Such code is only broken up if it
+        // already spanned multiple lines.
+        let line = self.out.rsplit('\n').next().unwrap_or_default();
+        let inline = line.trim_start().len() > 1;
+        let multiline = source(node).contains('\n');
+        let width = if inline && !multiline { usize::MAX } else { self.width };
+        let indent = line.len() - line.trim_start().len();
+        self.layout(&[expr(node)], indent, width);
+    }
+
+    /// Print documents, breaking groups that don't fit into the width.
+    fn layout(&mut self, docs: &[Doc], indent: usize, width: usize) {
+        let mut stack: Vec<Command> =
+            docs.iter().rev().map(|doc| (indent, Mode::Break, doc)).collect();
+
+        while let Some((indent, mode, doc)) = stack.pop() {
+            match doc {
+                Doc::Text(text) => self.text(text),
+                Doc::Line if mode == Mode::Flat => self.text(" "),
+                Doc::Soft if mode == Mode::Flat => {}
+                Doc::Line | Doc::Soft | Doc::Hard => self.newline(indent),
+                Doc::Either(flat, _) if mode == Mode::Flat => self.text(flat),
+                Doc::Either(_, broken) => self.text(broken),
+                Doc::Indent(docs) => {
+                    stack.extend(docs.iter().rev().map(|doc| (indent + 2, mode, doc)));
+                }
+                Doc::Group(docs) => {
+                    let mode = self.choose(docs, indent, mode, &stack, width);
+                    stack.extend(docs.iter().rev().map(|doc| (indent, mode, doc)));
+                }
+                Doc::Hug(hugged, docs) => {
+                    let mode = self.choose(docs, indent, mode, &stack, width);
+                    let docs = if mode == Mode::Break
+                        && self.fits(
+                            hugged.iter().map(|doc| (indent, Mode::Flat, doc)),
+                            &stack,
+                            width,
+                        ) {
+                        hugged
+                    } else {
+                        docs
+                    };
+                    stack.extend(docs.iter().rev().map(|doc| (indent, mode, doc)));
+                }
+                Doc::Markup(node) => self.markup(node),
+            }
+        }
+    }
+
+    /// Decide whether a group is printed flat or broken.
+    fn choose(
+        &self,
+        docs: &[Doc],
+        indent: usize,
+        mode: Mode,
+        rest: &[Command],
+        width: usize,
+    ) -> Mode {
+        if mode == Mode::Flat
+            || (!docs.iter().any(Doc::has_hard)
+                && self.fits(
+                    docs.iter().map(|doc| (indent, Mode::Flat, doc)),
+                    rest,
+                    width,
+                ))
+        {
+            Mode::Flat
+        } else {
+            Mode::Break
+        }
+    }
+
+    /// Whether the documents and what follows them up to the next line break
+    /// fit into the rest of the current line.
+    fn fits<'a, 'b>(
+        &self,
+        docs: impl DoubleEndedIterator<Item = Command<'a, 'b>>,
+        rest: &[Command<'a, 'b>],
+        width: usize,
+    ) -> bool {
+        let mut remaining = width.saturating_sub(self.column());
+        let mut stack: Vec<Command> = docs.rev().collect();
+        let mut rest = rest.iter().rev().copied();
+        let mut in_rest = false;
+
+        loop {
+            let (indent, mode, doc) = match stack.pop() {
+                Some(command) => command,
+                None => match rest.next() {
+                    Some(command) => {
+                        in_rest = true;
+                        command
+                    }
+                    None => return true,
+                },
+            };
+
+            let markup;
+            let text = match doc {
+                Doc::Text(text) => text.as_str(),
+                Doc::Line if mode == Mode::Flat => " ",
+                Doc::Soft if mode == Mode::Flat => "",
+                Doc::Line | Doc::Soft | Doc::Hard => return true,
+                Doc::Either(flat, _) if mode == Mode::Flat => flat,
+                Doc::Either(_, broken) => broken,
+                Doc::Indent(docs) | Doc::Group(docs) | Doc::Hug(_, docs) => {
+                    stack.extend(docs.iter().rev().map(|doc| (indent, mode, doc)));
+                    continue;
+                }
+                // Markup that follows the documents can't be broken up by
+                // breaking them, so it doesn't count.
+                Doc::Markup(_) if in_rest => return true,
+                Doc::Markup(node) => {
+                    markup = source(node);
+                    markup.as_str()
+                }
+            };
+
+            let line = text.split('\n').next().unwrap_or_default();
+            match remaining.checked_sub(line.chars().count()) {
+                Some(_) if text.contains('\n') => return true,
+                Some(rest) => remaining = rest,
+                None => return false,
+            }
+        }
+    }
+
+    /// Write text, preceded by pending indentation.
+    fn text(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+
+        if let Some(indent) = self.pending.take() {
+            self.out.extend(std::iter::repeat(' ').take(indent));
+        }
+
+        self.out.push_str(text);
+    }
+
+    /// Write a line break. The indentation is only written once something
+    /// follows on the new line.
+    fn newline(&mut self, indent: usize) {
+        self.out.push('\n');
+        self.pending = Some(indent);
+    }
+
+    /// The column at which the next text is written.
+    fn column(&self) -> usize {
+        match self.pending {
+            Some(indent) => indent,
+            None => self.out.rsplit('\n').next().unwrap_or_default().chars().count(),
+        }
+    }
+}
+
+/// Build the document for a code expression.
+fn expr(node: &SyntaxNode) -> Doc<'_> {
+    match node.kind() {
+        SyntaxKind::CodeBlock => code_block(node),
+        SyntaxKind::ContentBlock => content_block(node),
+        SyntaxKind::Args => args(node),
+        SyntaxKind::Params if node.children().len() > 1 => list(node),
+        SyntaxKind::Array | SyntaxKind::Dict | SyntaxKind::Destructuring => list(node),
+        SyntaxKind::Parenthesized
+        | SyntaxKind::LetBinding
+        | SyntaxKind::SetRule
+        | SyntaxKind::ShowRule
+        | SyntaxKind::Conditional
+        | SyntaxKind::WhileLoop
+        | SyntaxKind::ForLoop
+        | SyntaxKind::ModuleImport
+        | SyntaxKind::ImportItems
+        | SyntaxKind::ModuleInclude
+        | SyntaxKind::FuncReturn
+        | SyntaxKind::Binary
+        | SyntaxKind::Unary
+        | SyntaxKind::FieldAccess
+        | SyntaxKind::FuncCall
+        | SyntaxKind::Named
+        | SyntaxKind::Keyed
+        | SyntaxKind::Spread
+        | SyntaxKind::Closure
+        | SyntaxKind::Params
+        | SyntaxKind::DestructAssignment
+            if !has_comments(node) =>
+        {
+            spaced(node)
+        }
+        _ => Doc::text(source(node)),
+    }
+}
+
+/// Build the document for an expression that is composed of keywords,
+/// operators and subexpressions, separating them by single spaces where
+/// needed.
+fn spaced(node: &SyntaxNode) -> Doc<'_> {
+    let parent = node.kind();
+    let mut docs = vec![];
+    let mut indented = None;
+    let mut prev: Option<&SyntaxNode> = None;
+    let mut newline = false;
+    for child in node.children() {
+        let next = child.kind();
+        if next.is_trivia() {
+            newline |= child.text().contains('\n');
+            continue;
+        }
+
+        if let Some(prev) = prev {
+            // Line breaks in method chains and before `else` are kept. The
+            // rest of a call whose method starts on a new line is indented.
+            if newline && next == SyntaxKind::Dot {
+                indented = Some(vec![Doc::Hard]);
+            } else if newline && next == SyntaxKind::Else {
+                docs.push(Doc::Hard);
+            } else if next == SyntaxKind::Args && breaks_before_dot(prev) {
+                indented = Some(vec![]);
+            } else if space_between(parent, prev.kind(), next) {
+                docs.push(Doc::text(" "));
+            }
+        }
+
+        indented.as_mut().unwrap_or(&mut docs).push(expr(child));
+        prev = Some(child);
+        newline = false;
+    }
+
+    docs.extend(indented.map(Doc::Indent));
+    Doc::Group(docs)
+}
+
+/// Whether a space separates two adjacent children of a node.
+fn space_between(parent: SyntaxKind, prev: SyntaxKind, next: SyntaxKind) -> bool {
+    match (prev, next) {
+        _ if matches!(parent, SyntaxKind::FieldAccess | SyntaxKind::FuncCall) => false,
+        (_, SyntaxKind::Args | SyntaxKind::Colon | SyntaxKind::Comma) => false,
+        (_, SyntaxKind::Params) if parent == SyntaxKind::Closure => false,
+        (SyntaxKind::LeftParen, _) | (_, SyntaxKind::RightParen) => false,
+        (SyntaxKind::Dots, _) => false,
+        (SyntaxKind::Plus | SyntaxKind::Minus, _) if parent == SyntaxKind::Unary => false,
+        _ => true,
+    }
+}
+
+/// Build the document for a code block.
+///
+/// Blocks that already spanned multiple lines keep one statement per line,
+/// other blocks are only broken up if they don't fit.
+fn code_block(node: &SyntaxNode) -> Doc<'_> {
+    let inner = node.children().flat_map(|child| match child.kind() {
+        SyntaxKind::Code => child.children().as_slice(),
+        SyntaxKind::LeftBrace | SyntaxKind::RightBrace => &[],
+        _ => std::slice::from_ref(child),
+    });
+
+    let (body, multiline) = statements(inner);
+    if body.is_empty() {
+        return Doc::text("{}");
+    }
+
+    let line = if multiline { Doc::Hard } else { Doc::Line };
+    Doc::Group(vec![
+        Doc::text("{"),
+        Doc::Indent([vec![line.clone()], body].concat()),
+        line,
+        Doc::text("}"),
+    ])
+}
+
+/// Build the documents for a sequence of statements. Statements that were
+/// on separate lines stay on separate lines and single blank lines between
+/// them are kept.
+///
+/// Also returns whether the statements must span multiple lines because
+/// they already did or contain a line comment.
+fn statements<'a>(
+    children: impl Iterator<Item = &'a SyntaxNode>,
+) -> (Vec<Doc<'a>>, bool) {
+    let mut docs = vec![];
+    let mut multiline = false;
+    let mut newlines = 0;
+    let mut prev = None;
+    for child in children {
+        match child.kind() {
+            SyntaxKind::Space => {
+                let count = child.text().chars().filter(|&c| c == '\n').count();
+                newlines += count;
+                multiline |= count > 0;
+            }
+            SyntaxKind::Semicolon => {}
+            kind => {
+                let comment =
+                    matches!(kind, SyntaxKind::LineComment | SyntaxKind::BlockComment);
+
+                if let Some(prev) = prev {
+                    if newlines > 0 {
+                        docs.push(Doc::Hard);
+                        if newlines > 1 {
+                            docs.push(Doc::Hard);
+                        }
+                    } else if comment || prev == SyntaxKind::BlockComment {
+                        docs.push(Doc::text(" "));
+                    } else {
+                        docs.push(Doc::Either(";", ""));
+                        docs.push(Doc::Line);
+                    }
+                }
+
+                multiline |= kind == SyntaxKind::LineComment;
+                docs.push(expr(child));
+                newlines = 0;
+                prev = Some(kind);
+            }
+        }
+    }
+    (docs, multiline)
+}
+
+/// Build the document for a content block.
+fn content_block(node: &SyntaxNode) -> Doc<'_> {
+    let mut docs = vec![];
+    for child in node.children() {
+        match child.kind() {
+            SyntaxKind::Markup => docs.push(Doc::Markup(child)),
+            _ => docs.push(Doc::text(child.text().clone())),
+        }
+    }
+    Doc::Group(docs)
+}
+
+/// Build the document for arguments, including trailing content blocks.
+fn args(node: &SyntaxNode) -> Doc<'_> {
+    let mut docs = vec![];
+    if node.children().next().map(SyntaxNode::kind) == Some(SyntaxKind::LeftParen) {
+        docs.push(list(node));
+    }
+
+    let trailing = node
+        .children()
+        .rev()
+        .take_while(|child| child.kind() == SyntaxKind::ContentBlock)
+        .collect::<Vec<_>>();
+
+    docs.extend(trailing.into_iter().rev().map(content_block));
+
+    Doc::Group(docs)
+}
+
+/// An item in a parenthesized list.
+struct Item<'a> {
+    /// Whether the item is preceded by a blank line.
+    blank: bool,
+    /// Comments before the item, each followed by a line break or a space.
+    leading: Vec<Doc<'a>>,
+    /// The item itself.
+    doc: Doc<'a>,
+    /// The item's node.
+    node: &'a SyntaxNode,
+    /// A comment on the same line after the item.
+    trailing: Option<&'a SyntaxNode>,
+}
+
+/// Build the document for a parenthesized list: Arguments, an array, a
+/// dictionary, parameters or a destructuring pattern.
+fn list(node: &SyntaxNode) -> Doc<'_> {
+    let kind = node.kind();
+    let mut items: Vec<Item> = vec![];
+    let mut leading: Vec<(&SyntaxNode, bool)> = vec![];
+    let mut newline = false;
+    let mut blank = false;
+
+    // Line comments and comments on their own lines must be followed by a
+    // line break, so they put each item on its own line.
+    let mut comments = false;
+
+    for child in node.children() {
+        match child.kind() {
+            SyntaxKind::Space if child.text().contains('\n') => {
+                blank |= child.text().chars().filter(|&c| c == '\n').count() > 1;
+                newline = true;
+                if let Some((_, own_line)) = leading.last_mut() {
+                    *own_line = true;
+                }
+            }
+            SyntaxKind::Space
+            | SyntaxKind::LeftParen
+            | SyntaxKind::Comma
+            | SyntaxKind::Colon => {}
+            SyntaxKind::RightParen => break,
+            kind @ (SyntaxKind::LineComment | SyntaxKind::BlockComment) => {
+                comments |= kind == SyntaxKind::LineComment;
+                match items.last_mut() {
+                    Some(item)
+                        if !newline && leading.is_empty() && item.trailing.is_none() =>
+                    {
+                        item.trailing = Some(child)
+                    }
+                    _ => leading.push((child, kind == SyntaxKind::LineComment)),
+                }
+                newline = false;
+            }
+            _ => {
+                let mut docs = vec![];
+                for (comment, own_line) in leading.drain(..) {
+                    comments |= own_line;
+                    docs.push(Doc::text(comment.text().clone()));
+                    docs.push(if own_line { Doc::Hard } else { Doc::text(" ") });
+                }
+                items.push(Item {
+                    blank,
+                    leading: docs,
+                    doc: expr(child),
+                    node: child,
+                    trailing: None,
+                });
+                newline = false;
+                blank = false;
+            }
+        }
+    }
+
+    comments |= !leading.is_empty();
+    if items.is_empty() && !comments {
+        return Doc::text(if kind == SyntaxKind::Dict { "(:)" } else { "()" });
+    }
+
+    let (line, soft) =
+        if comments { (Doc::Hard, Doc::Hard) } else { (Doc::Line, Doc::Soft) };
+
+    // The parentheses around a single argument that is itself a list stick
+    // to the argument's parentheses.
+    if kind == SyntaxKind::Args
+        && !comments
+        && matches!(items.as_slice(), [item] if item.leading.is_empty()
+            && item.trailing.is_none()
+            && nested(item.node))
+    {
+        let doc = items.pop().unwrap().doc;
+        return Doc::Group(vec![Doc::text("("), doc, Doc::text(")")]);
+    }
+
+    // A single item needs a comma to not be parsed as a parenthesized
+    // expression.
+    let single = matches!(kind, SyntaxKind::Array | SyntaxKind::Destructuring)
+        && matches!(items.as_slice(), [item] if item.node.kind() != SyntaxKind::Spread);
+
+    // A dictionary without named or keyed pairs needs a colon to not be
+    // parsed as an array.
+    let open = if kind == SyntaxKind::Dict
+        && !items
+            .iter()
+            .any(|item| matches!(item.node.kind(), SyntaxKind::Named | SyntaxKind::Keyed))
+    {
+        "(:"
+    } else {
+        "("
+    };
+
+    let hug = !comments
+        && items
+            .iter()
+            .all(|item| item.leading.is_empty() && item.trailing.is_none())
+        && items.last().map_or(false, |item| huggable(item.node))
+        && items[..items.len() - 1].iter().all(|item| !item.doc.has_hard());
+
+    let len = items.len();
+    let mut body = vec![soft.clone()];
+    let mut hugged = vec![Doc::text(open)];
+    for (i, item) in items.into_iter().enumerate() {
+        if i > 0 {
+            body.push(line.clone());
+            if item.blank {
+                body.push(soft.clone());
+            }
+            hugged.push(Doc::text(", "));
+        }
+
+        body.extend(item.leading);
+
+        if hug {
+            hugged.push(item.doc.clone());
+        }
+
+        body.push(item.doc);
+        if i + 1 < len || single {
+            body.push(Doc::text(","));
+        } else {
+            body.push(Doc::Either("", ","));
+        }
+
+        if let Some(comment) = item.trailing {
+            body.push(Doc::text(" "));
+            body.push(Doc::text(comment.text().clone()));
+        }
+    }
+
+    for (i, (comment, _)) in leading.into_iter().enumerate() {
+        if len > 0 || i > 0 {
+            body.push(Doc::Hard);
+        }
+        body.push(Doc::text(comment.text().clone()));
+    }
+
+    let docs = vec![Doc::text(open), Doc::Indent(body), soft, Doc::text(")")];
+    if hug {
+        hugged.push(Doc::text(")"));
+        Doc::Hug(hugged, docs)
+    } else {
+        Doc::Group(docs)
+    }
+}
+
+/// Whether a list item ends in a block and can thus be hugged by the list's
+/// parentheses: A block itself or a closure, call or statement whose last
+/// part is such an item.
+fn huggable(node: &SyntaxNode) -> bool {
+    match node.kind() {
+        SyntaxKind::CodeBlock | SyntaxKind::ContentBlock => true,
+        SyntaxKind::Closure
+        | SyntaxKind::FuncCall
+        | SyntaxKind::Args
+        | SyntaxKind::Conditional
+        | SyntaxKind::WhileLoop
+        | SyntaxKind::ForLoop => node
+            .children()
+            .rev()
+            .find(|child| {
+                !child.kind().is_trivia()
+                    && !matches!(child.kind(), SyntaxKind::RightParen | SyntaxKind::Comma)
+            })
+            .map_or(false, huggable),
+        _ => false,
+    }
+}
+
+/// Whether an argument is a call without trailing content blocks, an array
+/// or a dictionary.
+fn nested(node: &SyntaxNode) -> bool {
+    match node.kind() {
+        SyntaxKind::FuncCall => node
+            .children()
+            .last()
+            .and_then(|args| args.children().last())
+            .map_or(false, |last| last.kind() == SyntaxKind::RightParen),
+        SyntaxKind::Array | SyntaxKind::Dict => true,
+        _ => false,
+    }
+}
+
+/// Whether a field access starts on a new line, as in a multi-line method
+/// chain.
+fn breaks_before_dot(node: &SyntaxNode) -> bool {
+    node.kind() == SyntaxKind::FieldAccess
+        && node
+            .children()
+            .any(|child| child.kind() == SyntaxKind::Space && child.text().contains('\n'))
+}
+
+/// Whether a node has comments among its direct children.
+fn has_comments(node: &SyntaxNode) -> bool {
+    node.children().any(|child| {
+        matches!(child.kind(), SyntaxKind::LineComment | SyntaxKind::BlockComment)
+    })
+}
+
+/// The source code of a node.
+fn source(node: &SyntaxNode) -> EcoString {
+    node.clone().into_text()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    #[track_caller]
+    fn test(text: &str, width: usize, expected: &str) {
+        let found = format(&parse(text), width).expect("should be valid");
+        assert_eq!(found, expected);
+        assert_eq!(format(&parse(&found), width).as_deref(), Some(expected));
+    }
+
+    #[test]
+    fn test_format_spacing() {
+        test("#f(a,b)", 80, "#f(a, b)");
+        test("#let f(x,y)=x+y", 80, "#let f(x, y) = x + y");
+        test("#set text(  size:12pt )", 80, "#set text(size: 12pt)");
+        test("#f(x=>x+1)", 80, "#f(x => x + 1)");
+        test("#f[a][b]", 80, "#f[a][b]");
+    }
+
+    #[test]
+    fn test_format_breaking() {
+        test("#set text(  size:12pt )", 20, "#set text(\n  size: 12pt,\n)");
+        test("#let x=(aaaa, bbbb, cccc)", 20, "#let x = (\n  aaaa,\n  bbbb,\n  cccc,\n)");
+        test("#let x = (1,)", 80, "#let x = (1,)");
+        test("#let (a,)=(1,)", 80, "#let (a,) = (1,)");
+    }
+
+    #[test]
+    fn test_format_code_blocks() {
+        test("#{let a=1;let b=2}", 80, "#{ let a = 1; let b = 2 }");
+        test("#{let a=1;let b=2}", 20, "#{\n  let a = 1\n  let b = 2\n}");
+        test("#{\nlet a=1\nlet b=2\n}", 80, "#{\n  let a = 1\n  let b = 2\n}");
+    }
+
+    #[test]
+    fn test_format_keeps_markup_and_comments() {
+        test("Some  *markup*   #f( 1 ) and", 80, "Some  *markup*   #f(1) and");
+        test("#(a: 1, // one\nb: 2)", 80, "#(\n  a: 1, // one\n  b: 2,\n)");
+        test(
+            "#{\n  let x = 1 // one\n  /* two */ x\n}",
+            80,
+            "#{\n  let x = 1 // one\n  /* two */ x\n}",
+        );
+    }
+
+    #[test]
+    fn test_format_erroneous() {
+        assert_eq!(format(&parse("#f(1"), 80), None);
+    }
+}
@@ -3,6 +3,7 @@
 pub mod ast;
 
 mod file;
+mod format;
 mod kind;
 mod lexer;
 mod node;
@@ -12,6 +13,7 @@ mod source;
 mod span;
 
 pub use self::file::{FileId, PackageSpec, PackageVersion};
+pub use self::format::format;
 pub use self::kind::SyntaxKind;
 pub use self::lexer::{is_id_continue, is_id_start, is_ident, is_newline};
 pub use self::node::{LinkedChildren, LinkedNode, SyntaxError, SyntaxNode};
@@ -8,15 +8,17 @@ use std::sync::Arc;
 
 use ecow::EcoString;
 
-use crate::eval::{cast, dict, Dict, Value};
+use crate::eval::{cast, dict, Datetime, Dict, Value};
 use crate::font::Font;
 use crate::geom::{
-    self, rounded_rect, Abs, Align, Axes, Color, Corners, Dir, Em, Geometry, Length,
-    Numeric, Paint, Point, Rel, RgbaColor, Shape, Sides, Size, Stroke, Transform,
+    self, ellipse, rounded_rect, Abs, Align, Axes, Color, Corners, Dir, Em, Geometry,
+    Length, LineCap, LineJoin, Numeric, Paint, Path, Point, Rel, RgbaColor, Shape, Sides,
+    Size, Stroke, Transform,
 };
 use crate::image::Image;
 use crate::model::{Content, Location, MetaElem, StyleChain};
 use crate::syntax::Span;
+use crate::util::Bytes;
 
 /// A finished document with metadata and page frames.
 #[derive(Debug, Default, Clone, Hash)]
@@ -27,6 +29,14 @@ pub struct Document {
     pub title: Option<EcoString>,
     /// The document's author.
     pub author: Vec<EcoString>,
+    /// A short description of the document.
+    pub description: Option<EcoString>,
+    /// The document's keywords.
+    pub keywords: Vec<EcoString>,
+    /// The document's creation date.
+    pub date: Option<Datetime>,
+    /// Additional metadata entries as pairs of keys and values.
+    pub custom: Vec<(EcoString, EcoString)>,
 }
 
 /// A finished layout with items at fixed positions.
@@ -176,8 +186,15 @@ impl Frame {
     }
 
     /// Whether the given frame should be inlined.
+    ///
+    /// Frames that carry elements or links stay groups because their metadata
+    /// applies to all of their content and would otherwise leak into the
+    /// outer frame.
     fn should_inline(&self, frame: &Frame) -> bool {
-        self.items.is_empty() || frame.items.len() <= 5
+        (self.items.is_empty() || frame.items.len() <= 5)
+            && !frame.items().any(|(_, item)| {
+                matches!(item, FrameItem::Meta(Meta::Elem(_) | Meta::Link(_), _))
+            })
     }
 
     /// Inline a frame at the given layer.
@@ -482,6 +499,17 @@ impl TextItem {
     pub fn width(&self) -> Abs {
         self.glyphs.iter().map(|g| g.x_advance).sum::<Em>().at(self.size)
     }
+
+    /// The bounding box of the text run as its top-left corner and its size,
+    /// relative to the start of the baseline.
+    ///
+    /// The box spans the font's ascender and descender.
+    pub fn bbox(&self) -> (Point, Size) {
+        let metrics = self.font.metrics();
+        let top = metrics.ascender.at(self.size);
+        let bottom = metrics.descender.at(self.size);
+        (Point::with_y(-top), Size::new(self.width(), top - bottom))
+    }
 }
 
 impl Debug for TextItem {
@@ -668,6 +696,15 @@ pub enum Meta {
     Elem(Content),
     /// The numbering of the current page.
     PageNumbering(Value),
+    /// The logical number of the current page, as counted by the page counter.
+    PageNumber(usize),
+    /// How the current page is prepared for printing.
+    PrintBoxes(PrintBoxes),
+    /// A file that should be embedded into the exported document.
+    File(EmbeddedFile),
+    /// An interactive form field that covers the area this metadata is
+    /// attached to.
+    Field(FormField),
     /// Indicates that content should be hidden. This variant doesn't appear
     /// in the final frames as it is removed alongside the content that should
     /// be hidden.
@@ -684,11 +721,127 @@ impl Debug for Meta {
             Self::Link(dest) => write!(f, "Link({dest:?})"),
             Self::Elem(content) => write!(f, "Elem({:?})", content.func()),
             Self::PageNumbering(value) => write!(f, "PageNumbering({value:?})"),
+            Self::PageNumber(number) => write!(f, "PageNumber({number})"),
+            Self::PrintBoxes(boxes) => write!(f, "PrintBoxes({boxes:?})"),
+            Self::File(file) => write!(f, "File({:?})", file.name),
+            Self::Field(field) => write!(f, "Field({:?})", field.name),
             Self::Hide => f.pad("Hide"),
         }
     }
 }
 
+/// How a page is prepared for printing.
+///
+/// The page's frame has the size of the finished page. The exporter enlarges
+/// it by the bleed and the room for the printer's marks.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct PrintBoxes {
+    /// How far the page's content extends beyond its edges.
+    pub bleed: Abs,
+    /// Whether to draw crop and registration marks outside of the bleed.
+    pub marks: bool,
+}
+
+/// A file that is embedded into a document.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct EmbeddedFile {
+    /// The name of the file.
+    pub name: EcoString,
+    /// The file's contents.
+    pub data: Bytes,
+    /// A description of the file.
+    pub description: Option<EcoString>,
+    /// The file's MIME type.
+    pub mime_type: Option<EcoString>,
+    /// Whether the file should also be attached to its position on the page
+    /// with an annotation.
+    pub annotate: bool,
+    /// The span of the element that embeds the file.
+    pub span: Span,
+}
+
+/// An interactive form field.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct FormField {
+    /// The name that identifies the field's value.
+    pub name: EcoString,
+    /// The kind of the field along with its default value.
+    pub kind: FormFieldKind,
+    /// The field's background.
+    pub fill: Option<Paint>,
+    /// The field's border.
+    pub stroke: Option<Stroke>,
+    /// The size of the field's text.
+    pub text_size: Abs,
+    /// The font of the field's text, for fields that have text.
+    pub font: Option<Font>,
+    /// The span of the element that creates the field.
+    pub span: Span,
+}
+
+/// The kind of a form field.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum FormFieldKind {
+    /// A text input.
+    Text { value: EcoString, multiline: bool },
+    /// A checkbox.
+    Checkbox { checked: bool },
+    /// A button in a group of radio buttons, which share the field's name.
+    /// The value is the one the group takes when the button is selected.
+    Radio { value: EcoString, checked: bool },
+    /// A dropdown to select one of multiple options.
+    Dropdown { options: Vec<EcoString>, selected: Option<EcoString> },
+}
+
+impl FormField {
+    /// Draw the field's box with the given size, with the mark of a checked
+    /// checkbox or radio button if `checked` is true.
+    ///
+    /// The field's text is not part of the frame.
+    pub fn appearance(&self, size: Size, checked: bool) -> Frame {
+        let mut frame = Frame::new(size);
+        let fill = self.fill.clone();
+        let stroke = self.stroke.clone();
+        let color = stroke.as_ref().map_or(Color::BLACK.into(), |s| s.paint.clone());
+
+        if let FormFieldKind::Radio { .. } = self.kind {
+            let shape = ellipse(size, fill, stroke);
+            frame.push(Point::zero(), FrameItem::Shape(shape, self.span));
+            if checked {
+                let dot = size / 2.0;
+                let pos = (size - dot).to_point() / 2.0;
+                let shape = ellipse(dot, Some(color), None);
+                frame.push(pos, FrameItem::Shape(shape, self.span));
+            }
+            return frame;
+        }
+
+        if fill.is_some() || stroke.is_some() {
+            let shape = Shape { geometry: Geometry::Rect(size), fill, stroke };
+            frame.push(Point::zero(), FrameItem::Shape(shape, self.span));
+        }
+
+        if checked {
+            let point = |x: f64, y: f64| Point::new(size.x * x, size.y * y);
+            let mut path = Path::new();
+            path.move_to(point(0.2, 0.55));
+            path.line_to(point(0.4, 0.75));
+            path.line_to(point(0.8, 0.25));
+            let thickness = size.x.min(size.y) * 0.12;
+            let shape = Geometry::Path(path).stroked(Stroke {
+                paint: color,
+                thickness,
+                line_cap: LineCap::Round,
+                line_join: LineJoin::Round,
+                ..Stroke::default()
+            });
+            frame.push(Point::zero(), FrameItem::Shape(shape, self.span));
+        }
+
+        frame
+    }
+}
+
 /// A link destination.
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub enum Destination {
@@ -698,6 +851,8 @@ pub enum Destination {
     Position(Position),
     /// An unresolved link to a location in the document.
     Location(Location),
+    /// A link to a named destination in another PDF file.
+    Remote(RemoteDestination),
 }
 
 cast! {
@@ -706,9 +861,14 @@ cast! {
         Self::Url(v) => v.into_value(),
         Self::Position(v) => v.into_value(),
         Self::Location(v) => v.into_value(),
+        Self::Remote(v) => v.into_value(),
     },
     v: EcoString => Self::Url(v),
-    v: Position => Self::Position(v),
+    dict: Dict => if dict.contains("file") {
+        Self::Remote(Value::Dict(dict).cast()?)
+    } else {
+        Self::Position(Value::Dict(dict).cast()?)
+    },
     v: Location => Self::Location(v),
 }
 
@@ -743,6 +903,35 @@ impl From<Position> for Dict {
     }
 }
 
+/// A named destination in another PDF file.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct RemoteDestination {
+    /// The path to the file, relative to the linking file.
+    pub file: EcoString,
+    /// The name of the destination within the file.
+    pub name: EcoString,
+}
+
+cast! {
+    RemoteDestination,
+    self => Value::Dict(self.into()),
+    mut dict: Dict => {
+        let file = dict.take("file")?.cast()?;
+        let name = dict.take("name")?.cast()?;
+        dict.finish(&["file", "name"])?;
+        Self { file, name }
+    },
+}
+
+impl From<RemoteDestination> for Dict {
+    fn from(dest: RemoteDestination) -> Self {
+        dict! {
+            "file" => dest.file,
+            "name" => dest.name,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -760,4 +949,17 @@ mod tests {
         fn ensure_send<T: Send>() {}
         ensure_send::<Document>();
     }
+
+    #[test]
+    fn test_frame_keeps_links_in_groups() {
+        let size = Size::splat(Abs::pt(10.0));
+        let mut link = Frame::new(size);
+        link.meta_iter([Meta::Link(Destination::Url("https://typst.app".into()))]);
+
+        let mut frame = Frame::new(size);
+        frame.push_frame(Point::zero(), Frame::new(size));
+        frame.push_frame(Point::zero(), link);
+        assert_eq!(frame.items().count(), 1);
+        assert!(matches!(frame.items().next(), Some((_, FrameItem::Group(_)))));
+    }
 }
@@ -33,10 +33,26 @@ pub struct LangItems {
     /// The root layout function.
     pub layout:
         fn(vt: &mut Vt, content: &Content, styles: StyleChain) -> SourceResult<Document>,
+    /// The HTML export function.
+    pub html: fn(
+        vt: &mut Vt,
+        content: &Content,
+        styles: StyleChain,
+        document: &Document,
+    ) -> SourceResult<String>,
+    /// The Markdown export function.
+    pub markdown: fn(
+        vt: &mut Vt,
+        content: &Content,
+        styles: StyleChain,
+        document: &Document,
+    ) -> SourceResult<String>,
     /// Access the em size.
     pub em: fn(StyleChain) -> Abs,
     /// Access the text direction.
     pub dir: fn(StyleChain) -> Dir,
+    /// Whether the document is tagged.
+    pub tagged: fn(StyleChain) -> bool,
     /// Whitespace.
     pub space: fn() -> Content,
     /// A forced line break: `\`.
@@ -83,6 +99,8 @@ pub struct LangItems {
     pub cmyk_func: &'static NativeFunc,
     /// The constructor for the 'luma' color kind.
     pub luma_func: &'static NativeFunc,
+    /// The constructor for the 'spot' color kind.
+    pub spot_func: &'static NativeFunc,
     /// A mathematical equation: `$x$`, `$ x^2 $`.
     pub equation: fn(body: Content, block: bool) -> Content,
     /// An alignment point in math: `&`.
@@ -129,8 +147,11 @@ impl Debug for LangItems {
 impl Hash for LangItems {
     fn hash<H: Hasher>(&self, state: &mut H) {
         (self.layout as usize).hash(state);
+        (self.html as usize).hash(state);
+        (self.markdown as usize).hash(state);
         (self.em as usize).hash(state);
         (self.dir as usize).hash(state);
+        (self.tagged as usize).hash(state);
         self.space.hash(state);
         self.linebreak.hash(state);
         self.text.hash(state);
@@ -153,6 +174,7 @@ impl Hash for LangItems {
         self.rgb_func.hash(state);
         self.cmyk_func.hash(state);
         self.luma_func.hash(state);
+        self.spot_func.hash(state);
         self.equation.hash(state);
         self.math_align_point.hash(state);
         self.math_delimited.hash(state);
@@ -2,10 +2,10 @@
 
 use ecow::{eco_format, EcoString};
 
-use super::{Args, IntoValue, Str, Value, Vm};
+use super::{array, Args, Array, IntoValue, Str, Value, Vm};
 use crate::diag::{At, Hint, SourceResult};
 use crate::eval::{bail, Datetime};
-use crate::geom::{Align, Axes, Color, Dir, Em, GenAlign};
+use crate::geom::{Align, Axes, Color, Dir, Em, GenAlign, Gradient, Ratio};
 use crate::model::{Location, Selector};
 use crate::syntax::Span;
 
@@ -29,6 +29,7 @@ pub fn call(
                 Color::Luma(_) => vm.items.luma_func.into_value(),
                 Color::Rgba(_) => vm.items.rgb_func.into_value(),
                 Color::Cmyk(_) => vm.items.cmyk_func.into_value(),
+                Color::Spot(_) => vm.items.spot_func.into_value(),
             },
             "hex" => color.to_rgba().to_hex().into_value(),
             "rgba" => color.to_rgba().to_array().into_value(),
@@ -38,6 +39,13 @@ pub fn call(
                     bail!(span, "cannot obtain cmyk values from rgba color")
                 }
                 Color::Cmyk(cmyk) => cmyk.to_array().into_value(),
+                Color::Spot(spot) => match spot.to_cmyk() {
+                    Some(cmyk) => cmyk.to_array().into_value(),
+                    None => bail!(
+                        span,
+                        "cannot obtain cmyk values from spot color with rgb alternate"
+                    ),
+                },
             },
             "luma" => match color {
                 Color::Luma(luma) => luma.0.into_value(),
@@ -47,6 +55,9 @@ pub fn call(
                 Color::Cmyk(_) => {
                     bail!(span, "cannot obtain the luma value of cmyk color")
                 }
+                Color::Spot(_) => {
+                    bail!(span, "cannot obtain the luma value of spot color")
+                }
             },
             _ => return missing(),
         },
@@ -247,6 +258,22 @@ pub fn call(
                     "second" => datetime.second().into_value(),
                     _ => return missing(),
                 }
+            } else if let Some(gradient) = dynamic.downcast::<Gradient>() {
+                match method {
+                    "stops" => gradient
+                        .stops()
+                        .iter()
+                        .map(|(color, offset)| {
+                            array![color.clone(), *offset].into_value()
+                        })
+                        .collect::<Array>()
+                        .into_value(),
+                    "sample" => {
+                        let t = args.expect::<Ratio>("offset")?;
+                        gradient.sample(t.get()).into_value()
+                    }
+                    _ => return missing(),
+                }
             } else if let Some(direction) = dynamic.downcast::<Dir>() {
                 match method {
                     "axis" => direction.axis().description().into_value(),
@@ -374,6 +401,7 @@ pub fn methods_on(type_name: &str) -> &[(&'static str, bool)] {
             ("cmyk", false),
             ("luma", false),
         ],
+        "gradient" => &[("stops", false), ("sample", true)],
         "string" => &[
             ("len", false),
             ("at", true),
@@ -5,9 +5,12 @@ use std::fmt::Debug;
 
 use ecow::eco_format;
 
-use super::{format_str, Regex, Value};
+use super::{format_str, FromValue, Regex, Value};
 use crate::diag::{bail, StrResult};
-use crate::geom::{Axes, Axis, GenAlign, Length, Numeric, PartialStroke, Rel, Smart};
+use crate::geom::{
+    Axes, Axis, GenAlign, Gradient, Length, Numeric, Paint, PartialStroke, Pattern, Rel,
+    Smart,
+};
 use Value::*;
 
 /// Bail with a type mismatch error.
@@ -113,6 +116,16 @@ pub fn add(lhs: Value, rhs: Value) -> StrResult<Value> {
             })
         }
 
+        (Dyn(paint), Length(thickness)) | (Length(thickness), Dyn(paint))
+            if paint.is::<Gradient>() || paint.is::<Pattern>() =>
+        {
+            Value::dynamic(PartialStroke {
+                paint: Smart::Custom(Paint::from_value(Dyn(paint))?),
+                thickness: Smart::Custom(thickness),
+                ..PartialStroke::default()
+            })
+        }
+
         (Dyn(a), Dyn(b)) => {
             // 1D alignments can be summed into 2D alignments.
             if let (Some(&a), Some(&b)) =
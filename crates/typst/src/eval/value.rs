@@ -5,6 +5,7 @@ use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 
 use ecow::eco_format;
+use serde::{Serialize, Serializer};
 use siphasher::sip128::{Hasher128, SipHasher13};
 
 use super::{
@@ -208,6 +209,26 @@ impl Debug for Value {
     }
 }
 
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::None => serializer.serialize_none(),
+            Self::Bool(v) => serializer.serialize_bool(*v),
+            Self::Int(v) => serializer.serialize_i64(*v),
+            Self::Float(v) => serializer.serialize_f64(*v),
+            Self::Str(v) => serializer.serialize_str(v),
+            Self::Bytes(v) => serializer.serialize_bytes(v),
+            Self::Content(v) => v.serialize(serializer),
+            Self::Array(v) => serializer.collect_seq(v.iter()),
+            Self::Dict(v) => {
+                serializer.collect_map(v.iter().map(|(k, v)| (k.as_str(), v)))
+            }
+            // Everything else is serialized in its code representation.
+            _ => serializer.serialize_str(&self.repr()),
+        }
+    }
+}
+
 impl PartialEq for Value {
     fn eq(&self, other: &Self) -> bool {
         ops::equal(self, other)
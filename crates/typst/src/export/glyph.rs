@@ -0,0 +1,243 @@
+//! Color glyphs, which exporters draw instead of filling their outlines.
+
+use std::io::Read;
+use std::sync::Arc;
+
+use ttf_parser::{GlyphId, OutlineBuilder, Tag};
+use usvg::{NodeExt, TreeParsing};
+
+use crate::font::Font;
+use crate::geom::{self, Abs, Point, RgbaColor, Size};
+use crate::image::{Image, ImageFormat, VectorFormat};
+
+/// A layer of a COLR glyph.
+///
+/// The path is in font units with the origin at the glyph's origin and the
+/// y-axis pointing down.
+#[derive(Debug, Clone, Hash)]
+pub(super) struct ColrLayer {
+    /// The outline of the layer.
+    pub path: geom::Path,
+    /// The color of the layer, `None` if it uses the text's fill.
+    pub color: Option<RgbaColor>,
+}
+
+/// Where the colors of a color glyph come from.
+///
+/// Fonts may provide the same glyph in multiple color formats. All exporters
+/// prefer them in the same order: SVG, then COLR, then bitmaps.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub(super) enum ColorGlyph {
+    /// An SVG document from the font's `SVG` table.
+    Svg,
+    /// Colored layers from the font's `COLR` and `CPAL` tables.
+    Colr,
+    /// An image from the font's `sbix`, `CBDT` or `EBDT` tables.
+    Bitmap,
+}
+
+/// How a glyph is drawn with its own colors, if at all.
+#[comemo::memoize]
+pub(super) fn color_glyph(font: &Font, id: u16) -> Option<ColorGlyph> {
+    let ttf = font.ttf();
+    if ttf.glyph_svg_image(GlyphId(id)).is_some() {
+        Some(ColorGlyph::Svg)
+    } else if colr_layers(font, id).is_some() {
+        Some(ColorGlyph::Colr)
+    } else if ttf.glyph_raster_image(GlyphId(id), u16::MAX).is_some() {
+        Some(ColorGlyph::Bitmap)
+    } else {
+        None
+    }
+}
+
+/// Whether a glyph is drawn with its own colors.
+pub(super) fn is_color_glyph(font: &Font, id: u16) -> bool {
+    color_glyph(font, id).is_some()
+}
+
+/// Extract the layers of a glyph from the font's COLR and CPAL tables.
+///
+/// Only version 0 of the COLR table is supported and colors are taken from
+/// the first palette.
+#[comemo::memoize]
+pub(super) fn colr_layers(font: &Font, id: u16) -> Option<Arc<Vec<ColrLayer>>> {
+    let ttf = font.ttf();
+    let colr = ttf.raw_face().table(Tag::from_bytes(b"COLR"))?;
+    let cpal = ttf.raw_face().table(Tag::from_bytes(b"CPAL"))?;
+
+    // Find the base glyph record, which lists the glyph's layers.
+    let num_base_glyphs = read_u16(colr, 2)? as usize;
+    let base_glyphs = read_u32(colr, 4)? as usize;
+    let layers = read_u32(colr, 8)? as usize;
+    let (mut lo, mut hi) = (0, num_base_glyphs);
+    let record = loop {
+        if lo >= hi {
+            return None;
+        }
+        let mid = (lo + hi) / 2;
+        let record = base_glyphs + 6 * mid;
+        let glyph = read_u16(colr, record)?;
+        match glyph.cmp(&id) {
+            std::cmp::Ordering::Less => lo = mid + 1,
+            std::cmp::Ordering::Greater => hi = mid,
+            std::cmp::Ordering::Equal => break record,
+        }
+    };
+
+    let first_layer = read_u16(colr, record + 2)? as usize;
+    let num_layers = read_u16(colr, record + 4)? as usize;
+
+    // Colors are stored as BGRA in the palette.
+    let num_entries = read_u16(cpal, 2)?;
+    let colors = read_u32(cpal, 8)? as usize;
+    let first_color = read_u16(cpal, 12)? as usize;
+
+    let mut result = vec![];
+    for i in first_layer..first_layer + num_layers {
+        let layer = layers + 4 * i;
+        let glyph = read_u16(colr, layer)?;
+        let index = read_u16(colr, layer + 2)?;
+        let color = if index == 0xFFFF {
+            None
+        } else if index < num_entries {
+            let at = colors + 4 * (first_color + index as usize);
+            let &[b, g, r, a] = cpal.get(at..at + 4)? else { return None };
+            Some(RgbaColor::new(r, g, b, a))
+        } else {
+            return None;
+        };
+
+        let mut builder = PathBuilder::default();
+        ttf.outline_glyph(GlyphId(glyph), &mut builder);
+        result.push(ColrLayer { path: builder.path, color });
+    }
+
+    Some(Arc::new(result))
+}
+
+/// Extract the image of a glyph drawn from an SVG document or a bitmap.
+///
+/// Returns the image along with its position and size in font units, relative
+/// to the glyph's origin with the y-axis pointing down.
+#[comemo::memoize]
+pub(super) fn glyph_image(font: &Font, id: u16) -> Option<(Image, Point, Size)> {
+    match color_glyph(font, id)? {
+        ColorGlyph::Svg => svg_glyph_image(font, id),
+        ColorGlyph::Bitmap => bitmap_glyph_image(font, id),
+        ColorGlyph::Colr => None,
+    }
+}
+
+/// Extract a glyph from the font's SVG table.
+fn svg_glyph_image(font: &Font, id: u16) -> Option<(Image, Point, Size)> {
+    let mut data = font.ttf().glyph_svg_image(GlyphId(id))?;
+
+    // Decompress SVGZ.
+    let mut decoded = vec![];
+    if data.starts_with(&[0x1f, 0x8b]) {
+        let mut decoder = flate2::read::GzDecoder::new(data);
+        decoder.read_to_end(&mut decoded).ok()?;
+        data = &decoded;
+    }
+
+    // Compute the glyph's bounding box.
+    let xml = std::str::from_utf8(data).ok()?;
+    let document = roxmltree::Document::parse(xml).ok()?;
+    let opts = usvg::Options::default();
+    let tree = usvg::Tree::from_xmltree(&document, &opts).ok()?;
+    let mut bbox = usvg::Rect::new_bbox();
+    for node in tree.root.descendants() {
+        if let Some(rect) = node.calculate_bbox().and_then(|b| b.to_rect()) {
+            bbox = bbox.expand(rect);
+        }
+    }
+
+    // The glyph is drawn above the baseline, at negative y coordinates, so it
+    // lies outside of the document's own view box. We thus wrap the document
+    // in one whose view box contains the whole glyph.
+    let (left, top) = (bbox.left(), bbox.top());
+    let (width, height) = (bbox.width(), bbox.height());
+    let inner = &xml[xml.find("<svg")?..];
+    let wrapper = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" \
+         height=\"{height}\" viewBox=\"{left} {top} {width} {height}\">{inner}</svg>"
+    );
+
+    let format = ImageFormat::Vector(VectorFormat::Svg);
+    let image = Image::new(wrapper.into_bytes().into(), format, None).ok()?;
+    let pos = Point::new(Abs::pt(left), Abs::pt(top));
+    let size = Size::new(Abs::pt(width), Abs::pt(height));
+    Some((image, pos, size))
+}
+
+/// Extract a glyph from the font's bitmap strikes, using the largest one.
+fn bitmap_glyph_image(font: &Font, id: u16) -> Option<(Image, Point, Size)> {
+    let raster = font.ttf().glyph_raster_image(GlyphId(id), u16::MAX)?;
+    let image = Image::new(raster.data.into(), raster.format.into(), None).ok()?;
+
+    // Position the bitmap like the raster exporter does.
+    let upem = font.units_per_em();
+    let h = upem;
+    let w = image.width() as f64 / image.height() as f64 * h;
+    let dx = raster.x as f64 / image.width() as f64 * upem;
+    let dy = raster.y as f64 / image.height() as f64 * upem;
+    let pos = Point::new(Abs::pt(dx), Abs::pt(-upem - dy));
+    let size = Size::new(Abs::pt(w), Abs::pt(h));
+    Some((image, pos, size))
+}
+
+/// Read a big-endian `u16` at the given offset.
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(data.get(offset..offset + 2)?.try_into().ok()?))
+}
+
+/// Read a big-endian `u32` at the given offset.
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(offset..offset + 4)?.try_into().ok()?))
+}
+
+/// Builds a path from a glyph outline, flipping it vertically.
+#[derive(Default)]
+struct PathBuilder {
+    path: geom::Path,
+    last: Point,
+}
+
+impl PathBuilder {
+    fn point(x: f32, y: f32) -> Point {
+        Point::new(Abs::pt(x.into()), Abs::pt(-f64::from(y)))
+    }
+}
+
+impl OutlineBuilder for PathBuilder {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.last = Self::point(x, y);
+        self.path.move_to(self.last);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.last = Self::point(x, y);
+        self.path.line_to(self.last);
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        // Elevate the quadratic curve to a cubic one.
+        let control = Self::point(x1, y1);
+        let end = Self::point(x, y);
+        let c1 = self.last + (control - self.last) * (2.0 / 3.0);
+        let c2 = end + (control - end) * (2.0 / 3.0);
+        self.path.cubic_to(c1, c2, end);
+        self.last = end;
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        self.last = Self::point(x, y);
+        self.path
+            .cubic_to(Self::point(x1, y1), Self::point(x2, y2), self.last);
+    }
+
+    fn close(&mut self) {
+        self.path.close_path();
+    }
+}
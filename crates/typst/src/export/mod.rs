@@ -1,7 +1,12 @@
 //! Exporting into external formats.
 
+mod glyph;
 mod pdf;
 mod render;
+mod svg;
+mod text;
 
-pub use self::pdf::pdf;
+pub use self::pdf::{pdf, pdf_with_options, OutputProfile, PdfOptions, PdfStandard};
 pub use self::render::render;
+pub use self::svg::svg;
+pub use self::text::text;
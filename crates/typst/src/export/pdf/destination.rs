@@ -0,0 +1,96 @@
+use std::collections::{BTreeMap, HashSet};
+
+use ecow::{eco_format, EcoString};
+use pdf_writer::{Finish, Name, Null, Ref, Str};
+
+use super::PdfContext;
+use crate::doc::Position;
+use crate::geom::Abs;
+
+/// Write the name tree of the document's named destinations.
+///
+/// Every labelled element becomes a destination named after its label. If
+/// requested, headings without a label are also included, named after their
+/// text. This allows other documents to link to them, for instance, with a
+/// URL like `manual.pdf#installation`.
+#[tracing::instrument(skip_all)]
+pub fn write_destinations(ctx: &mut PdfContext) -> Option<Ref> {
+    let mut dests = BTreeMap::new();
+    let mut headings = vec![];
+    for elem in ctx.introspector.all() {
+        let Some(loc) = elem.location() else { continue };
+        if let Some(label) = elem.label() {
+            // If multiple elements have the same label, the first one wins.
+            dests.entry(label.0.clone()).or_insert(loc);
+        } else if ctx.options.heading_destinations && elem.func().name() == "heading" {
+            headings.push((slug(&elem.plain_text()), loc));
+        }
+    }
+
+    // Headings with the same text are distinguished by a number.
+    let mut used: HashSet<EcoString> = dests.keys().cloned().collect();
+    for (slug, loc) in headings {
+        let base = if slug.is_empty() { "heading".into() } else { slug };
+        let mut name = base.clone();
+        let mut i = 1;
+        while used.contains(&name) {
+            i += 1;
+            name = eco_format!("{base}-{i}");
+        }
+        used.insert(name.clone());
+        dests.insert(name, loc);
+    }
+
+    // Destinations on pages that aren't exported are left out.
+    let dests: Vec<_> = dests
+        .into_iter()
+        .map(|(name, loc)| (name, ctx.introspector.position(loc)))
+        .filter_map(|(name, pos)| Some((name, ctx.page_index(pos.page)?, pos)))
+        .collect();
+
+    if dests.is_empty() {
+        return None;
+    }
+
+    // The names are already sorted, as required for a name tree, because
+    // they come from an ordered map.
+    let tree_ref = ctx.alloc.bump();
+    let mut tree = ctx.writer.indirect(tree_ref).dict();
+    let mut names = tree.insert(Name(b"Names")).array();
+    for (name, index, Position { point, .. }) in dests {
+        let height = ctx.page_heights[index];
+        let y = (point.y - Abs::pt(10.0)).max(Abs::zero());
+        names.item(Str(name.as_bytes()));
+        names
+            .push()
+            .array()
+            .item(ctx.page_refs[index])
+            .item(Name(b"XYZ"))
+            .item(point.x.to_f32())
+            .item(height - y.to_f32())
+            .item(Null);
+    }
+
+    names.finish();
+    tree.finish();
+
+    Some(tree_ref)
+}
+
+/// Turn a heading's text into a destination name.
+///
+/// The name consists of the text's lowercase alphanumeric characters, with
+/// hyphens between the words.
+fn slug(text: &str) -> EcoString {
+    let mut slug = EcoString::new();
+    for word in text.split(|c: char| !c.is_alphanumeric()) {
+        if word.is_empty() {
+            continue;
+        }
+        if !slug.is_empty() {
+            slug.push('-');
+        }
+        slug.push_str(&word.to_lowercase());
+    }
+    slug
+}
@@ -0,0 +1,97 @@
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
+
+use ecow::eco_format;
+use pdf_writer::{Filter, Finish, Name, Ref, Str, TextStr};
+
+use super::{deflate, PdfContext, RefExt};
+use crate::doc::EmbeddedFile;
+
+/// A file that is embedded into the PDF.
+///
+/// Files are identified by their name and contents. A file that is embedded
+/// in multiple places is written once, with the description and MIME type it
+/// was first embedded with.
+#[derive(Debug, Clone)]
+pub struct PdfFile(pub EmbeddedFile);
+
+impl PartialEq for PdfFile {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.name == other.0.name && self.0.data == other.0.data
+    }
+}
+
+impl Eq for PdfFile {}
+
+impl Hash for PdfFile {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.name.hash(state);
+        self.0.data.hash(state);
+    }
+}
+
+/// Embed all files that were attached to the document.
+#[tracing::instrument(skip_all)]
+pub fn write_files(ctx: &mut PdfContext) {
+    for PdfFile(file) in ctx.file_map.items() {
+        let spec_ref = ctx.alloc.bump();
+        let stream_ref = ctx.alloc.bump();
+        ctx.file_refs.push(spec_ref);
+
+        let data = deflate(&file.data);
+        let mut stream = ctx.writer.stream(stream_ref, &data);
+        stream.filter(Filter::FlateDecode);
+        stream.pair(Name(b"Type"), Name(b"EmbeddedFile"));
+        if let Some(mime_type) = &file.mime_type {
+            stream.pair(Name(b"Subtype"), Name(mime_type.as_bytes()));
+        }
+        stream
+            .insert(Name(b"Params"))
+            .dict()
+            .pair(Name(b"Size"), file.data.len() as i32);
+        stream.finish();
+
+        let mut spec = ctx.writer.indirect(spec_ref).dict();
+        spec.pair(Name(b"Type"), Name(b"Filespec"));
+        spec.pair(Name(b"F"), Str(file.name.as_bytes()));
+        spec.pair(Name(b"UF"), TextStr(&file.name));
+        if let Some(description) = &file.description {
+            spec.pair(Name(b"Desc"), TextStr(description));
+        }
+        spec.insert(Name(b"EF")).dict().pair(Name(b"F"), stream_ref);
+        spec.finish();
+    }
+}
+
+/// Write the name tree that lists the embedded files in viewers' attachment
+/// panels.
+pub fn write_file_tree(ctx: &mut PdfContext) -> Option<Ref> {
+    if ctx.file_refs.is_empty() {
+        return None;
+    }
+
+    // The names in the tree must be unique and sorted.
+    let mut files = BTreeMap::new();
+    for (spec_ref, PdfFile(file)) in ctx.file_refs.iter().zip(ctx.file_map.items()) {
+        let mut name = file.name.clone();
+        let mut i = 1;
+        while files.contains_key(&name) {
+            i += 1;
+            name = eco_format!("{} ({i})", file.name);
+        }
+        files.insert(name, *spec_ref);
+    }
+
+    let tree_ref = ctx.alloc.bump();
+    let mut tree = ctx.writer.indirect(tree_ref).dict();
+    let mut names = tree.insert(Name(b"Names")).array();
+    for (name, spec_ref) in &files {
+        names.item(Str(name.as_bytes()));
+        names.item(*spec_ref);
+    }
+
+    names.finish();
+    tree.finish();
+
+    Some(tree_ref)
+}
@@ -0,0 +1,243 @@
+use ecow::{eco_format, EcoString};
+use pdf_writer::{Dict, Filter, Finish, Name, Rect, Ref, TextStr};
+
+use super::{AbsExt, PdfContext, RefExt};
+use crate::doc::{FormField, FormFieldKind};
+use crate::geom::{Color, Paint, Size};
+
+/// The state of a checked checkbox in its appearance dictionary.
+const ON: Name<'static> = Name(b"Yes");
+
+/// The state of an unchecked checkbox or radio button.
+const OFF: Name<'static> = Name(b"Off");
+
+/// Field flag for text fields that span multiple lines.
+const MULTILINE: i32 = 1 << 12;
+
+/// Field flag for radio buttons.
+const RADIO: i32 = 1 << 15;
+
+/// Field flag for radio groups that always have a selected button.
+const NO_TOGGLE_TO_OFF: i32 = 1 << 14;
+
+/// Field flag for choice fields that are shown as a dropdown.
+const COMBO: i32 = 1 << 17;
+
+/// The document's interactive form.
+#[derive(Default)]
+pub struct AcroForm {
+    /// The default appearance of the text of form fields, which is that of
+    /// the first field with text.
+    appearance: Option<EcoString>,
+    /// The top-level fields.
+    fields: Vec<Ref>,
+    /// The groups of radio buttons.
+    radios: Vec<RadioGroup>,
+}
+
+/// Radio buttons that share a name.
+struct RadioGroup {
+    /// The shared name.
+    name: EcoString,
+    /// The field that holds the group's value.
+    id: Ref,
+    /// The widget annotations of the buttons.
+    kids: Vec<Ref>,
+    /// The value of the selected button.
+    value: Option<EcoString>,
+}
+
+/// A form field as it is placed on a page.
+pub struct PdfField {
+    /// The field.
+    pub field: FormField,
+    /// The field's area in the PDF coordinate system.
+    pub rect: Rect,
+    /// The field's size.
+    pub size: Size,
+    /// The deflated content stream of the field's appearance. For checkboxes
+    /// and radio buttons, this is their unchecked appearance. Viewers replace
+    /// it once the field's value changes.
+    pub normal: Vec<u8>,
+    /// The deflated content stream of a checkbox's or radio button's checked
+    /// appearance.
+    pub checked: Option<Vec<u8>>,
+    /// The resource name of the font for the text of a text field or
+    /// dropdown.
+    pub font: Option<EcoString>,
+}
+
+/// Write the widget annotation of a form field on a page.
+pub fn write_widget(ctx: &mut PdfContext, id: Ref, page_ref: Ref, field: PdfField) {
+    let PdfField { field, rect, size, normal, checked, font } = field;
+    let normal_ref = write_appearance(ctx, &normal, size);
+    let checked_ref = checked.map(|data| write_appearance(ctx, &data, size));
+
+    let parent = match &field.kind {
+        FormFieldKind::Radio { value, checked } => {
+            let index = match ctx.form.radios.iter().position(|g| g.name == field.name) {
+                Some(index) => index,
+                None => {
+                    let id = ctx.alloc.bump();
+                    let name = field.name.clone();
+                    ctx.form.radios.push(RadioGroup {
+                        name,
+                        id,
+                        kids: vec![],
+                        value: None,
+                    });
+                    ctx.form.radios.len() - 1
+                }
+            };
+
+            let group = &mut ctx.form.radios[index];
+            group.kids.push(id);
+            if *checked && group.value.is_none() {
+                group.value = Some(value.clone());
+            }
+            Some(group.id)
+        }
+        _ => {
+            ctx.form.fields.push(id);
+            None
+        }
+    };
+
+    let mut widget = ctx.writer.indirect(id).dict();
+    widget.pair(Name(b"Type"), Name(b"Annot"));
+    widget.pair(Name(b"Subtype"), Name(b"Widget"));
+    widget.pair(Name(b"Rect"), rect);
+    widget.pair(Name(b"F"), 4);
+    widget.pair(Name(b"P"), page_ref);
+
+    // The colors allow viewers to regenerate the appearance.
+    let mut characteristics = widget.insert(Name(b"MK")).dict();
+    if let Some(Paint::Solid(color)) = &field.fill {
+        characteristics.insert(Name(b"BG")).array().items(rgb(color));
+    }
+    if let Some(Paint::Solid(color)) = field.stroke.as_ref().map(|s| &s.paint) {
+        characteristics.insert(Name(b"BC")).array().items(rgb(color));
+    }
+    characteristics.finish();
+
+    if let Some(parent) = parent {
+        widget.pair(Name(b"Parent"), parent);
+    } else {
+        widget.pair(Name(b"T"), TextStr(&field.name));
+    }
+
+    // Viewers draw the text of edited fields in the same font as the rest of
+    // the document, which is referenced from the form's resources.
+    let default_appearance =
+        font.map(|font| eco_format!("/{} {} Tf 0 g", font, field.text_size.to_f32()));
+    if let Some(appearance) = &default_appearance {
+        ctx.form.appearance.get_or_insert_with(|| appearance.clone());
+    }
+    match &field.kind {
+        FormFieldKind::Text { value, multiline } => {
+            widget.pair(Name(b"FT"), Name(b"Tx"));
+            widget.pair(Name(b"V"), TextStr(value));
+            widget.pair(Name(b"DV"), TextStr(value));
+            if *multiline {
+                widget.pair(Name(b"Ff"), MULTILINE);
+            }
+            if let Some(appearance) = &default_appearance {
+                widget.pair(Name(b"DA"), TextStr(appearance));
+            }
+            widget.insert(Name(b"AP")).dict().pair(Name(b"N"), normal_ref);
+        }
+        FormFieldKind::Checkbox { checked } => {
+            let state = if *checked { ON } else { OFF };
+            widget.pair(Name(b"FT"), Name(b"Btn"));
+            widget.pair(Name(b"V"), state);
+            widget.pair(Name(b"DV"), state);
+            widget.pair(Name(b"AS"), state);
+            write_states(&mut widget, ON, normal_ref, checked_ref);
+        }
+        FormFieldKind::Radio { value, checked } => {
+            let on = Name(value.as_bytes());
+            widget.pair(Name(b"AS"), if *checked { on } else { OFF });
+            write_states(&mut widget, on, normal_ref, checked_ref);
+        }
+        FormFieldKind::Dropdown { options, selected } => {
+            widget.pair(Name(b"FT"), Name(b"Ch"));
+            widget.pair(Name(b"Ff"), COMBO);
+            widget
+                .insert(Name(b"Opt"))
+                .array()
+                .items(options.iter().map(|option| TextStr(option)));
+            if let Some(selected) = selected {
+                widget.pair(Name(b"V"), TextStr(selected));
+                widget.pair(Name(b"DV"), TextStr(selected));
+            }
+            if let Some(appearance) = &default_appearance {
+                widget.pair(Name(b"DA"), TextStr(appearance));
+            }
+            widget.insert(Name(b"AP")).dict().pair(Name(b"N"), normal_ref);
+        }
+    }
+}
+
+/// Write the document's interactive form.
+///
+/// Returns `None` if the document has no form fields.
+#[tracing::instrument(skip_all)]
+pub fn write_form(ctx: &mut PdfContext) -> Option<Ref> {
+    if ctx.form.fields.is_empty() && ctx.form.radios.is_empty() {
+        return None;
+    }
+
+    for group in std::mem::take(&mut ctx.form.radios) {
+        let mut field = ctx.writer.indirect(group.id).dict();
+        field.pair(Name(b"FT"), Name(b"Btn"));
+        field.pair(Name(b"T"), TextStr(&group.name));
+        field.pair(Name(b"Ff"), RADIO | NO_TOGGLE_TO_OFF);
+        let value = group.value.as_ref().map_or(OFF, |value| Name(value.as_bytes()));
+        field.pair(Name(b"V"), value);
+        field.pair(Name(b"DV"), value);
+        field.insert(Name(b"Kids")).array().items(group.kids);
+        field.finish();
+        ctx.form.fields.push(group.id);
+    }
+
+    let form_ref = ctx.alloc.bump();
+    let mut form = ctx.writer.indirect(form_ref).dict();
+    form.insert(Name(b"Fields"))
+        .array()
+        .items(ctx.form.fields.iter().copied());
+    form.pair(Name(b"DR"), ctx.global_resources_ref);
+    if let Some(appearance) = &ctx.form.appearance {
+        form.pair(Name(b"DA"), TextStr(appearance));
+    }
+    form.finish();
+
+    Some(form_ref)
+}
+
+/// Write an appearance stream as a form XObject.
+fn write_appearance(ctx: &mut PdfContext, data: &[u8], size: Size) -> Ref {
+    let id = ctx.alloc.bump();
+    let mut appearance = ctx.writer.form_xobject(id, data);
+    appearance.bbox(Rect::new(0.0, 0.0, size.x.to_f32(), size.y.to_f32()));
+    appearance.pair(Name(b"Resources"), ctx.global_resources_ref);
+    appearance.filter(Filter::FlateDecode);
+    appearance.finish();
+    id
+}
+
+/// Write the appearances of a checkbox or radio button for both of its
+/// states.
+fn write_states(widget: &mut Dict, on: Name, off_ref: Ref, on_ref: Option<Ref>) {
+    let mut appearances = widget.insert(Name(b"AP")).dict();
+    let mut normal = appearances.insert(Name(b"N")).dict();
+    normal.pair(OFF, off_ref);
+    if let Some(on_ref) = on_ref {
+        normal.pair(on, on_ref);
+    }
+}
+
+/// The RGB components of a color.
+fn rgb(color: &Color) -> [f32; 3] {
+    let c = color.to_rgba();
+    [c.r, c.g, c.b].map(|v| v as f32 / 255.0)
+}
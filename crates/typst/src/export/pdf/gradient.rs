@@ -0,0 +1,212 @@
+use std::f64::consts::PI;
+
+use pdf_writer::types::FunctionShadingType;
+use pdf_writer::writers::ColorSpace;
+use pdf_writer::{Filter, Finish, Name, Ref};
+
+use super::{deflate, PdfContext, RefExt};
+use crate::geom::{Color, Gradient, Size, Transform};
+
+/// A gradient as it is placed on a page.
+///
+/// PDF patterns live in the default coordinate space of the page, so each
+/// placement of a gradient with a different transform or bounding box needs
+/// its own pattern.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct PdfGradient {
+    /// The gradient.
+    pub gradient: Gradient,
+    /// Maps from the gradient's coordinate system to the page's default
+    /// coordinate system.
+    ///
+    /// For linear gradients, the gradient's coordinate system is the bounding
+    /// box in points. For radial and conic gradients, it is the unit square
+    /// stretched over the bounding box.
+    pub transform: Transform,
+    /// The size of the bounding box the gradient is applied to.
+    pub size: Size,
+}
+
+/// How many triangles to use for a full turn of a conic gradient.
+const CONIC_SEGMENTS: usize = 256;
+
+/// Write all used gradients as shading patterns.
+#[tracing::instrument(skip_all)]
+pub fn write_gradients(ctx: &mut PdfContext) {
+    let gradients: Vec<_> = ctx.gradient_map.items().cloned().collect();
+    for PdfGradient { gradient, transform, size } in gradients {
+        let pattern_ref = ctx.alloc.bump();
+        ctx.gradient_refs.push(pattern_ref);
+
+        let Transform { sx, ky, kx, sy, tx, ty } = transform;
+        let matrix = [
+            sx.get() as f32,
+            ky.get() as f32,
+            kx.get() as f32,
+            sy.get() as f32,
+            tx.to_pt() as f32,
+            ty.to_pt() as f32,
+        ];
+
+        match &gradient {
+            Gradient::Linear(linear) => {
+                let function = write_function(ctx, &gradient);
+                let (start, end) = linear.axis(size);
+                let mut pattern = ctx.writer.shading_pattern(pattern_ref);
+                let mut shading = pattern.function_shading();
+                shading.shading_type(FunctionShadingType::Axial);
+                shading.color_space().srgb();
+                shading
+                    .function(function)
+                    .coords([
+                        start.x.to_pt() as f32,
+                        start.y.to_pt() as f32,
+                        end.x.to_pt() as f32,
+                        end.y.to_pt() as f32,
+                    ])
+                    .extend([true, true]);
+                shading.finish();
+                pattern.matrix(matrix);
+            }
+            Gradient::Radial(radial) => {
+                let function = write_function(ctx, &gradient);
+                let cx = radial.center.x.get() as f32;
+                let cy = radial.center.y.get() as f32;
+                let r = radial.radius.get() as f32;
+                let mut pattern = ctx.writer.shading_pattern(pattern_ref);
+                let mut shading = pattern.function_shading();
+                shading.shading_type(FunctionShadingType::Radial);
+                shading.color_space().srgb();
+                shading
+                    .function(function)
+                    .coords([cx, cy, 0.0, cx, cy, r])
+                    .extend([true, true]);
+                shading.finish();
+                pattern.matrix(matrix);
+            }
+            Gradient::Conic(_) => {
+                let shading_ref = write_conic_mesh(ctx, &gradient);
+                let mut pattern = ctx.writer.indirect(pattern_ref).dict();
+                pattern.pair(Name(b"Type"), Name(b"Pattern"));
+                pattern.pair(Name(b"PatternType"), 2);
+                pattern.pair(Name(b"Shading"), shading_ref);
+                pattern.insert(Name(b"Matrix")).array().items(matrix);
+            }
+        }
+    }
+}
+
+/// Write the function that maps from the gradient's offset to its color.
+///
+/// The function is stitched together from linear interpolations between the
+/// gradient's stops.
+fn write_function(ctx: &mut PdfContext, gradient: &Gradient) -> Ref {
+    let stops = gradient.srgb_stops();
+    let mut functions = vec![];
+    let mut bounds = vec![];
+
+    for window in stops.windows(2) {
+        let ((c0, a), (c1, b)) = (&window[0], &window[1]);
+        if b <= a {
+            continue;
+        }
+
+        if !functions.is_empty() {
+            bounds.push(a.get() as f32);
+        }
+
+        let function_ref = ctx.alloc.bump();
+        ctx.writer
+            .exponential_function(function_ref)
+            .domain([0.0, 1.0])
+            .range([0.0, 1.0, 0.0, 1.0, 0.0, 1.0])
+            .c0(rgb(c0))
+            .c1(rgb(c1))
+            .n(1.0);
+        functions.push(function_ref);
+    }
+
+    let function_ref = ctx.alloc.bump();
+    ctx.writer
+        .stitching_function(function_ref)
+        .domain([0.0, 1.0])
+        .range([0.0, 1.0, 0.0, 1.0, 0.0, 1.0])
+        .functions(functions.iter().copied())
+        .bounds(bounds)
+        .encode(functions.iter().flat_map(|_| [0.0, 1.0]));
+
+    function_ref
+}
+
+/// Write a conic gradient as a free-form triangle mesh shading.
+///
+/// The mesh is a fan of thin triangles around the center whose outer
+/// vertices lie outside of the unit square.
+fn write_conic_mesh(ctx: &mut PdfContext, gradient: &Gradient) -> Ref {
+    let Gradient::Conic(conic) = gradient else { unreachable!() };
+    let cx = conic.center.x.get();
+    let cy = conic.center.y.get();
+
+    // The triangles must reach the corner farthest from the center.
+    let radius = [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 1.0)]
+        .into_iter()
+        .map(|(x, y): (f64, f64)| (x - cx).hypot(y - cy))
+        .fold(0.0, f64::max)
+        * 1.1;
+
+    let (min_x, max_x) = (cx - radius, cx + radius);
+    let (min_y, max_y) = (cy - radius, cy + radius);
+    let quantize = |v: f64, min: f64, max: f64| {
+        ((v - min) / (max - min) * u16::MAX as f64).round() as u16
+    };
+
+    let mut data = vec![];
+    let mut vertex = |x: f64, y: f64, color: Color| {
+        let c = color.to_rgba();
+        data.push(0);
+        data.extend(quantize(x, min_x, max_x).to_be_bytes());
+        data.extend(quantize(y, min_y, max_y).to_be_bytes());
+        data.extend([c.r, c.g, c.b]);
+    };
+
+    for i in 0..CONIC_SEGMENTS {
+        let t0 = i as f64 / CONIC_SEGMENTS as f64;
+        let t1 = (i + 1) as f64 / CONIC_SEGMENTS as f64;
+        let a0 = conic.angle.to_rad() + 2.0 * PI * t0;
+        let a1 = conic.angle.to_rad() + 2.0 * PI * t1;
+        vertex(cx, cy, gradient.sample((t0 + t1) / 2.0));
+        vertex(cx + radius * a0.cos(), cy + radius * a0.sin(), gradient.sample(t0));
+        vertex(cx + radius * a1.cos(), cy + radius * a1.sin(), gradient.sample(t1));
+    }
+
+    let data = deflate(&data);
+    let shading_ref = ctx.alloc.bump();
+    let mut stream = ctx.writer.stream(shading_ref, &data);
+    stream.filter(Filter::FlateDecode);
+    stream.pair(Name(b"ShadingType"), 4);
+    stream.insert(Name(b"ColorSpace")).start::<ColorSpace>().srgb();
+    stream.pair(Name(b"BitsPerCoordinate"), 16);
+    stream.pair(Name(b"BitsPerComponent"), 8);
+    stream.pair(Name(b"BitsPerFlag"), 8);
+    stream.insert(Name(b"Decode")).array().items([
+        min_x as f32,
+        max_x as f32,
+        min_y as f32,
+        max_y as f32,
+        0.0,
+        1.0,
+        0.0,
+        1.0,
+        0.0,
+        1.0,
+    ]);
+    stream.finish();
+
+    shading_ref
+}
+
+/// Convert a color into sRGB components.
+fn rgb(color: &Color) -> [f32; 3] {
+    let c = color.to_rgba();
+    [c.r, c.g, c.b].map(|v| v as f32 / 255.0)
+}
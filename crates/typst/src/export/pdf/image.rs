@@ -1,12 +1,19 @@
 use std::io::Cursor;
 
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
 use image::{DynamicImage, GenericImageView, Rgba};
 use pdf_writer::{Filter, Finish};
 
 use super::{deflate, PdfContext, RefExt};
+use crate::geom::Axes;
 use crate::image::{DecodedImage, Image, RasterFormat};
 use crate::util::Bytes;
 
+/// The quality at which JPEG images are re-encoded if no other quality is
+/// requested.
+const DEFAULT_JPEG_QUALITY: u8 = 75;
+
 /// Embed all used images into the PDF.
 #[tracing::instrument(skip_all)]
 pub fn write_images(ctx: &mut PdfContext) {
@@ -15,25 +22,33 @@ pub fn write_images(ctx: &mut PdfContext) {
         let icc_ref = ctx.alloc.bump();
         ctx.image_refs.push(image_ref);
 
-        let width = image.width();
-        let height = image.height();
-
         // Add the primary image.
-        // TODO: Error if image could not be encoded.
         match image.decoded().as_ref() {
-            DecodedImage::Raster(dynamic, icc, _) => {
+            DecodedImage::Raster(_, icc, _) => {
+                // The largest number of pixels that is still needed to show
+                // the image at the requested resolution wherever it's placed.
+                let max_size = ctx
+                    .options
+                    .image_dpi
+                    .zip(ctx.image_extents.get(image))
+                    .map(|(dpi, extent)| {
+                        extent.map(|length| {
+                            (length.to_inches() * f64::from(dpi)).ceil().max(1.0) as u32
+                        })
+                    });
+
                 // TODO: Error if image could not be encoded.
-                let (data, filter, has_color) = encode_image(image);
-                let mut image = ctx.writer.image_xobject(image_ref, &data);
-                image.filter(filter);
-                image.width(width as i32);
-                image.height(height as i32);
+                let encoded = encode_image(image, max_size, ctx.options.jpeg_quality);
+                let mut image = ctx.writer.image_xobject(image_ref, &encoded.data);
+                image.filter(encoded.filter);
+                image.width(encoded.size.x as i32);
+                image.height(encoded.size.y as i32);
                 image.bits_per_component(8);
 
                 let space = image.color_space();
                 if icc.is_some() {
                     space.icc_based(icc_ref);
-                } else if has_color {
+                } else if encoded.has_color {
                     space.device_rgb();
                 } else {
                     space.device_gray();
@@ -41,16 +56,15 @@ pub fn write_images(ctx: &mut PdfContext) {
 
                 // Add a second gray-scale image containing the alpha values if
                 // this image has an alpha channel.
-                if dynamic.color().has_alpha() {
-                    let (alpha_data, alpha_filter) = encode_alpha(dynamic);
+                if let Some(alpha) = &encoded.alpha {
                     let mask_ref = ctx.alloc.bump();
                     image.s_mask(mask_ref);
                     image.finish();
 
-                    let mut mask = ctx.writer.image_xobject(mask_ref, &alpha_data);
-                    mask.filter(alpha_filter);
-                    mask.width(width as i32);
-                    mask.height(height as i32);
+                    let mut mask = ctx.writer.image_xobject(mask_ref, alpha);
+                    mask.filter(Filter::FlateDecode);
+                    mask.width(encoded.size.x as i32);
+                    mask.height(encoded.size.y as i32);
                     mask.color_space().device_gray();
                     mask.bits_per_component(8);
                 } else {
@@ -61,7 +75,7 @@ pub fn write_images(ctx: &mut PdfContext) {
                     let compressed = deflate(&icc.0);
                     let mut stream = ctx.writer.icc_profile(icc_ref, &compressed);
                     stream.filter(Filter::FlateDecode);
-                    if has_color {
+                    if encoded.has_color {
                         stream.n(3);
                         stream.alternate().srgb();
                     } else {
@@ -83,32 +97,63 @@ pub fn write_images(ctx: &mut PdfContext) {
     }
 }
 
-/// Encode an image with a suitable filter and return the data, filter and
-/// whether the image has color.
+/// A raster image encoded for embedding into the PDF.
+#[derive(Clone)]
+struct EncodedImage {
+    /// The encoded color channels.
+    data: Bytes,
+    /// The filter with which `data` is encoded.
+    filter: Filter,
+    /// Whether the image has color.
+    has_color: bool,
+    /// The size of the encoded image in pixels.
+    size: Axes<u32>,
+    /// The deflated alpha channel, if the image has one.
+    alpha: Option<Bytes>,
+}
+
+/// Encode an image with a suitable filter.
 ///
-/// Skips the alpha channel as that's encoded separately.
+/// Images that are larger than `max_size` in both dimensions are downsampled
+/// first. JPEG images that needn't be downsampled are embedded as is if their
+/// color space allows it. Otherwise, they are re-encoded at the given quality.
 #[comemo::memoize]
 #[tracing::instrument(skip_all)]
-fn encode_image(image: &Image) -> (Bytes, Filter, bool) {
+fn encode_image(
+    image: &Image,
+    max_size: Option<Axes<u32>>,
+    quality: Option<u8>,
+) -> EncodedImage {
     let decoded = image.decoded();
     let (dynamic, format) = match decoded.as_ref() {
         DecodedImage::Raster(dynamic, _, format) => (dynamic, *format),
         _ => panic!("can only encode raster image"),
     };
 
-    match (format, dynamic) {
+    let resized = max_size
+        .and_then(|max| downsampled_size(image.size(), max))
+        .map(|size| dynamic.resize_exact(size.x, size.y, FilterType::Triangle));
+    let buf = resized.as_ref().unwrap_or(dynamic);
+    let quality = quality.unwrap_or(DEFAULT_JPEG_QUALITY);
+
+    let (data, filter, has_color) = match (format, buf) {
+        // 8-bit gray or RGB JPEG that can be embedded without re-encoding.
+        (RasterFormat::Jpg, DynamicImage::ImageLuma8(_) | DynamicImage::ImageRgb8(_))
+            if resized.is_none()
+                && jpeg_components(image.data()) == Some(buf.color().channel_count()) =>
+        {
+            let has_color = buf.color().has_color();
+            (image.data().clone(), Filter::DctDecode, has_color)
+        }
+
         // 8-bit gray JPEG.
         (RasterFormat::Jpg, DynamicImage::ImageLuma8(_)) => {
-            let mut data = Cursor::new(vec![]);
-            dynamic.write_to(&mut data, image::ImageFormat::Jpeg).unwrap();
-            (data.into_inner().into(), Filter::DctDecode, false)
+            (encode_jpeg(buf, quality), Filter::DctDecode, false)
         }
 
         // 8-bit RGB JPEG (CMYK JPEGs get converted to RGB earlier).
         (RasterFormat::Jpg, DynamicImage::ImageRgb8(_)) => {
-            let mut data = Cursor::new(vec![]);
-            dynamic.write_to(&mut data, image::ImageFormat::Jpeg).unwrap();
-            (data.into_inner().into(), Filter::DctDecode, true)
+            (encode_jpeg(buf, quality), Filter::DctDecode, true)
         }
 
         // TODO: Encode flate streams with PNG-predictor?
@@ -132,12 +177,135 @@ fn encode_image(image: &Image) -> (Bytes, Filter, bool) {
             let data = deflate(&pixels);
             (data.into(), Filter::FlateDecode, true)
         }
+    };
+
+    let size = Axes::new(buf.width(), buf.height());
+    let alpha = buf.color().has_alpha().then(|| encode_alpha(buf));
+    EncodedImage { data, filter, has_color, size, alpha }
+}
+
+/// Determine the size to which an image must be downsampled to not exceed
+/// `max` in both dimensions, preserving its aspect ratio.
+///
+/// Returns `None` if the image is small enough already.
+fn downsampled_size(size: Axes<u32>, max: Axes<u32>) -> Option<Axes<u32>> {
+    // Scale by the smaller factor so that neither dimension drops below the
+    // requested resolution.
+    let scale =
+        (f64::from(max.x) / f64::from(size.x)).max(f64::from(max.y) / f64::from(size.y));
+    (scale < 1.0).then(|| size.map(|v| (f64::from(v) * scale).round().max(1.0) as u32))
+}
+
+/// Encode a gray or RGB image as a JPEG.
+fn encode_jpeg(dynamic: &DynamicImage, quality: u8) -> Bytes {
+    let mut data = Cursor::new(vec![]);
+    JpegEncoder::new_with_quality(&mut data, quality)
+        .encode(dynamic.as_bytes(), dynamic.width(), dynamic.height(), dynamic.color())
+        .unwrap();
+    data.into_inner().into()
+}
+
+/// Read the number of color components from a JPEG's frame header.
+fn jpeg_components(data: &[u8]) -> Option<u8> {
+    if !data.starts_with(&[0xFF, 0xD8]) {
+        return None;
+    }
+
+    let mut i = 2;
+    while i + 4 <= data.len() {
+        if data[i] != 0xFF {
+            return None;
+        }
+
+        let marker = data[i + 1];
+        match marker {
+            // Fill byte in front of a marker.
+            0xFF => i += 1,
+            // Start of frame, except for the DHT, JPG and DAC markers that
+            // share the range.
+            0xC0..=0xCF if !matches!(marker, 0xC4 | 0xC8 | 0xCC) => {
+                return data.get(i + 9).copied();
+            }
+            _ => {
+                let len = u16::from_be_bytes([data[i + 2], data[i + 3]]);
+                i += 2 + usize::from(len);
+            }
+        }
     }
+
+    None
 }
 
-/// Encode an image's alpha channel if present.
+/// Encode an image's alpha channel.
 #[tracing::instrument(skip_all)]
-fn encode_alpha(dynamic: &DynamicImage) -> (Vec<u8>, Filter) {
+fn encode_alpha(dynamic: &DynamicImage) -> Bytes {
     let pixels: Vec<_> = dynamic.pixels().map(|(_, _, Rgba([_, _, _, a]))| a).collect();
-    (deflate(&pixels), Filter::FlateDecode)
+    deflate(&pixels).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{GrayImage, RgbImage};
+
+    use super::*;
+
+    #[test]
+    fn test_downsampled_size() {
+        // Images that are small enough stay untouched.
+        assert_eq!(downsampled_size(Axes::new(100, 50), Axes::new(200, 100)), None);
+        assert_eq!(downsampled_size(Axes::new(100, 50), Axes::new(100, 50)), None);
+        assert_eq!(downsampled_size(Axes::new(100, 50), Axes::new(10, 80)), None);
+
+        // The aspect ratio is preserved.
+        assert_eq!(
+            downsampled_size(Axes::new(1000, 500), Axes::new(100, 20)),
+            Some(Axes::new(100, 50)),
+        );
+    }
+
+    #[test]
+    fn test_downsampled_size_tiny() {
+        // A zero DPI must not produce an empty image.
+        assert_eq!(
+            downsampled_size(Axes::new(300, 200), Axes::new(0, 0)),
+            Some(Axes::new(1, 1)),
+        );
+
+        // Neither dimension of a very thin image drops to zero.
+        assert_eq!(
+            downsampled_size(Axes::new(1000, 1), Axes::new(1, 0)),
+            Some(Axes::new(1, 1)),
+        );
+        assert_eq!(
+            downsampled_size(Axes::new(2, 2000), Axes::new(1, 1)),
+            Some(Axes::new(1, 1000)),
+        );
+    }
+
+    #[test]
+    fn test_jpeg_components() {
+        let gray = DynamicImage::ImageLuma8(GrayImage::new(2, 2));
+        let rgb = DynamicImage::ImageRgb8(RgbImage::new(2, 2));
+        assert_eq!(jpeg_components(&encode_jpeg(&gray, 75)), Some(1));
+        assert_eq!(jpeg_components(&encode_jpeg(&rgb, 75)), Some(3));
+    }
+
+    #[test]
+    fn test_jpeg_components_cmyk() {
+        // An APP0 segment, a DHT segment and a fill byte in front of a
+        // progressive frame header with four components.
+        let data = [
+            0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00, 0xFF, 0xC4, 0x00, 0x03, 0x00,
+            0xFF, 0xFF, 0xC2, 0x00, 0x14, 0x08, 0x00, 0x10, 0x00, 0x10, 0x04,
+        ];
+        assert_eq!(jpeg_components(&data), Some(4));
+    }
+
+    #[test]
+    fn test_jpeg_components_invalid() {
+        assert_eq!(jpeg_components(&[]), None);
+        assert_eq!(jpeg_components(b"\x89PNG\r\n\x1a\n"), None);
+        assert_eq!(jpeg_components(&[0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x10]), None);
+        assert_eq!(jpeg_components(&[0xFF, 0xD8, 0xFF, 0xC0, 0x00, 0x11, 0x08]), None);
+    }
 }
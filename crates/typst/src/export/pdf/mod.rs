@@ -1,58 +1,237 @@
 //! Exporting into PDF documents.
 
+mod destination;
+mod embed;
 mod font;
+mod form;
+mod gradient;
 mod image;
 mod outline;
 mod page;
+mod pattern;
+mod structure;
 
 use std::cmp::Eq;
 use std::collections::{BTreeMap, HashMap};
 use std::hash::Hash;
+use std::num::NonZeroUsize;
 
 use ecow::EcoString;
 use pdf_writer::types::Direction;
-use pdf_writer::{Finish, Name, PdfWriter, Ref, TextStr};
-use xmp_writer::{LangId, RenditionClass, XmpWriter};
+use pdf_writer::{Filter, Finish, Name, PdfWriter, Ref, TextStr};
+use xmp_writer::{LangId, Namespace, RenditionClass, XmpWriter};
 
-use self::page::Page;
+use self::embed::PdfFile;
+use self::form::AcroForm;
+use self::gradient::PdfGradient;
+use self::page::{Page, PdfPageLabel};
+use self::pattern::PdfPattern;
+use self::structure::StructTree;
+use crate::diag::{bail, SourceDiagnostic, SourceResult, StrResult};
 use crate::doc::{Document, Lang};
+use crate::eval::Datetime;
 use crate::font::Font;
-use crate::geom::{Abs, Dir, Em};
+use crate::geom::{Abs, Dir, Em, Pattern, Size, SpotAlternate};
 use crate::image::Image;
 use crate::model::Introspector;
+use crate::syntax::Span;
+use crate::util::{hash128, Bytes};
 
 /// Export a document into a PDF file.
 ///
-/// Returns the raw bytes making up the PDF file.
+/// Returns the raw bytes making up the PDF file or the reasons why the
+/// document can't be exported.
+pub fn pdf(document: &Document) -> SourceResult<Vec<u8>> {
+    pdf_with_options(document, &PdfOptions::default())
+}
+
+/// Export a document into a PDF file with the given settings.
+///
+/// Returns the raw bytes making up the PDF file or the reasons why the
+/// document can't be exported, for example in conformance with the requested
+/// standard.
 #[tracing::instrument(skip_all)]
-pub fn pdf(document: &Document) -> Vec<u8> {
-    let mut ctx = PdfContext::new(document);
+pub fn pdf_with_options(
+    document: &Document,
+    options: &PdfOptions,
+) -> SourceResult<Vec<u8>> {
+    let mut ctx = PdfContext::new(document, options);
+    if ctx.is_pdfa() && options.output_profile.as_ref().is_some_and(|p| p.components != 3)
+    {
+        ctx.error(
+            Span::detached(),
+            "PDF/A export requires an RGB output profile",
+            "PDF/A documents may only use RGB colors, try an RGB profile instead",
+        );
+    }
+
+    if ctx.is_pdfx() && options.output_profile.is_none() {
+        ctx.error(
+            Span::detached(),
+            "PDF/X export requires an output profile",
+            "PDF/X documents must describe their printing condition with an ICC profile",
+        );
+    }
+
     page::construct_pages(&mut ctx, &document.pages);
+    if !ctx.errors.is_empty() {
+        return Err(Box::new(ctx.errors));
+    }
+
     font::write_fonts(&mut ctx);
     image::write_images(&mut ctx);
+    embed::write_files(&mut ctx);
+    gradient::write_gradients(&mut ctx);
+    pattern::write_patterns(&mut ctx);
     page::write_page_tree(&mut ctx);
     write_catalog(&mut ctx);
-    ctx.writer.finish()
+
+    // The standards require a file identifier. It is derived from the
+    // document so that the same document always results in the same file.
+    if options.standard != PdfStandard::V1_7 {
+        let id = hash128(document).to_be_bytes().to_vec();
+        ctx.writer.set_file_id((id.clone(), id));
+    }
+
+    Ok(ctx.writer.finish())
+}
+
+/// Settings for PDF export.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+pub struct PdfOptions {
+    /// The standard the exported file should conform to.
+    pub standard: PdfStandard,
+    /// The zero-based indices of the pages to export, in ascending order. If
+    /// this is `None`, all pages are exported.
+    pub pages: Option<Vec<usize>>,
+    /// Whether headings without a label are exported as named destinations,
+    /// too. Labelled elements always are.
+    pub heading_destinations: bool,
+    /// The profile of the device the document is printed on. PDF/X documents
+    /// require one, PDF/A documents fall back to an sRGB profile and plain
+    /// PDF documents have no output intent.
+    pub output_profile: Option<OutputProfile>,
+    /// The maximum resolution, in dots per inch, at which raster images are
+    /// embedded. Images with more pixels than needed for this resolution at
+    /// the largest size they are placed at are downsampled. If this is
+    /// `None`, images are embedded at their full resolution.
+    pub image_dpi: Option<u32>,
+    /// The quality, from 1 to 100, at which JPEG images are re-encoded when
+    /// they can't be embedded as they are, for example because they were
+    /// downsampled. If this is `None`, a quality of 75 is used.
+    pub jpeg_quality: Option<u8>,
+}
+
+/// An ICC profile that describes the device a document is printed on.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct OutputProfile {
+    /// The raw ICC profile.
+    data: Bytes,
+    /// The name of the printing condition the profile describes.
+    condition: EcoString,
+    /// The number of color components of the profile's color space.
+    components: i32,
+}
+
+impl OutputProfile {
+    /// Create an output profile from an ICC profile and the name of the
+    /// printing condition it describes, like `FOGRA39`.
+    ///
+    /// Fails if the profile isn't for a gray, RGB or CMYK color space.
+    pub fn new(data: Bytes, condition: EcoString) -> StrResult<Self> {
+        // The color space's signature is part of the profile's header.
+        let components = match data.get(16..20) {
+            Some(b"GRAY") => 1,
+            Some(b"RGB ") => 3,
+            Some(b"CMYK") => 4,
+            _ => bail!("output profile must be a gray, RGB or CMYK ICC profile"),
+        };
+
+        Ok(Self { data, condition, components })
+    }
+}
+
+/// A PDF standard that an exported file can conform to.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum PdfStandard {
+    /// Plain PDF 1.7.
+    #[default]
+    V1_7,
+    /// PDF/A-2b for long-term archival. Embeds an sRGB output intent and
+    /// forbids CMYK colors and glyphs that are missing from their font.
+    A2b,
+    /// PDF/X-4 for print production. Requires an output profile that
+    /// describes the printing condition.
+    X4,
 }
 
 /// Identifies the color space definitions.
 const SRGB: Name<'static> = Name(b"srgb");
 const D65_GRAY: Name<'static> = Name(b"d65gray");
 
+/// Keys of the document information dictionary that custom metadata can't
+/// override.
+const RESERVED_INFO_KEYS: &[&str] = &[
+    "Title",
+    "Author",
+    "Subject",
+    "Keywords",
+    "Creator",
+    "Producer",
+    "CreationDate",
+    "ModDate",
+    "Trapped",
+    "GTS_PDFXVersion",
+];
+
+/// The XMP namespaces of the PDF/A and PDF/X identification schemas.
+const PDFA_ID_NS: Namespace<'static> =
+    Namespace::Custom(("pdfaid", "http://www.aiim.org/pdfa/ns/id/"));
+const PDFX_ID_NS: Namespace<'static> =
+    Namespace::Custom(("pdfxid", "http://www.npes.org/pdfx/ns/id/"));
+
+/// The XMP namespace of the document's custom metadata.
+const CUSTOM_NS: Namespace<'static> =
+    Namespace::Custom(("custom", "http://typst.app/ns/custom/1.0/"));
+
+/// The sRGB color profile used as the output intent for PDF/A.
+const SRGB_ICC: &[u8] = include_bytes!("icc/sRGB-v2.icc");
+
 /// Context for exporting a whole PDF document.
 pub struct PdfContext<'a> {
     document: &'a Document,
+    options: &'a PdfOptions,
     introspector: Introspector,
     writer: PdfWriter,
     pages: Vec<Page>,
     page_heights: Vec<f32>,
+    /// For each page of the document, its index among the exported pages.
+    page_indices: Vec<Option<usize>>,
+    /// The label of each page, `None` for pages without numbering.
+    page_labels: Vec<Option<PdfPageLabel>>,
     alloc: Ref,
     page_tree_ref: Ref,
+    global_resources_ref: Ref,
     font_refs: Vec<Ref>,
     image_refs: Vec<Ref>,
+    gradient_refs: Vec<Ref>,
+    pattern_refs: Vec<Ref>,
+    /// The file specifications of the embedded files.
+    file_refs: Vec<Ref>,
     page_refs: Vec<Ref>,
     font_map: Remapper<Font>,
     image_map: Remapper<Image>,
+    gradient_map: Remapper<PdfGradient>,
+    pattern_map: Remapper<PdfPattern>,
+    file_map: Remapper<PdfFile>,
+    /// The inks of the spot colors, one separation color space each.
+    spot_map: Remapper<EcoString>,
+    /// The alternate color of each ink.
+    spot_alternates: HashMap<EcoString, SpotAlternate>,
+    /// The deflated content streams of the patterns' tiles.
+    pattern_tiles: HashMap<Pattern, Vec<u8>>,
+    /// The largest size at which each image is placed, in both dimensions.
+    image_extents: HashMap<Image, Size>,
     /// For each font a mapping from used glyphs to their text representation.
     /// May contain multiple chars in case of ligatures or similar things. The
     /// same glyph can have a different text representation within one document,
@@ -61,29 +240,81 @@ pub struct PdfContext<'a> {
     /// cmap. This is important for copy-paste and searching.
     glyph_sets: HashMap<Font, BTreeMap<u16, EcoString>>,
     languages: HashMap<Lang, usize>,
+    /// The logical structure of the document.
+    structure: StructTree,
+    /// The document's interactive form.
+    form: AcroForm,
+    /// Problems that prevent the export, like violations of the standard.
+    errors: Vec<SourceDiagnostic>,
 }
 
 impl<'a> PdfContext<'a> {
-    fn new(document: &'a Document) -> Self {
+    fn new(document: &'a Document, options: &'a PdfOptions) -> Self {
         let mut alloc = Ref::new(1);
         let page_tree_ref = alloc.bump();
+        let global_resources_ref = alloc.bump();
         Self {
             document,
+            options,
             introspector: Introspector::new(&document.pages),
             writer: PdfWriter::new(),
             pages: vec![],
             page_heights: vec![],
+            page_indices: vec![],
+            page_labels: vec![],
             alloc,
             page_tree_ref,
+            global_resources_ref,
             page_refs: vec![],
             font_refs: vec![],
             image_refs: vec![],
+            gradient_refs: vec![],
+            pattern_refs: vec![],
+            file_refs: vec![],
             font_map: Remapper::new(),
             image_map: Remapper::new(),
+            gradient_map: Remapper::new(),
+            pattern_map: Remapper::new(),
+            file_map: Remapper::new(),
+            spot_map: Remapper::new(),
+            spot_alternates: HashMap::new(),
+            pattern_tiles: HashMap::new(),
+            image_extents: HashMap::new(),
             glyph_sets: HashMap::new(),
             languages: HashMap::new(),
+            structure: StructTree::new(),
+            form: AcroForm::default(),
+            errors: vec![],
         }
     }
+
+    /// The index of a document page among the exported pages, or `None` if
+    /// the page isn't exported.
+    fn page_index(&self, page: NonZeroUsize) -> Option<usize> {
+        self.page_indices.get(page.get() - 1).copied().flatten()
+    }
+
+    /// Whether the document is exported as PDF/A.
+    fn is_pdfa(&self) -> bool {
+        self.options.standard == PdfStandard::A2b
+    }
+
+    /// Whether the document is exported as PDF/X.
+    fn is_pdfx(&self) -> bool {
+        self.options.standard == PdfStandard::X4
+    }
+
+    /// Report a problem that prevents the export.
+    ///
+    /// The same violation is only reported once per span.
+    fn error(&mut self, span: Span, message: &str, hint: &str) {
+        if self.errors.iter().any(|e| e.span == span && e.message == message) {
+            return;
+        }
+
+        self.errors
+            .push(SourceDiagnostic::error(span, message).with_hint(hint.into()));
+    }
 }
 
 /// Write the document catalog.
@@ -104,7 +335,21 @@ fn write_catalog(ctx: &mut PdfContext) {
     // Write the outline tree.
     let outline_root_id = outline::write_outline(ctx);
 
+    // Write the page labels.
+    let page_labels_id = page::write_page_labels(ctx);
+
+    // Write the structure tree.
+    let struct_tree_root_id = structure::write_structure(ctx);
+
+    // Write the named destinations and the list of embedded files.
+    let dests_id = destination::write_destinations(ctx);
+    let files_id = embed::write_file_tree(ctx);
+
+    // Write the interactive form.
+    let form_id = form::write_form(ctx);
+
     // Write the document information.
+    let (pdfa, pdfx) = (ctx.is_pdfa(), ctx.is_pdfx());
     let mut info = ctx.writer.document_info(ctx.alloc.bump());
     let mut xmp = XmpWriter::new();
     if let Some(title) = &ctx.document.title {
@@ -114,40 +359,189 @@ fn write_catalog(ctx: &mut PdfContext) {
 
     let authors = &ctx.document.author;
     if !authors.is_empty() {
-        info.author(TextStr(&authors.join(", ")));
-        xmp.creator(authors.iter().map(|s| s.as_str()));
+        let joined = authors.join(", ");
+        info.author(TextStr(&joined));
+
+        // PDF/A requires the XMP creators to match the info dictionary's
+        // author entry.
+        if pdfa {
+            xmp.creator([joined.as_str()]);
+        } else {
+            xmp.creator(authors.iter().map(|s| s.as_str()));
+        }
     }
+
+    if let Some(description) = &ctx.document.description {
+        info.subject(TextStr(description));
+        xmp.description([(None, description.as_str())]);
+    }
+
+    let keywords = &ctx.document.keywords;
+    if !keywords.is_empty() {
+        let joined = keywords.join(", ");
+        info.keywords(TextStr(&joined));
+        xmp.pdf_keywords(&joined);
+    }
+
+    if let Some(date) = ctx.document.date {
+        if let Some(pdf_date) = pdf_date(date) {
+            info.creation_date(pdf_date);
+            info.modified_date(pdf_date);
+        }
+        if let Some(xmp_date) = xmp_date(date) {
+            xmp.create_date(xmp_date);
+            xmp.modify_date(xmp_date);
+        }
+    }
+
+    // PDF/A only allows custom metadata whose schema is described in the
+    // metadata itself, so it is left out there, both from the information
+    // dictionary and the XMP metadata.
+    if !pdfa {
+        for (key, value) in &ctx.document.custom {
+            if !RESERVED_INFO_KEYS.contains(&key.as_str()) {
+                info.pair(Name(key.as_bytes()), TextStr(value));
+            }
+            xmp.element(key, CUSTOM_NS).value(value.as_str());
+        }
+    }
+
     info.creator(TextStr("Typst"));
+    if pdfx {
+        info.pair(Name(b"GTS_PDFXVersion"), TextStr("PDF/X-4"));
+        info.pair(Name(b"Trapped"), Name(b"False"));
+    }
     info.finish();
     xmp.creator_tool("Typst");
-    xmp.num_pages(ctx.document.pages.len() as u32);
+    xmp.num_pages(ctx.page_refs.len() as u32);
     xmp.format("application/pdf");
     xmp.language(ctx.languages.keys().map(|lang| LangId(lang.as_str())));
     xmp.rendition_class(RenditionClass::Proof);
     xmp.pdf_version("1.7");
 
+    // Identify the standard the document conforms to.
+    if pdfx {
+        xmp.element("GTS_PDFXVersion", PDFX_ID_NS).value("PDF/X-4");
+    }
+    if pdfa {
+        xmp.element("part", PDFA_ID_NS).value(2);
+        xmp.element("conformance", PDFA_ID_NS).value("B");
+    }
+
     let xmp_buf = xmp.finish(None);
+
     let meta_ref = ctx.alloc.bump();
     let mut meta_stream = ctx.writer.stream(meta_ref, xmp_buf.as_bytes());
     meta_stream.pair(Name(b"Type"), Name(b"Metadata"));
     meta_stream.pair(Name(b"Subtype"), Name(b"XML"));
     meta_stream.finish();
 
+    // Write the output intent's color profile.
+    let profile = ctx.options.output_profile.as_ref();
+    let subtype = if ctx.is_pdfa() { Name(b"GTS_PDFA1") } else { Name(b"GTS_PDFX") };
+    let icc_ref = (ctx.is_pdfa() || ctx.is_pdfx()).then(|| {
+        let icc_ref = ctx.alloc.bump();
+        let (icc, n) = profile.map_or((SRGB_ICC, 3), |p| (&p.data[..], p.components));
+        let data = deflate(icc);
+        let mut stream = ctx.writer.icc_profile(icc_ref, &data);
+        stream.filter(Filter::FlateDecode);
+        stream.n(n);
+        icc_ref
+    });
+
     // Write the document catalog.
     let mut catalog = ctx.writer.catalog(ctx.alloc.bump());
     catalog.pages(ctx.page_tree_ref);
     catalog.viewer_preferences().direction(dir);
     catalog.pair(Name(b"Metadata"), meta_ref);
+    catalog.pair(Name(b"StructTreeRoot"), struct_tree_root_id);
+    catalog.insert(Name(b"MarkInfo")).dict().pair(Name(b"Marked"), true);
+
+    if let Some(icc_ref) = icc_ref {
+        let mut intent = catalog.insert(Name(b"OutputIntents")).array().push().dict();
+        intent.pair(Name(b"Type"), Name(b"OutputIntent"));
+        intent.pair(Name(b"S"), subtype);
+        match profile {
+            Some(profile) => {
+                let condition = TextStr(&profile.condition);
+                intent.pair(Name(b"OutputConditionIdentifier"), condition);
+                intent.pair(Name(b"Info"), condition);
+            }
+            None => {
+                intent.pair(Name(b"OutputConditionIdentifier"), TextStr("sRGB"));
+                intent.pair(Name(b"Info"), TextStr("sRGB IEC61966-2.1"));
+            }
+        }
+        intent.pair(Name(b"DestOutputProfile"), icc_ref);
+    }
 
     if let Some(outline_root_id) = outline_root_id {
         catalog.outlines(outline_root_id);
     }
 
+    if let Some(page_labels_id) = page_labels_id {
+        catalog.pair(Name(b"PageLabels"), page_labels_id);
+    }
+
+    if dests_id.is_some() || files_id.is_some() {
+        let mut names = catalog.insert(Name(b"Names")).dict();
+        if let Some(dests_id) = dests_id {
+            names.pair(Name(b"Dests"), dests_id);
+        }
+        if let Some(files_id) = files_id {
+            names.pair(Name(b"EmbeddedFiles"), files_id);
+        }
+    }
+
+    if let Some(form_id) = form_id {
+        catalog.pair(Name(b"AcroForm"), form_id);
+    }
+
     if let Some(lang) = lang {
         catalog.lang(TextStr(lang.as_str()));
     }
 }
 
+/// Convert a datetime into a PDF date.
+///
+/// Returns `None` if the datetime has no date or the year is negative.
+fn pdf_date(datetime: Datetime) -> Option<pdf_writer::Date> {
+    let year = datetime.year().filter(|&y| y >= 0)? as u16;
+    let mut date = pdf_writer::Date::new(year);
+    if let Some(month) = datetime.month() {
+        date = date.month(month);
+    }
+    if let Some(day) = datetime.day() {
+        date = date.day(day);
+    }
+    if let Some(hour) = datetime.hour() {
+        date = date.hour(hour);
+    }
+    if let Some(minute) = datetime.minute() {
+        date = date.minute(minute);
+    }
+    if let Some(second) = datetime.second() {
+        date = date.second(second);
+    }
+    Some(date)
+}
+
+/// Convert a datetime into an XMP date.
+///
+/// Returns `None` if the datetime has no date or the year is negative.
+fn xmp_date(datetime: Datetime) -> Option<xmp_writer::DateTime> {
+    let year = datetime.year().filter(|&y| y >= 0)? as u16;
+    Some(xmp_writer::DateTime {
+        year,
+        month: datetime.month(),
+        day: datetime.day(),
+        hour: datetime.hour(),
+        minute: datetime.minute(),
+        second: datetime.second(),
+        timezone: None,
+    })
+}
+
 /// Compress data with the DEFLATE algorithm.
 #[tracing::instrument(skip_all)]
 fn deflate(data: &[u8]) -> Vec<u8> {
@@ -233,3 +627,158 @@ impl RefExt for Ref {
         prev
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::{EmbeddedFile, Frame, FrameItem, Meta, PrintBoxes};
+    use crate::geom::{CmykColor, Geometry, Point, RgbaColor, SpotColor};
+
+    fn document(colors: &[SpotColor]) -> Document {
+        let mut frame = Frame::new(Size::splat(Abs::pt(10.0)));
+        for color in colors {
+            let rect = Geometry::Rect(Size::splat(Abs::pt(5.0)));
+            let shape = rect.filled(color.clone().into());
+            frame.push(Point::zero(), FrameItem::Shape(shape, Span::detached()));
+        }
+        Document { pages: vec![frame], ..Default::default() }
+    }
+
+    fn export(document: &Document, standard: PdfStandard) -> SourceResult<Vec<u8>> {
+        let profile = OutputProfile::new(Bytes::from_static(SRGB_ICC), "sRGB".into());
+        let options = PdfOptions {
+            standard,
+            output_profile: (standard == PdfStandard::X4).then(|| profile.unwrap()),
+            ..Default::default()
+        };
+        pdf_with_options(document, &options)
+    }
+
+    fn contains(pdf: &[u8], needle: &str) -> bool {
+        pdf.windows(needle.len()).any(|window| window == needle.as_bytes())
+    }
+
+    #[test]
+    fn test_pdf_spot_colors_share_separation() {
+        let alternate = SpotAlternate::Cmyk(CmykColor::new(0, 255, 0, 0));
+        let spots = [
+            SpotColor::new("Magenta Ink".into(), alternate, 255),
+            SpotColor::new("Magenta Ink".into(), alternate, 128),
+        ];
+
+        let pdf = export(&document(&spots), PdfStandard::V1_7).unwrap();
+        assert_eq!(pdf.windows(10).filter(|w| w == b"Separation").count(), 1);
+    }
+
+    #[test]
+    fn test_pdf_spot_colors_conflicting_alternates() {
+        let spots = [
+            SpotColor::new(
+                "Brand".into(),
+                SpotAlternate::Cmyk(CmykColor::new(0, 0, 255, 0)),
+                255,
+            ),
+            SpotColor::new(
+                "Brand".into(),
+                SpotAlternate::Rgb(RgbaColor::new(255, 255, 0, 255)),
+                255,
+            ),
+        ];
+
+        let errors = export(&document(&spots), PdfStandard::V1_7).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].message,
+            "spot color `Brand` is used with different alternates"
+        );
+    }
+
+    #[test]
+    fn test_pdf_output_intent_matches_standard() {
+        let document = document(&[]);
+        let plain = export(&document, PdfStandard::V1_7).unwrap();
+        assert!(!contains(&plain, "OutputIntent"));
+
+        let archival = export(&document, PdfStandard::A2b).unwrap();
+        assert!(contains(&archival, "GTS_PDFA1"));
+        assert!(!contains(&archival, "GTS_PDFX"));
+
+        let print = export(&document, PdfStandard::X4).unwrap();
+        assert!(contains(&print, "/GTS_PDFX"));
+        assert!(contains(&print, "<pdfxid:GTS_PDFXVersion>PDF/X-4"));
+
+        let missing = pdf_with_options(
+            &document,
+            &PdfOptions { standard: PdfStandard::X4, ..Default::default() },
+        );
+        assert_eq!(
+            missing.unwrap_err()[0].message,
+            "PDF/X export requires an output profile"
+        );
+    }
+
+    #[test]
+    fn test_pdf_file_id() {
+        let document = document(&[]);
+        let plain = pdf(&document).unwrap();
+        assert_eq!(plain, export(&document, PdfStandard::V1_7).unwrap());
+        assert!(!contains(&plain, "/ID"));
+
+        let archival = export(&document, PdfStandard::A2b).unwrap();
+        assert!(contains(&archival, "/ID ["));
+        assert_eq!(archival, export(&document, PdfStandard::A2b).unwrap());
+    }
+
+    #[test]
+    fn test_pdf_custom_metadata() {
+        let mut document = document(&[]);
+        document.custom = vec![("Department".into(), "R&D".into())];
+
+        let plain = export(&document, PdfStandard::V1_7).unwrap();
+        assert!(contains(&plain, "/Department (R&D)"));
+        assert!(contains(&plain, "<custom:Department>R&amp;D</custom:Department>"));
+
+        // PDF/A leaves custom metadata out everywhere.
+        let archival = export(&document, PdfStandard::A2b).unwrap();
+        assert!(!contains(&archival, "Department"));
+        assert!(contains(&archival, "<pdfaid:part>2</pdfaid:part>"));
+    }
+
+    #[test]
+    fn test_pdf_print_boxes() {
+        let mut document = document(&[]);
+        let boxes = PrintBoxes { bleed: Abs::pt(2.0), marks: true };
+        let meta = FrameItem::Meta(Meta::PrintBoxes(boxes), Size::zero());
+        document.pages[0].push(Point::zero(), meta);
+
+        let pdf = pdf(&document).unwrap();
+        assert!(contains(&pdf, "/MediaBox [-20 -20 30 30]"));
+        assert!(contains(&pdf, "/BleedBox [-2 -2 12 12]"));
+        assert!(contains(&pdf, "/TrimBox [0 0 10 10]"));
+        assert!(contains(&pdf, "/Separation /All /DeviceGray"));
+    }
+
+    #[test]
+    fn test_pdf_embedded_files_are_deduplicated() {
+        let file = |data: &'static [u8], annotate| EmbeddedFile {
+            name: "data.csv".into(),
+            data: Bytes::from_static(data),
+            description: None,
+            mime_type: None,
+            annotate,
+            span: Span::detached(),
+        };
+
+        // The same file is embedded once, even if only one of its uses is
+        // annotated. Another file with the same name is embedded separately.
+        let mut document = document(&[]);
+        for file in [file(b"1,2", false), file(b"1,2", true), file(b"3,4", false)] {
+            let meta = FrameItem::Meta(Meta::File(file), Size::zero());
+            document.pages[0].push(Point::zero(), meta);
+        }
+
+        let pdf = pdf(&document).unwrap();
+        assert_eq!(pdf.windows(8).filter(|w| w == b"Filespec").count(), 2);
+        assert_eq!(pdf.windows(14).filter(|w| w == b"FileAttachment").count(), 1);
+    }
+}
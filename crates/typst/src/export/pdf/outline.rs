@@ -163,8 +163,8 @@ fn write_outline_item(
 
     let loc = node.element.location().unwrap();
     let pos = ctx.introspector.position(loc);
-    let index = pos.page.get() - 1;
-    if let Some(&height) = ctx.page_heights.get(index) {
+    if let Some(index) = ctx.page_index(pos.page) {
+        let height = ctx.page_heights[index];
         let y = (pos.point.y - Abs::pt(10.0)).max(Abs::zero());
         outline.dest().page(ctx.page_refs[index]).xyz(
             pos.point.x.to_f32(),
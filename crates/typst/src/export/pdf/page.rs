@@ -1,24 +1,49 @@
-use ecow::eco_format;
+use ecow::{eco_format, EcoString};
 use pdf_writer::types::{
     ActionType, AnnotationType, ColorSpaceOperand, LineCapStyle, LineJoinStyle,
 };
-use pdf_writer::writers::ColorSpace;
-use pdf_writer::{Content, Filter, Finish, Name, Rect, Ref, Str};
+use pdf_writer::writers::{Annotation, ColorSpace, Resources};
+use pdf_writer::{Content, Filter, Finish, Name, Rect, Ref, Str, TextStr};
 
+use super::embed::PdfFile;
+use super::form::{self, PdfField};
+use super::gradient::PdfGradient;
+use super::pattern::{flip_y, PdfPattern};
 use super::{deflate, AbsExt, EmExt, PdfContext, RefExt, D65_GRAY, SRGB};
-use crate::doc::{Destination, Frame, FrameItem, GroupItem, Meta, TextItem};
+use crate::doc::{
+    Destination, EmbeddedFile, FormField, FormFieldKind, Frame, FrameItem, Glyph,
+    GroupItem, Meta, PrintBoxes, TextItem,
+};
+use crate::eval::Value;
+use crate::export::glyph::{
+    color_glyph, colr_layers, glyph_image, is_color_glyph, ColorGlyph,
+};
 use crate::font::Font;
 use crate::geom::{
-    self, Abs, Color, Em, Geometry, LineCap, LineJoin, Numeric, Paint, Point, Ratio,
-    Shape, Size, Stroke, Transform,
+    self, Abs, Color, Em, Geometry, Gradient, LineCap, LineJoin, Numeric, Paint, Pattern,
+    Point, Ratio, Shape, Size, SpotAlternate, SpotColor, Stroke, Transform,
 };
 use crate::image::Image;
+use crate::syntax::Span;
+
+/// The name of the registration color's separation color space.
+const REGISTRATION: Name<'static> = Name(b"reg");
 
 /// Construct page objects.
 #[tracing::instrument(skip_all)]
 pub fn construct_pages(ctx: &mut PdfContext, frames: &[Frame]) {
-    for frame in frames {
-        construct_page(ctx, frame);
+    for (i, frame) in frames.iter().enumerate() {
+        let exported = match &ctx.options.pages {
+            Some(pages) => pages.binary_search(&i).is_ok(),
+            None => true,
+        };
+
+        if exported {
+            ctx.page_indices.push(Some(ctx.page_refs.len()));
+            construct_page(ctx, frame);
+        } else {
+            ctx.page_indices.push(None);
+        }
     }
 }
 
@@ -28,59 +53,152 @@ pub fn construct_page(ctx: &mut PdfContext, frame: &Frame) {
     let page_ref = ctx.alloc.bump();
     ctx.page_refs.push(page_ref);
     ctx.page_heights.push(frame.height().to_f32());
+    ctx.page_labels.push(PdfPageLabel::of_frame(frame));
+    let index = ctx.structure.add_page();
 
     let mut ctx = PageContext {
         parent: ctx,
-        page_ref,
         content: Content::new(),
         state: State::default(),
         saves: vec![],
         bottom: 0.0,
         links: vec![],
+        files: vec![],
+        fields: vec![],
+        page: Some(index),
+        markers: vec![],
     };
 
     let size = frame.size();
+    let boxes = frame.items().find_map(|(_, item)| match item {
+        FrameItem::Meta(Meta::PrintBoxes(boxes), _) => Some(*boxes),
+        _ => None,
+    });
 
     // Make the coordinate system start at the top-left.
     ctx.bottom = size.y.to_f32();
-    ctx.transform(Transform {
-        sx: Ratio::one(),
-        ky: Ratio::zero(),
-        kx: Ratio::zero(),
-        sy: Ratio::new(-1.0),
-        tx: Abs::zero(),
-        ty: size.y,
-    });
+    ctx.transform(flip_y(size.y));
 
     // Encode the page into the content stream.
     write_frame(&mut ctx, frame);
 
+    // The printer's marks lie outside of the page's frame.
+    if let Some(PrintBoxes { bleed, marks: true }) = boxes {
+        write_marks(&mut ctx, size, bleed);
+    }
+
     let page = Page {
         size,
+        boxes,
         content: ctx.content,
-        id: ctx.page_ref,
+        id: page_ref,
         links: ctx.links,
+        files: ctx.files,
+        fields: ctx.fields,
     };
 
     ctx.parent.pages.push(page);
 }
 
+/// Construct the content stream of a pattern's tile.
+///
+/// Returns the deflated content stream. Links within the tile are ignored and
+/// its content is not part of the document's structure.
+#[tracing::instrument(skip_all)]
+fn construct_tile(ctx: &mut PdfContext, pattern: &Pattern) -> Vec<u8> {
+    construct_detached(ctx, pattern.frame())
+}
+
+/// Construct a content stream for a frame outside of any page, like a tile or
+/// the appearance of a form field.
+///
+/// Returns the deflated content stream.
+fn construct_detached(ctx: &mut PdfContext, frame: &Frame) -> Vec<u8> {
+    let mut ctx = PageContext {
+        parent: ctx,
+        content: Content::new(),
+        state: State::default(),
+        saves: vec![],
+        bottom: 0.0,
+        links: vec![],
+        files: vec![],
+        fields: vec![],
+        page: None,
+        markers: vec![],
+    };
+
+    // Like pages, the content is written with its origin at the top-left.
+    let size = frame.size();
+    ctx.bottom = size.y.to_f32();
+    ctx.transform(flip_y(size.y));
+    write_frame(&mut ctx, frame);
+
+    deflate(&ctx.content.finish())
+}
+
 /// Write the page tree.
 #[tracing::instrument(skip_all)]
 pub fn write_page_tree(ctx: &mut PdfContext) {
-    for page in std::mem::take(&mut ctx.pages).into_iter() {
-        write_page(ctx, page);
+    let marks = ctx.pages.iter().any(|page| page.boxes.is_some_and(|b| b.marks));
+    for (i, page) in std::mem::take(&mut ctx.pages).into_iter().enumerate() {
+        write_page(ctx, i, page);
     }
 
     let mut pages = ctx.writer.pages(ctx.page_tree_ref);
     pages
         .count(ctx.page_refs.len() as i32)
         .kids(ctx.page_refs.iter().copied());
+    pages.pair(Name(b"Resources"), ctx.global_resources_ref);
+    pages.finish();
 
-    let mut resources = pages.resources();
+    // The resources are shared between the pages and the patterns' tiles.
+    let mut resources =
+        ctx.writer.indirect(ctx.global_resources_ref).start::<Resources>();
     let mut spaces = resources.color_spaces();
     spaces.insert(SRGB).start::<ColorSpace>().srgb();
     spaces.insert(D65_GRAY).start::<ColorSpace>().d65_gray();
+
+    // The registration color appears on every separation. Its alternate is
+    // black, which is also fine with a PDF/A output intent.
+    if marks {
+        let mut space = spaces.insert(REGISTRATION).array();
+        space.item(Name(b"Separation"));
+        space.item(Name(b"All"));
+        space.item(Name(b"DeviceGray"));
+        let mut tint = space.push().dict();
+        tint.pair(Name(b"FunctionType"), 2);
+        tint.insert(Name(b"Domain")).array().items([0.0_f32, 1.0]);
+        tint.insert(Name(b"C0")).array().item(1.0_f32);
+        tint.insert(Name(b"C1")).array().item(0.0_f32);
+        tint.pair(Name(b"N"), 1.0_f32);
+    }
+
+    // Spot colors are separations whose tints are approximated by scaling
+    // the alternate color.
+    for (i, ink) in ctx.spot_map.items().enumerate() {
+        let name = eco_format!("Sp{}", i);
+        let (alternate, c0, c1) = match ctx.spot_alternates[ink] {
+            SpotAlternate::Rgb(c) => {
+                (Name(b"DeviceRGB"), vec![1.0_f32; 3], vec![c.r, c.g, c.b])
+            }
+            SpotAlternate::Cmyk(c) => {
+                (Name(b"DeviceCMYK"), vec![0.0_f32; 4], vec![c.c, c.m, c.y, c.k])
+            }
+        };
+
+        let mut space = spaces.insert(Name(name.as_bytes())).array();
+        space.item(Name(b"Separation"));
+        space.item(Name(ink.as_bytes()));
+        space.item(alternate);
+        let mut tint = space.push().dict();
+        tint.pair(Name(b"FunctionType"), 2);
+        tint.insert(Name(b"Domain")).array().items([0.0_f32, 1.0]);
+        tint.insert(Name(b"C0")).array().items(c0);
+        tint.insert(Name(b"C1"))
+            .array()
+            .items(c1.into_iter().map(|v| v as f32 / 255.0));
+        tint.pair(Name(b"N"), 1.0_f32);
+    }
     spaces.finish();
 
     let mut fonts = resources.fonts();
@@ -98,28 +216,77 @@ pub fn write_page_tree(ctx: &mut PdfContext) {
     }
 
     images.finish();
+
+    let mut patterns = resources.patterns();
+    for (gradient_ref, gr) in ctx.gradient_map.pdf_indices(&ctx.gradient_refs) {
+        let name = eco_format!("Gr{}", gr);
+        patterns.pair(Name(name.as_bytes()), gradient_ref);
+    }
+
+    for (pattern_ref, p) in ctx.pattern_map.pdf_indices(&ctx.pattern_refs) {
+        let name = eco_format!("P{}", p);
+        patterns.pair(Name(name.as_bytes()), pattern_ref);
+    }
+
+    patterns.finish();
     resources.finish();
-    pages.finish();
 }
 
 /// Write a page tree node.
 #[tracing::instrument(skip_all)]
-fn write_page(ctx: &mut PdfContext, page: Page) {
+fn write_page(ctx: &mut PdfContext, index: usize, page: Page) {
     let content_id = ctx.alloc.bump();
+    let annotation_count = page.links.len() + page.files.len() + page.fields.len();
+    let annotation_ids: Vec<Ref> =
+        (0..annotation_count).map(|_| ctx.alloc.bump()).collect();
+    let (link_ids, rest) = annotation_ids.split_at(page.links.len());
+    let (file_ids, field_ids) = rest.split_at(page.files.len());
 
     let mut page_writer = ctx.writer.page(page.id);
     page_writer.parent(ctx.page_tree_ref);
 
+    // The page's frame is the trim box. The bleed and the printer's marks
+    // extend the media box beyond it.
     let w = page.size.x.to_f32();
     let h = page.size.y.to_f32();
-    page_writer.media_box(Rect::new(0.0, 0.0, w, h));
+    let outset =
+        |d: Abs| Rect::new(-d.to_f32(), -d.to_f32(), w + d.to_f32(), h + d.to_f32());
+    match page.boxes {
+        Some(PrintBoxes { bleed, marks }) => {
+            let slug = if marks { MARK_OFFSET + MARK_LENGTH } else { Abs::zero() };
+            page_writer.media_box(outset(bleed + slug));
+            page_writer.bleed_box(outset(bleed));
+            page_writer.trim_box(outset(Abs::zero()));
+        }
+        None => {
+            page_writer.media_box(outset(Abs::zero()));
+        }
+    }
+
     page_writer.contents(content_id);
 
-    let mut annotations = page_writer.annotations();
-    for (dest, rect) in page.links {
-        let mut annotation = annotations.push();
+    // Link the page to its marked content in the structure tree and make
+    // the tab order follow the structure.
+    page_writer.pair(Name(b"StructParents"), index as i32);
+    page_writer.pair(Name(b"Tabs"), Name(b"S"));
+    page_writer
+        .insert(Name(b"Annots"))
+        .array()
+        .items(annotation_ids.iter().copied());
+    page_writer.finish();
+
+    // Link annotations are written as indirect objects so that the structure
+    // tree can refer to them.
+    for ((dest, rect, node), &id) in page.links.into_iter().zip(link_ids) {
+        let mut annotation = ctx.writer.indirect(id).start::<Annotation>();
         annotation.subtype(AnnotationType::Link).rect(rect);
         annotation.border(0.0, 0.0, 0.0, None);
+        annotation.pair(Name(b"F"), 4);
+
+        if let Some(node) = node {
+            let key = ctx.structure.annotate(node, index, id);
+            annotation.pair(Name(b"StructParent"), key as i32);
+        }
 
         let pos = match dest {
             Destination::Url(uri) => {
@@ -129,13 +296,22 @@ fn write_page(ctx: &mut PdfContext, page: Page) {
                     .uri(Str(uri.as_bytes()));
                 continue;
             }
+            Destination::Remote(dest) => {
+                let mut action = annotation.insert(Name(b"A")).dict();
+                action.pair(Name(b"Type"), Name(b"Action"));
+                action.pair(Name(b"S"), Name(b"GoToR"));
+                action.pair(Name(b"F"), Str(dest.file.as_bytes()));
+                action.pair(Name(b"D"), Str(dest.name.as_bytes()));
+                continue;
+            }
             Destination::Position(pos) => pos,
             Destination::Location(loc) => ctx.introspector.position(loc),
         };
 
-        let index = pos.page.get() - 1;
+        // Links to pages that aren't exported have no action.
         let y = (pos.point.y - Abs::pt(10.0)).max(Abs::zero());
-        if let Some(&height) = ctx.page_heights.get(index) {
+        if let Some(index) = ctx.page_index(pos.page) {
+            let height = ctx.page_heights[index];
             annotation
                 .action()
                 .action_type(ActionType::GoTo)
@@ -145,8 +321,22 @@ fn write_page(ctx: &mut PdfContext, page: Page) {
         }
     }
 
-    annotations.finish();
-    page_writer.finish();
+    // File attachment annotations show an icon that opens the file.
+    for ((index, rect), &id) in page.files.into_iter().zip(file_ids) {
+        let PdfFile(file) = &ctx.file_map.to_items[index];
+        let mut annotation = ctx.writer.indirect(id).start::<Annotation>();
+        annotation.pair(Name(b"Subtype"), Name(b"FileAttachment"));
+        annotation.rect(rect);
+        annotation.pair(Name(b"F"), 4);
+        annotation.pair(Name(b"FS"), ctx.file_refs[index]);
+        annotation.pair(Name(b"Name"), Name(b"Paperclip"));
+        let contents = file.description.as_ref().unwrap_or(&file.name);
+        annotation.pair(Name(b"Contents"), TextStr(contents));
+    }
+
+    for (field, &id) in page.fields.into_iter().zip(field_ids) {
+        form::write_widget(ctx, id, page.id, field);
+    }
 
     let data = page.content.finish();
     let data = deflate(&data);
@@ -159,21 +349,194 @@ pub struct Page {
     pub id: Ref,
     /// The page's dimensions.
     pub size: Size,
+    /// Where the page is trimmed after printing, if that was specified.
+    pub boxes: Option<PrintBoxes>,
     /// The page's content stream.
     pub content: Content,
-    /// Links in the PDF coordinate system.
-    pub links: Vec<(Destination, Rect)>,
+    /// Links in the PDF coordinate system along with the structure elements
+    /// they belong to.
+    pub links: Vec<(Destination, Rect, Option<usize>)>,
+    /// File attachment annotations in the PDF coordinate system, with the
+    /// indices of their files.
+    pub files: Vec<(usize, Rect)>,
+    /// Form fields along with their appearances.
+    pub fields: Vec<PdfField>,
+}
+
+/// A page label, which viewers show instead of the physical page number.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct PdfPageLabel {
+    /// The text in front of the number.
+    prefix: EcoString,
+    /// How the number is displayed, `None` if the label has no number.
+    style: Option<PdfPageLabelStyle>,
+    /// The logical page number.
+    number: usize,
+}
+
+/// A numbering style for page labels.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+enum PdfPageLabelStyle {
+    Arabic,
+    LowerRoman,
+    UpperRoman,
+    LowerAlpha,
+    UpperAlpha,
+}
+
+impl PdfPageLabel {
+    /// Create the label for a page from the numbering metadata in its frame.
+    ///
+    /// Returns `None` if the page isn't numbered.
+    fn of_frame(frame: &Frame) -> Option<Self> {
+        let mut numbering = None;
+        let mut number = None;
+        for (_, item) in frame.items() {
+            match item {
+                FrameItem::Meta(Meta::PageNumbering(value), _) => numbering = Some(value),
+                FrameItem::Meta(Meta::PageNumber(n), _) => number = Some(*n),
+                _ => {}
+            }
+        }
+
+        Self::new(numbering?, number?)
+    }
+
+    /// Create a label from a page's numbering and its logical number.
+    ///
+    /// PDF labels consist of a prefix and a number in one of a few styles.
+    /// Patterns with other counting symbols fall back to arabic numbers and
+    /// their suffix is dropped. Numbering functions are labelled with plain
+    /// arabic numbers.
+    fn new(numbering: &Value, number: usize) -> Option<Self> {
+        let (prefix, style) = match numbering {
+            Value::None => return None,
+            Value::Str(pattern) => {
+                let pattern = pattern.as_str();
+                match pattern.char_indices().find(|&(_, c)| is_counting_symbol(c)) {
+                    Some((i, c)) => (pattern[..i].into(), PdfPageLabelStyle::of(c)),
+                    None => (EcoString::new(), PdfPageLabelStyle::Arabic),
+                }
+            }
+            _ => (EcoString::new(), PdfPageLabelStyle::Arabic),
+        };
+
+        // Label numbers start at one, so a page numbered zero only gets the
+        // prefix.
+        let style = (number > 0).then_some(style);
+        Some(Self { prefix, style, number })
+    }
+}
+
+impl PdfPageLabelStyle {
+    /// The style for a counting symbol of a numbering pattern.
+    fn of(c: char) -> Self {
+        match c {
+            'i' => Self::LowerRoman,
+            'I' => Self::UpperRoman,
+            'a' => Self::LowerAlpha,
+            'A' => Self::UpperAlpha,
+            _ => Self::Arabic,
+        }
+    }
+
+    /// The name of the style in a page label dictionary.
+    fn name(self) -> Name<'static> {
+        Name(match self {
+            Self::Arabic => b"D",
+            Self::LowerRoman => b"r",
+            Self::UpperRoman => b"R",
+            Self::LowerAlpha => b"a",
+            Self::UpperAlpha => b"A",
+        })
+    }
+}
+
+/// Whether a character is a counting symbol in a numbering pattern.
+fn is_counting_symbol(c: char) -> bool {
+    matches!(
+        c.to_ascii_lowercase(),
+        '1' | 'a' | 'i' | '*' | 'א' | '一' | '壹' | 'い' | 'イ' | 'ㄱ' | '가'
+    )
+}
+
+/// Write the page labels as a number tree and return its reference.
+#[tracing::instrument(skip_all)]
+pub fn write_page_labels(ctx: &mut PdfContext) -> Option<Ref> {
+    if ctx.page_labels.iter().all(Option::is_none) {
+        return None;
+    }
+
+    let tree_ref = ctx.alloc.bump();
+    let mut tree = ctx.writer.indirect(tree_ref).dict();
+    let mut nums = tree.insert(Name(b"Nums")).array();
+
+    for (i, label) in label_ranges(&ctx.page_labels) {
+        nums.item(i as i32);
+        let mut dict = nums.push().dict();
+        dict.pair(Name(b"Type"), Name(b"PageLabel"));
+        if !label.prefix.is_empty() {
+            dict.pair(Name(b"P"), TextStr(&label.prefix));
+        }
+        if let Some(style) = label.style {
+            dict.pair(Name(b"S"), style.name());
+            dict.pair(Name(b"St"), label.number as i32);
+        }
+        dict.finish();
+    }
+
+    nums.finish();
+    tree.finish();
+
+    Some(tree_ref)
+}
+
+/// Group the pages into ranges that share one entry in the page label tree,
+/// each starting at the index of its first page.
+///
+/// Consecutive pages whose labels continue each other form one range. Pages
+/// without numbering get ranges without a style, so that viewers show no
+/// label for them. As the tree must have an entry for the first page, the
+/// first range always starts there.
+fn label_ranges(labels: &[Option<PdfPageLabel>]) -> Vec<(usize, PdfPageLabel)> {
+    let mut ranges: Vec<(usize, PdfPageLabel)> = vec![];
+    for (i, label) in labels.iter().enumerate() {
+        let label = label.clone().unwrap_or(PdfPageLabel {
+            prefix: EcoString::new(),
+            style: None,
+            number: 0,
+        });
+
+        if let Some((j, prev)) = ranges.last() {
+            if label.prefix == prev.prefix
+                && label.style == prev.style
+                && (label.style.is_none() || label.number == prev.number + (i - j))
+            {
+                continue;
+            }
+        }
+
+        ranges.push((i, label));
+    }
+    ranges
 }
 
 /// An exporter for the contents of a single PDF page.
 struct PageContext<'a, 'b> {
     parent: &'a mut PdfContext<'b>,
-    page_ref: Ref,
     content: Content,
     state: State,
     saves: Vec<State>,
     bottom: f32,
-    links: Vec<(Destination, Rect)>,
+    links: Vec<(Destination, Rect, Option<usize>)>,
+    files: Vec<(usize, Rect)>,
+    fields: Vec<PdfField>,
+    /// The index of the page in the structure tree. `None` if the content is
+    /// not tagged, like the content of a pattern's tile.
+    page: Option<usize>,
+    /// The elements and links the current content belongs to, from the
+    /// outermost to the innermost one.
+    markers: Vec<Meta>,
 }
 
 /// A simulated graphics state used to deduplicate graphics state changes and
@@ -221,23 +584,85 @@ impl PageContext<'_, '_> {
         }
     }
 
-    fn set_fill(&mut self, fill: &Paint) {
-        if self.state.fill.as_ref() != Some(fill) {
+    /// Check that a paint can be used in the document and with the PDF/A
+    /// output intent.
+    fn check_paint(&mut self, paint: &Paint, span: Span) {
+        // An ink can only have one separation color space.
+        if let Paint::Solid(Color::Spot(spot)) = paint {
+            let alternates = &mut self.parent.spot_alternates;
+            let alternate =
+                *alternates.entry(spot.name.clone()).or_insert(spot.alternate);
+            if alternate != spot.alternate {
+                let message = eco_format!(
+                    "spot color `{}` is used with different alternates",
+                    spot.name
+                );
+                self.parent.error(
+                    span,
+                    &message,
+                    "all tints of an ink must use the same alternate color",
+                );
+            }
+        }
+
+        if !self.parent.is_pdfa() {
+            return;
+        }
+
+        match paint {
+            Paint::Solid(Color::Cmyk(_)) => self.parent.error(
+                span,
+                "PDF/A export does not support CMYK colors",
+                "PDF/A documents use an sRGB output intent, try an RGB color instead",
+            ),
+            Paint::Solid(Color::Spot(SpotColor {
+                alternate: SpotAlternate::Cmyk(_),
+                ..
+            })) => self.parent.error(
+                span,
+                "PDF/A export does not support spot colors with a CMYK alternate",
+                "PDF/A documents use an sRGB output intent, try an RGB alternate instead",
+            ),
+            _ => {}
+        }
+    }
+
+    fn set_fill(&mut self, fill: &Paint, bbox: (Point, Size)) {
+        if self.state.fill.as_ref() != Some(fill) || !matches!(fill, Paint::Solid(_)) {
             let f = |c| c as f32 / 255.0;
-            let Paint::Solid(color) = fill;
-            match color {
-                Color::Luma(c) => {
+            match fill {
+                Paint::Solid(Color::Luma(c)) => {
                     self.set_fill_color_space(D65_GRAY);
                     self.content.set_fill_gray(f(c.0));
                 }
-                Color::Rgba(c) => {
+                Paint::Solid(Color::Rgba(c)) => {
                     self.set_fill_color_space(SRGB);
                     self.content.set_fill_color([f(c.r), f(c.g), f(c.b)]);
                 }
-                Color::Cmyk(c) => {
+                Paint::Solid(Color::Cmyk(c)) => {
                     self.reset_fill_color_space();
                     self.content.set_fill_cmyk(f(c.c), f(c.m), f(c.y), f(c.k));
                 }
+                Paint::Solid(Color::Spot(c)) => {
+                    let name = self.spot(c);
+                    self.reset_fill_color_space();
+                    self.content.set_fill_color_space(ColorSpaceOperand::Named(Name(
+                        name.as_bytes(),
+                    )));
+                    self.content.set_fill_color([f(c.tint)]);
+                }
+                Paint::Gradient(gradient) => {
+                    let name = self.gradient(gradient, bbox);
+                    self.reset_fill_color_space();
+                    self.content.set_fill_color_space(ColorSpaceOperand::Pattern);
+                    self.content.set_fill_pattern([], Name(name.as_bytes()));
+                }
+                Paint::Pattern(pattern) => {
+                    let name = self.pattern(pattern, bbox);
+                    self.reset_fill_color_space();
+                    self.content.set_fill_color_space(ColorSpaceOperand::Pattern);
+                    self.content.set_fill_pattern([], Name(name.as_bytes()));
+                }
             }
             self.state.fill = Some(fill.clone());
         }
@@ -254,8 +679,10 @@ impl PageContext<'_, '_> {
         self.state.fill_space = None;
     }
 
-    fn set_stroke(&mut self, stroke: &Stroke) {
-        if self.state.stroke.as_ref() != Some(stroke) {
+    fn set_stroke(&mut self, stroke: &Stroke, bbox: (Point, Size)) {
+        if self.state.stroke.as_ref() != Some(stroke)
+            || !matches!(stroke.paint, Paint::Solid(_))
+        {
             let Stroke {
                 paint,
                 thickness,
@@ -266,20 +693,39 @@ impl PageContext<'_, '_> {
             } = stroke;
 
             let f = |c| c as f32 / 255.0;
-            let Paint::Solid(color) = paint;
-            match color {
-                Color::Luma(c) => {
+            match paint {
+                Paint::Solid(Color::Luma(c)) => {
                     self.set_stroke_color_space(D65_GRAY);
                     self.content.set_stroke_gray(f(c.0));
                 }
-                Color::Rgba(c) => {
+                Paint::Solid(Color::Rgba(c)) => {
                     self.set_stroke_color_space(SRGB);
                     self.content.set_stroke_color([f(c.r), f(c.g), f(c.b)]);
                 }
-                Color::Cmyk(c) => {
+                Paint::Solid(Color::Cmyk(c)) => {
                     self.reset_stroke_color_space();
                     self.content.set_stroke_cmyk(f(c.c), f(c.m), f(c.y), f(c.k));
                 }
+                Paint::Solid(Color::Spot(c)) => {
+                    let name = self.spot(c);
+                    self.reset_stroke_color_space();
+                    self.content.set_stroke_color_space(ColorSpaceOperand::Named(Name(
+                        name.as_bytes(),
+                    )));
+                    self.content.set_stroke_color([f(c.tint)]);
+                }
+                Paint::Gradient(gradient) => {
+                    let name = self.gradient(gradient, bbox);
+                    self.reset_stroke_color_space();
+                    self.content.set_stroke_color_space(ColorSpaceOperand::Pattern);
+                    self.content.set_stroke_pattern([], Name(name.as_bytes()));
+                }
+                Paint::Pattern(pattern) => {
+                    let name = self.pattern(pattern, bbox);
+                    self.reset_stroke_color_space();
+                    self.content.set_stroke_color_space(ColorSpaceOperand::Pattern);
+                    self.content.set_stroke_pattern([], Name(name.as_bytes()));
+                }
             }
 
             self.content.set_line_width(thickness.to_f32());
@@ -316,26 +762,140 @@ impl PageContext<'_, '_> {
     fn reset_stroke_color_space(&mut self) {
         self.state.stroke_space = None;
     }
+
+    /// Start a marked-content sequence that ties the following content to its
+    /// element in the structure tree. Images are placed in a figure.
+    fn begin_tagged(&mut self, image: Option<&Image>) {
+        let Some(page) = self.page else { return };
+        let structure = &mut self.parent.structure;
+        let mut node = structure.resolve(&self.markers);
+        if let Some(image) = image {
+            node = structure.figure(node, image.alt());
+        }
+
+        let mcid = structure.mark(node, page);
+        let role = structure.role(node);
+        let mut marked = self
+            .content
+            .begin_marked_content_with_properties(Name(role.as_bytes()));
+        let mut properties = marked.properties();
+        properties.pair(Name(b"MCID"), mcid as i32);
+        if let Some(alt) = image.and_then(Image::alt) {
+            properties.pair(Name(b"Alt"), Str(alt.as_bytes()));
+        }
+        properties.finish();
+        marked.finish();
+    }
+
+    /// Start a marked-content sequence for content that is not part of the
+    /// document's structure, like decorative shapes.
+    fn begin_artifact(&mut self) {
+        if self.page.is_some() {
+            self.content.begin_marked_content(Name(b"Artifact"));
+        }
+    }
+
+    /// End a sequence started by `begin_tagged` or `begin_artifact`.
+    fn end_marked(&mut self) {
+        if self.page.is_some() {
+            self.content.end_marked_content();
+        }
+    }
+
+    /// Register a gradient that is applied to the given bounding box in the
+    /// current coordinate system and return the name of its pattern.
+    fn gradient(&mut self, gradient: &Gradient, (pos, size): (Point, Size)) -> EcoString {
+        // Avoid a degenerate pattern matrix for straight lines.
+        let size = size.map(|v| if v > Abs::zero() { v } else { Abs::pt(1.0) });
+        let mut transform =
+            self.state.transform.pre_concat(Transform::translate(pos.x, pos.y));
+        if !matches!(gradient, Gradient::Linear(_)) {
+            transform = transform.pre_concat(Transform::scale(
+                Ratio::new(size.x.to_pt()),
+                Ratio::new(size.y.to_pt()),
+            ));
+        }
+
+        let pdf_gradient = PdfGradient { gradient: gradient.clone(), transform, size };
+        self.parent.gradient_map.insert(pdf_gradient.clone());
+        eco_format!("Gr{}", self.parent.gradient_map.map(pdf_gradient))
+    }
+
+    /// The name of a spot color's separation color space in the resources.
+    fn spot(&mut self, spot: &SpotColor) -> EcoString {
+        // All tints of an ink share one color space. Its alternate was recorded
+        // when the paint was checked.
+        self.parent.spot_map.insert(spot.name.clone());
+        eco_format!("Sp{}", self.parent.spot_map.map(spot.name.clone()))
+    }
+
+    /// Register a pattern whose first tile starts at the top-left corner of the
+    /// given bounding box and return its name.
+    fn pattern(&mut self, pattern: &Pattern, (pos, _): (Point, Size)) -> EcoString {
+        if !self.parent.pattern_tiles.contains_key(pattern) {
+            let tile = construct_tile(self.parent, pattern);
+            self.parent.pattern_tiles.insert(pattern.clone(), tile);
+        }
+
+        let transform =
+            self.state.transform.pre_concat(Transform::translate(pos.x, pos.y));
+        let pdf_pattern = PdfPattern { pattern: pattern.clone(), transform };
+        self.parent.pattern_map.insert(pdf_pattern.clone());
+        eco_format!("P{}", self.parent.pattern_map.map(pdf_pattern))
+    }
 }
 
 /// Encode a frame into the content stream.
 fn write_frame(ctx: &mut PageContext, frame: &Frame) {
+    // Viewers draw form fields from the appearances of their widgets, so the
+    // field's content isn't part of the page.
+    //
+    // Frames also carry the full chain of elements and links their content
+    // belongs to, so they replace the chain of the outer frame.
+    let mut field = None;
+    let mut markers = vec![];
+    for (_, item) in frame.items() {
+        match item {
+            FrameItem::Meta(Meta::Field(f), _) => field = Some(f),
+            FrameItem::Meta(meta @ (Meta::Elem(_) | Meta::Link(_)), _) => {
+                markers.push(meta.clone())
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(field) = field.filter(|_| ctx.page.is_some()) {
+        write_field(ctx, frame, field);
+        return;
+    }
+
+    let outer =
+        (!markers.is_empty()).then(|| std::mem::replace(&mut ctx.markers, markers));
+
     for &(pos, ref item) in frame.items() {
         let x = pos.x.to_f32();
         let y = pos.y.to_f32();
         match item {
             FrameItem::Group(group) => write_group(ctx, pos, group),
-            FrameItem::Text(text) => write_text(ctx, x, y, text),
-            FrameItem::Shape(shape, _) => write_shape(ctx, x, y, shape),
+            FrameItem::Text(text) => write_text(ctx, pos, text),
+            FrameItem::Shape(shape, span) => write_shape(ctx, pos, shape, *span),
             FrameItem::Image(image, size, _) => write_image(ctx, x, y, image, *size),
             FrameItem::Meta(meta, size) => match meta {
                 Meta::Link(dest) => write_link(ctx, pos, dest, *size),
                 Meta::Elem(_) => {}
                 Meta::Hide => {}
                 Meta::PageNumbering(_) => {}
+                Meta::PageNumber(_) => {}
+                Meta::PrintBoxes(_) => {}
+                Meta::File(file) => write_file(ctx, pos, file),
+                Meta::Field(_) => {}
             },
         }
     }
+
+    if let Some(outer) = outer {
+        ctx.markers = outer;
+    }
 }
 
 /// Encode a group into the content stream.
@@ -362,62 +922,164 @@ fn write_group(ctx: &mut PageContext, pos: Point, group: &GroupItem) {
 }
 
 /// Encode a text run into the content stream.
-fn write_text(ctx: &mut PageContext, x: f32, y: f32, text: &TextItem) {
+fn write_text(ctx: &mut PageContext, pos: Point, text: &TextItem) {
+    let x = pos.x.to_f32();
+    let y = pos.y.to_f32();
     *ctx.parent.languages.entry(text.lang).or_insert(0) += text.glyphs.len();
 
-    let glyph_set = ctx.parent.glyph_sets.entry(text.font.clone()).or_default();
-    for g in &text.glyphs {
-        let segment = &text.text[g.range()];
-        glyph_set.entry(g.id).or_insert_with(|| segment.into());
+    // Color glyphs are drawn separately instead of being shown as text.
+    let colored: Vec<bool> =
+        text.glyphs.iter().map(|g| is_color_glyph(&text.font, g.id)).collect();
+    let has_plain = colored.iter().any(|&c| !c);
+
+    if has_plain {
+        let glyph_set = ctx.parent.glyph_sets.entry(text.font.clone()).or_default();
+        for (g, _) in text.glyphs.iter().zip(&colored).filter(|(_, &c)| !c) {
+            let segment = &text.text[g.range()];
+            glyph_set.entry(g.id).or_insert_with(|| segment.into());
+        }
+    }
+
+    let span = text.glyphs.first().map_or(Span::detached(), |g| g.span.0);
+    ctx.check_paint(&text.fill, span);
+    if ctx.parent.is_pdfa() {
+        if let Some(glyph) = text.glyphs.iter().find(|g| g.id == 0) {
+            ctx.parent.error(
+                glyph.span.0,
+                "the text contains glyphs that are missing from the font",
+                "PDF/A requires all glyphs to be embedded, try a different font",
+            );
+        }
+    }
+
+    let (origin, size) = text.bbox();
+    let bbox = (pos + origin, size);
+    if has_plain {
+        ctx.set_fill(&text.fill, bbox);
+        ctx.set_font(&text.font, text.size);
     }
 
-    ctx.set_fill(&text.fill);
-    ctx.set_font(&text.font, text.size);
-    ctx.content.begin_text();
+    ctx.begin_tagged(None);
+
+    if has_plain {
+        ctx.content.begin_text();
 
-    // Positiosn the text.
-    ctx.content.set_text_matrix([1.0, 0.0, 0.0, -1.0, x, y]);
+        // Positiosn the text.
+        ctx.content.set_text_matrix([1.0, 0.0, 0.0, -1.0, x, y]);
 
-    let mut positioned = ctx.content.show_positioned();
-    let mut items = positioned.items();
-    let mut adjustment = Em::zero();
-    let mut encoded = vec![];
+        let mut positioned = ctx.content.show_positioned();
+        let mut items = positioned.items();
+        let mut adjustment = Em::zero();
+        let mut encoded = vec![];
 
-    // Write the glyphs with kerning adjustments.
-    for glyph in &text.glyphs {
-        adjustment += glyph.x_offset;
+        // Write the glyphs with kerning adjustments.
+        for (glyph, &color) in text.glyphs.iter().zip(&colored) {
+            // Leave space for color glyphs.
+            if color {
+                adjustment += glyph.x_advance;
+                continue;
+            }
+
+            adjustment += glyph.x_offset;
+
+            if !adjustment.is_zero() {
+                if !encoded.is_empty() {
+                    items.show(Str(&encoded));
+                    encoded.clear();
+                }
 
-        if !adjustment.is_zero() {
-            if !encoded.is_empty() {
-                items.show(Str(&encoded));
-                encoded.clear();
+                items.adjust(-adjustment.to_font_units());
+                adjustment = Em::zero();
             }
 
-            items.adjust(-adjustment.to_font_units());
-            adjustment = Em::zero();
-        }
+            encoded.push((glyph.id >> 8) as u8);
+            encoded.push((glyph.id & 0xff) as u8);
 
-        encoded.push((glyph.id >> 8) as u8);
-        encoded.push((glyph.id & 0xff) as u8);
+            if let Some(advance) = text.font.advance(glyph.id) {
+                adjustment += glyph.x_advance - advance;
+            }
 
-        if let Some(advance) = text.font.advance(glyph.id) {
-            adjustment += glyph.x_advance - advance;
+            adjustment -= glyph.x_offset;
         }
 
-        adjustment -= glyph.x_offset;
+        if !encoded.is_empty() {
+            items.show(Str(&encoded));
+        }
+
+        items.finish();
+        positioned.finish();
+        ctx.content.end_text();
     }
 
-    if !encoded.is_empty() {
-        items.show(Str(&encoded));
+    let mut offset = Abs::zero();
+    for (glyph, &color) in text.glyphs.iter().zip(&colored) {
+        if color {
+            let at = pos + Point::with_x(offset + glyph.x_offset.at(text.size));
+            write_color_glyph(ctx, at, text, glyph, bbox);
+        }
+        offset += glyph.x_advance.at(text.size);
     }
 
-    items.finish();
-    positioned.finish();
-    ctx.content.end_text();
+    ctx.end_marked();
+}
+
+/// Draw a color glyph whose origin is at the given position.
+///
+/// The glyph is marked with its text so that it can be copied like the rest
+/// of the text run, which has the given bounding box.
+fn write_color_glyph(
+    ctx: &mut PageContext,
+    pos: Point,
+    text: &TextItem,
+    glyph: &Glyph,
+    (bbox_pos, bbox_size): (Point, Size),
+) {
+    let segment = &text.text[glyph.range()];
+    let mut marked = ctx.content.begin_marked_content_with_properties(Name(b"Span"));
+    marked.properties().pair(Name(b"ActualText"), TextStr(segment));
+    marked.finish();
+
+    // The glyph is drawn in font units.
+    let scale = text.size.to_pt() / text.font.units_per_em();
+    ctx.save_state();
+    ctx.transform(
+        Transform::translate(pos.x, pos.y)
+            .pre_concat(Transform::scale(Ratio::new(scale), Ratio::new(scale))),
+    );
+
+    match color_glyph(&text.font, glyph.id) {
+        Some(ColorGlyph::Colr) => {
+            let layers = colr_layers(&text.font, glyph.id).unwrap_or_default();
+
+            // The text's fill is positioned relative to the whole run.
+            let bbox = ((bbox_pos - pos) / scale, bbox_size / scale);
+            for layer in layers.iter() {
+                let fill = match layer.color {
+                    Some(color) => Paint::Solid(Color::Rgba(color)),
+                    None => text.fill.clone(),
+                };
+
+                ctx.set_fill(&fill, bbox);
+                write_path(ctx, 0.0, 0.0, &layer.path);
+                ctx.content.fill_nonzero();
+            }
+        }
+        Some(ColorGlyph::Svg | ColorGlyph::Bitmap) => {
+            if let Some((image, at, size)) = glyph_image(&text.font, glyph.id) {
+                draw_image(ctx, at.x.to_f32(), at.y.to_f32(), &image, size);
+            }
+        }
+        None => {}
+    }
+
+    ctx.restore_state();
+    ctx.content.end_marked_content();
 }
 
 /// Encode a geometrical shape into the content stream.
-fn write_shape(ctx: &mut PageContext, x: f32, y: f32, shape: &Shape) {
+fn write_shape(ctx: &mut PageContext, pos: Point, shape: &Shape, span: Span) {
+    let x = pos.x.to_f32();
+    let y = pos.y.to_f32();
     let stroke = shape.stroke.as_ref().and_then(|stroke| {
         if stroke.thickness.to_f32() > 0.0 {
             Some(stroke)
@@ -431,13 +1093,25 @@ fn write_shape(ctx: &mut PageContext, x: f32, y: f32, shape: &Shape) {
     }
 
     if let Some(fill) = &shape.fill {
-        ctx.set_fill(fill);
+        ctx.check_paint(fill, span);
+    }
+    if let Some(stroke) = stroke {
+        ctx.check_paint(&stroke.paint, span);
+    }
+
+    let (origin, size) = shape.geometry.bbox();
+    let bbox = (pos + origin, size);
+
+    if let Some(fill) = &shape.fill {
+        ctx.set_fill(fill, bbox);
     }
 
     if let Some(stroke) = stroke {
-        ctx.set_stroke(stroke);
+        ctx.set_stroke(stroke, bbox);
     }
 
+    ctx.begin_artifact();
+
     match shape.geometry {
         Geometry::Line(target) => {
             let dx = target.x.to_f32();
@@ -463,6 +1137,64 @@ fn write_shape(ctx: &mut PageContext, x: f32, y: f32, shape: &Shape) {
         (None, Some(_)) => ctx.content.stroke(),
         (Some(_), Some(_)) => ctx.content.fill_nonzero_and_stroke(),
     };
+
+    ctx.end_marked();
+}
+
+/// The distance of the printer's marks from the bleed.
+const MARK_OFFSET: Abs = Abs::raw(3.0);
+
+/// The length of the printer's marks.
+const MARK_LENGTH: Abs = Abs::raw(15.0);
+
+/// Draw crop marks at the corners of the trimmed page and registration marks
+/// at the centers of its sides, in registration color.
+fn write_marks(ctx: &mut PageContext, size: Size, bleed: Abs) {
+    let w = size.x.to_f32();
+    let h = size.y.to_f32();
+    let inner = (bleed + MARK_OFFSET).to_f32();
+    let outer = (bleed + MARK_OFFSET + MARK_LENGTH).to_f32();
+
+    ctx.begin_artifact();
+    ctx.content.save_state();
+    ctx.content
+        .set_stroke_color_space(ColorSpaceOperand::Named(REGISTRATION));
+    ctx.content.set_stroke_color([1.0]);
+    ctx.content.set_line_width(0.25);
+
+    // The crop marks extend the edges of the trimmed page.
+    for x in [0.0, w] {
+        ctx.content.move_to(x, -outer).line_to(x, -inner);
+        ctx.content.move_to(x, h + inner).line_to(x, h + outer);
+    }
+    for y in [0.0, h] {
+        ctx.content.move_to(-outer, y).line_to(-inner, y);
+        ctx.content.move_to(w + inner, y).line_to(w + outer, y);
+    }
+
+    // The registration marks are crosshairs in a circle.
+    let half = MARK_LENGTH.to_f32() / 2.0;
+    let radius = MARK_LENGTH / 3.0;
+    let circle = geom::ellipse(Size::splat(radius * 2.0), None, None);
+    let centers = [
+        (w / 2.0, -outer + half),
+        (w / 2.0, h + outer - half),
+        (-outer + half, h / 2.0),
+        (w + outer - half, h / 2.0),
+    ];
+
+    for (x, y) in centers {
+        ctx.content.move_to(x - half, y).line_to(x + half, y);
+        ctx.content.move_to(x, y - half).line_to(x, y + half);
+        if let Geometry::Path(path) = &circle.geometry {
+            let r = radius.to_f32();
+            write_path(ctx, x - r, y - r, path);
+        }
+    }
+
+    ctx.content.stroke();
+    ctx.content.restore_state();
+    ctx.end_marked();
 }
 
 /// Encode a bezier path into the content stream.
@@ -490,38 +1222,120 @@ fn write_path(ctx: &mut PageContext, x: f32, y: f32, path: &geom::Path) {
 
 /// Encode a vector or raster image into the content stream.
 fn write_image(ctx: &mut PageContext, x: f32, y: f32, image: &Image, size: Size) {
+    ctx.begin_tagged(Some(image));
+    draw_image(ctx, x, y, image, size);
+    ctx.end_marked();
+}
+
+/// Draw an image without tying it to the document's structure.
+fn draw_image(ctx: &mut PageContext, x: f32, y: f32, image: &Image, size: Size) {
+    // Remember the size at which the image ends up on the page, taking scaling
+    // into account, to determine the resolution it needs.
+    let Transform { sx, ky, kx, sy, .. } = ctx.state.transform;
+    let extent =
+        Size::new(size.x * sx.get().hypot(ky.get()), size.y * kx.get().hypot(sy.get()));
+    ctx.parent
+        .image_extents
+        .entry(image.clone())
+        .and_modify(|max| *max = max.max(extent))
+        .or_insert(extent);
+
     ctx.parent.image_map.insert(image.clone());
     let name = eco_format!("Im{}", ctx.parent.image_map.map(image.clone()));
     let w = size.x.to_f32();
     let h = size.y.to_f32();
     ctx.content.save_state();
     ctx.content.transform([w, 0.0, 0.0, -h, x, y + h]);
+    ctx.content.x_object(Name(name.as_bytes()));
+    ctx.content.restore_state();
+}
 
-    if let Some(alt) = image.alt() {
-        let mut image_span =
-            ctx.content.begin_marked_content_with_properties(Name(b"Span"));
-        let mut image_alt = image_span.properties();
-        image_alt.pair(Name(b"Alt"), pdf_writer::Str(alt.as_bytes()));
-        image_alt.finish();
-        image_span.finish();
+/// Save a link for later writing in the annotations dictionary.
+fn write_link(ctx: &mut PageContext, pos: Point, dest: &Destination, size: Size) {
+    let rect = annotation_rect(ctx, pos, size);
+    let node = ctx.page.map(|_| ctx.parent.structure.resolve(&ctx.markers));
+    ctx.links.push((dest.clone(), rect, node));
+}
 
-        ctx.content.x_object(Name(name.as_bytes()));
-        ctx.content.end_marked_content();
-    } else {
-        ctx.content.x_object(Name(name.as_bytes()));
+/// Save an embedded file and its annotation, if any, for later writing.
+fn write_file(ctx: &mut PageContext, pos: Point, file: &EmbeddedFile) {
+    // Files in the tiles of patterns would be embedded once per pattern
+    // instead of once per use, so they are ignored.
+    if ctx.page.is_none() {
+        return;
     }
 
-    ctx.content.restore_state();
+    // PDF/A-2 only allows embedding files that conform to PDF/A themselves.
+    if ctx.parent.is_pdfa() {
+        ctx.parent.error(
+            file.span,
+            "PDF/A-2 does not allow embedding arbitrary files",
+            "remove the file or export without a PDF standard",
+        );
+        return;
+    }
+
+    let pdf_file = PdfFile(file.clone());
+    ctx.parent.file_map.insert(pdf_file.clone());
+    if file.annotate {
+        // Viewers draw the annotation as an icon of a fixed size.
+        let index = ctx.parent.file_map.map(pdf_file);
+        let rect = annotation_rect(ctx, pos, Size::splat(Abs::pt(16.0)));
+        ctx.files.push((index, rect));
+    }
 }
 
-/// Save a link for later writing in the annotations dictionary.
-fn write_link(ctx: &mut PageContext, pos: Point, dest: &Destination, size: Size) {
+/// Save a form field and its appearances for later writing as a widget
+/// annotation.
+fn write_field(ctx: &mut PageContext, frame: &Frame, field: &FormField) {
+    if ctx.parent.is_pdfa() {
+        ctx.parent.error(
+            field.span,
+            "PDF/A export does not support form fields",
+            "remove the field or export without a PDF standard",
+        );
+        return;
+    }
+
+    let size = frame.size();
+    let rect = annotation_rect(ctx, Point::zero(), size);
+    let (normal, checked) = match field.kind {
+        FormFieldKind::Checkbox { .. } | FormFieldKind::Radio { .. } => {
+            let off = construct_detached(ctx.parent, &field.appearance(size, false));
+            let on = construct_detached(ctx.parent, &field.appearance(size, true));
+            (off, Some(on))
+        }
+        FormFieldKind::Text { .. } | FormFieldKind::Dropdown { .. } => {
+            (construct_detached(ctx.parent, frame), None)
+        }
+    };
+
+    // Viewers show the text of edited fields in the field's font, so it must
+    // be embedded even if the field is empty.
+    let font = field.font.as_ref().map(|font| {
+        ctx.parent.font_map.insert(font.clone());
+        ctx.parent.glyph_sets.entry(font.clone()).or_default();
+        eco_format!("F{}", ctx.parent.font_map.map(font.clone()))
+    });
+
+    ctx.fields.push(PdfField {
+        field: field.clone(),
+        rect,
+        size,
+        normal,
+        checked,
+        font,
+    });
+}
+
+/// Compute the bounding box of an annotation in the PDF coordinate system.
+fn annotation_rect(ctx: &PageContext, pos: Point, size: Size) -> Rect {
     let mut min_x = Abs::inf();
     let mut min_y = Abs::inf();
     let mut max_x = -Abs::inf();
     let mut max_y = -Abs::inf();
 
-    // Compute the bounding box of the transformed link.
+    // Compute the bounding box of the transformed area.
     for point in [
         pos,
         pos + Point::with_x(size.x),
@@ -539,9 +1353,7 @@ fn write_link(ctx: &mut PageContext, pos: Point, dest: &Destination, size: Size)
     let x2 = max_x.to_f32();
     let y1 = max_y.to_f32();
     let y2 = min_y.to_f32();
-    let rect = Rect::new(x1, y1, x2, y2);
-
-    ctx.links.push((dest.clone(), rect));
+    Rect::new(x1, y1, x2, y2)
 }
 
 impl From<&LineCap> for LineCapStyle {
@@ -563,3 +1375,62 @@ impl From<&LineJoin> for LineJoinStyle {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_label_from_numbering() {
+        let label = |pattern: &str, number| {
+            let label = PdfPageLabel::new(&Value::Str(pattern.into()), number).unwrap();
+            (label.prefix, label.style, label.number)
+        };
+
+        assert_eq!(label("1", 3), ("".into(), Some(PdfPageLabelStyle::Arabic), 3));
+        assert_eq!(label("i", 2), ("".into(), Some(PdfPageLabelStyle::LowerRoman), 2));
+        assert_eq!(
+            label("- A -", 1),
+            ("- ".into(), Some(PdfPageLabelStyle::UpperAlpha), 1)
+        );
+        assert_eq!(label("p. *", 1), ("p. ".into(), Some(PdfPageLabelStyle::Arabic), 1));
+        assert_eq!(label("1", 0), ("".into(), None, 0));
+        assert_eq!(PdfPageLabel::new(&Value::None, 1), None);
+    }
+
+    #[test]
+    fn test_page_label_ranges() {
+        let label = |pattern: &str, number| {
+            PdfPageLabel::new(&Value::Str(pattern.into()), number)
+        };
+
+        // An unnumbered title page, roman front matter, an unnumbered page
+        // and arabic main matter that restarts at one.
+        let labels = [
+            None,
+            label("i", 1),
+            label("i", 2),
+            None,
+            None,
+            label("1", 1),
+            label("1", 2),
+            label("1", 5),
+        ];
+
+        let ranges: Vec<_> = label_ranges(&labels)
+            .into_iter()
+            .map(|(i, label)| (i, label.style, label.number))
+            .collect();
+
+        assert_eq!(
+            ranges,
+            [
+                (0, None, 0),
+                (1, Some(PdfPageLabelStyle::LowerRoman), 1),
+                (3, None, 0),
+                (5, Some(PdfPageLabelStyle::Arabic), 1),
+                (7, Some(PdfPageLabelStyle::Arabic), 5),
+            ]
+        );
+    }
+}
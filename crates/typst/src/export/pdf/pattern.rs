@@ -0,0 +1,65 @@
+use pdf_writer::types::{PaintType, TilingType};
+use pdf_writer::{Filter, Finish, Name, Rect};
+
+use super::{AbsExt, PdfContext, RefExt};
+use crate::geom::{Abs, Pattern, Ratio, Transform};
+
+/// A pattern as it is placed on a page.
+///
+/// Like gradients, each placement of a pattern with a different transform
+/// needs its own PDF pattern. The content stream of the tile is shared.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct PdfPattern {
+    /// The pattern.
+    pub pattern: Pattern,
+    /// Maps from the top-left corner of the painted item's bounding box to
+    /// the parent's default coordinate system.
+    pub transform: Transform,
+}
+
+/// Write all used patterns as tiling patterns.
+#[tracing::instrument(skip_all)]
+pub fn write_patterns(ctx: &mut PdfContext) {
+    let patterns: Vec<_> = ctx.pattern_map.items().cloned().collect();
+    for PdfPattern { pattern, transform } in patterns {
+        let pattern_ref = ctx.alloc.bump();
+        ctx.pattern_refs.push(pattern_ref);
+
+        // The tile's content is written with its y-axis pointing upwards.
+        let size = pattern.size();
+        let cell = pattern.cell();
+        let Transform { sx, ky, kx, sy, tx, ty } = transform.pre_concat(flip_y(size.y));
+
+        let content = &ctx.pattern_tiles[&pattern];
+        let mut tiling = ctx.writer.tiling_pattern(pattern_ref, content);
+        tiling
+            .tiling_type(TilingType::ConstantSpacing)
+            .paint_type(PaintType::Colored)
+            .bbox(Rect::new(0.0, 0.0, size.x.to_f32(), size.y.to_f32()))
+            .x_step(cell.x.to_f32())
+            .y_step(cell.y.to_f32())
+            .matrix([
+                sx.get() as f32,
+                ky.get() as f32,
+                kx.get() as f32,
+                sy.get() as f32,
+                tx.to_f32(),
+                ty.to_f32(),
+            ]);
+        tiling.pair(Name(b"Resources"), ctx.global_resources_ref);
+        tiling.filter(Filter::FlateDecode);
+        tiling.finish();
+    }
+}
+
+/// A transform that flips the y-axis of an area with the given height.
+pub fn flip_y(height: Abs) -> Transform {
+    Transform {
+        sx: Ratio::one(),
+        ky: Ratio::zero(),
+        kx: Ratio::zero(),
+        sy: Ratio::new(-1.0),
+        tx: Abs::zero(),
+        ty: height,
+    }
+}
@@ -0,0 +1,379 @@
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+
+use ecow::{eco_format, EcoString};
+use pdf_writer::{Finish, Name, Ref, TextStr};
+
+use super::{PdfContext, RefExt};
+use crate::doc::{Destination, Meta};
+use crate::model::{Content, Location};
+
+/// The logical structure of a document.
+///
+/// The tree is built up while the pages are written: Frames carry the elements
+/// and links they belong to as metadata and each piece of content is assigned
+/// to the innermost structure element among them.
+///
+/// Paragraphs, lists and tables only carry a location, and thus only appear in
+/// the tree, if the document is tagged. Otherwise, their content is assigned
+/// to the structure element around them.
+pub struct StructTree {
+    /// The structure elements. The first one is the document element, which
+    /// is the root of all others.
+    nodes: Vec<StructNode>,
+    /// Maps from element locations to their structure elements.
+    elems: HashMap<Location, usize>,
+    /// Maps from links to their structure elements, per parent element.
+    links: HashMap<(usize, Destination), usize>,
+    /// For each page, the structure elements of its marked-content sequences,
+    /// indexed by their MCID.
+    pages: Vec<Vec<usize>>,
+    /// For each link annotation, its structure element.
+    annotations: Vec<usize>,
+}
+
+/// An element in the structure tree.
+struct StructNode {
+    /// The structure type, like `P` or `H1`.
+    role: EcoString,
+    /// The parent element, `None` for the document element.
+    parent: Option<usize>,
+    /// An alternate description of the element's content.
+    alt: Option<EcoString>,
+    /// The element's children in reading order.
+    kids: Vec<StructKid>,
+}
+
+/// A child of an element in the structure tree.
+enum StructKid {
+    /// Another structure element.
+    Node(usize),
+    /// A marked-content sequence on a page.
+    Content { page: usize, mcid: usize },
+    /// An annotation on a page.
+    Annotation { page: usize, annotation: Ref },
+}
+
+impl StructTree {
+    /// Create a new tree with just the document element.
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![StructNode::new("Document".into(), None)],
+            elems: HashMap::new(),
+            links: HashMap::new(),
+            pages: vec![],
+            annotations: vec![],
+        }
+    }
+
+    /// Start a new page and return its index.
+    pub fn add_page(&mut self) -> usize {
+        self.pages.push(vec![]);
+        self.pages.len() - 1
+    }
+
+    /// Find or create the structure element for content that is marked with
+    /// the given metadata, ordered from the outermost to the innermost.
+    pub fn resolve(&mut self, markers: &[Meta]) -> usize {
+        let mut parent = 0;
+        for meta in markers {
+            parent = match meta {
+                Meta::Elem(elem) => {
+                    let (Some(role), Some(loc)) = (role(elem), elem.location()) else {
+                        continue;
+                    };
+
+                    // Paragraphs contribute their content directly to the
+                    // headings and paragraphs they are nested in.
+                    let outer = self.nodes[parent].role.as_str();
+                    if role.as_str() == "P" && (outer == "P" || is_heading(outer)) {
+                        continue;
+                    }
+
+                    match self.elems.get(&loc) {
+                        Some(&node) => node,
+                        None => {
+                            let node = self.push(parent, role);
+                            self.elems.insert(loc, node);
+                            node
+                        }
+                    }
+                }
+                Meta::Link(dest) => {
+                    let key = (parent, dest.clone());
+                    match self.links.get(&key) {
+                        Some(&node) => node,
+                        None => {
+                            let node = self.push(parent, "Link".into());
+                            self.links.insert(key, node);
+                            node
+                        }
+                    }
+                }
+                _ => continue,
+            };
+        }
+        parent
+    }
+
+    /// Find or create the figure element for an image within the given
+    /// structure element.
+    pub fn figure(&mut self, parent: usize, alt: Option<&str>) -> usize {
+        let node = if self.nodes[parent].role.as_str() == "Figure" {
+            parent
+        } else {
+            self.push(parent, "Figure".into())
+        };
+
+        let figure = &mut self.nodes[node];
+        if figure.alt.is_none() {
+            figure.alt = alt.map(Into::into);
+        }
+
+        node
+    }
+
+    /// Add a marked-content sequence on a page to a structure element and
+    /// return its MCID.
+    pub fn mark(&mut self, node: usize, page: usize) -> usize {
+        let mcid = self.pages[page].len();
+        self.pages[page].push(node);
+        self.nodes[node].kids.push(StructKid::Content { page, mcid });
+        mcid
+    }
+
+    /// Add an annotation on a page to a structure element and return its key
+    /// in the parent tree.
+    pub fn annotate(&mut self, node: usize, page: usize, annotation: Ref) -> usize {
+        self.annotations.push(node);
+        self.nodes[node].kids.push(StructKid::Annotation { page, annotation });
+        self.pages.len() + self.annotations.len() - 1
+    }
+
+    /// The structure type of an element in the tree.
+    pub fn role(&self, node: usize) -> &str {
+        &self.nodes[node].role
+    }
+
+    /// Add a new element to the tree.
+    fn push(&mut self, parent: usize, role: EcoString) -> usize {
+        let node = self.nodes.len();
+        self.nodes.push(StructNode::new(role, Some(parent)));
+        self.nodes[parent].kids.push(StructKid::Node(node));
+        node
+    }
+}
+
+impl StructNode {
+    fn new(role: EcoString, parent: Option<usize>) -> Self {
+        Self { role, parent, alt: None, kids: vec![] }
+    }
+}
+
+/// The structure type of an element, if it is part of the structure tree.
+fn role(elem: &Content) -> Option<EcoString> {
+    Some(match elem.func().name() {
+        "heading" => {
+            let level = elem.cast_field::<NonZeroUsize>("level").map_or(1, |l| l.get());
+            eco_format!("H{}", level.min(6))
+        }
+        "par" => "P".into(),
+        "list" | "enum" | "terms" => "L".into(),
+        "listitem" | "enumitem" | "termitem" => "LI".into(),
+        "table" => "Table".into(),
+        "figure" => "Figure".into(),
+        "footnote" => "Reference".into(),
+        "footnoteentry" => "Note".into(),
+        _ => return None,
+    })
+}
+
+/// Whether the structure type is a heading.
+fn is_heading(role: &str) -> bool {
+    role.len() == 2 && role.starts_with('H') && role.as_bytes()[1].is_ascii_digit()
+}
+
+/// Write the structure tree and return the reference of its root.
+#[tracing::instrument(skip_all)]
+pub fn write_structure(ctx: &mut PdfContext) -> Ref {
+    let tree = std::mem::replace(&mut ctx.structure, StructTree::new());
+    let root_ref = ctx.alloc.bump();
+    let refs: Vec<Ref> = tree.nodes.iter().map(|_| ctx.alloc.bump()).collect();
+
+    for (node, &node_ref) in tree.nodes.iter().zip(&refs) {
+        let mut elem = ctx.writer.indirect(node_ref).dict();
+        elem.pair(Name(b"Type"), Name(b"StructElem"));
+        elem.pair(Name(b"S"), Name(node.role.as_bytes()));
+        elem.pair(Name(b"P"), node.parent.map_or(root_ref, |parent| refs[parent]));
+        if let Some(alt) = &node.alt {
+            elem.pair(Name(b"Alt"), TextStr(alt));
+        }
+
+        let mut kids = elem.insert(Name(b"K")).array();
+        for kid in &node.kids {
+            match *kid {
+                StructKid::Node(child) => {
+                    kids.item(refs[child]);
+                }
+                StructKid::Content { page, mcid } => {
+                    let mut mcr = kids.push().dict();
+                    mcr.pair(Name(b"Type"), Name(b"MCR"));
+                    mcr.pair(Name(b"Pg"), ctx.page_refs[page]);
+                    mcr.pair(Name(b"MCID"), mcid as i32);
+                }
+                StructKid::Annotation { page, annotation } => {
+                    let mut objr = kids.push().dict();
+                    objr.pair(Name(b"Type"), Name(b"OBJR"));
+                    objr.pair(Name(b"Pg"), ctx.page_refs[page]);
+                    objr.pair(Name(b"Obj"), annotation);
+                }
+            }
+        }
+    }
+
+    let mut root = ctx.writer.indirect(root_ref).dict();
+    root.pair(Name(b"Type"), Name(b"StructTreeRoot"));
+    root.pair(Name(b"K"), refs[0]);
+
+    // The parent tree maps from the pages' marked-content sequences and from
+    // annotations back to their structure elements. Pages use their index as
+    // key and annotations follow after them.
+    let mut parent_tree = root.insert(Name(b"ParentTree")).dict();
+    let mut nums = parent_tree.insert(Name(b"Nums")).array();
+    for (i, nodes) in tree.pages.iter().enumerate() {
+        nums.item(i as i32);
+        nums.push().array().items(nodes.iter().map(|&node| refs[node]));
+    }
+
+    for (i, &node) in tree.annotations.iter().enumerate() {
+        nums.item((tree.pages.len() + i) as i32);
+        nums.item(refs[node]);
+    }
+
+    nums.finish();
+    parent_tree.finish();
+
+    let next_key = tree.pages.len() + tree.annotations.len();
+    root.pair(Name(b"ParentTreeNextKey"), next_key as i32);
+    root.finish();
+
+    root_ref
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{element, Element, Locator};
+
+    /// Display: Paragraph
+    /// Category: test
+    #[element]
+    struct ParElem {}
+
+    /// Display: Bullet List
+    /// Category: test
+    #[element]
+    struct ListElem {}
+
+    /// Display: Bullet List Item
+    /// Category: test
+    #[element]
+    struct ListItem {}
+
+    /// Display: Table
+    /// Category: test
+    #[element]
+    struct TableElem {}
+
+    fn marker(mut elem: Content, loc: Option<Location>) -> Meta {
+        if let Some(loc) = loc {
+            elem.set_location(loc);
+        }
+        Meta::Elem(elem)
+    }
+
+    fn roles(tree: &StructTree, mut node: usize) -> Vec<&str> {
+        let mut roles = vec![tree.role(node)];
+        while let Some(parent) = tree.nodes[node].parent {
+            roles.insert(0, tree.role(parent));
+            node = parent;
+        }
+        roles
+    }
+
+    #[test]
+    fn test_structure_nests_elements() {
+        let mut locator = Locator::new();
+        let list = marker(ListElem::new().pack(), Some(locator.locate(1)));
+        let item = marker(ListItem::new().pack(), Some(locator.locate(2)));
+        let par = marker(ParElem::new().pack(), Some(locator.locate(3)));
+
+        let mut tree = StructTree::new();
+        let node = tree.resolve(&[list.clone(), item.clone(), par.clone()]);
+        assert_eq!(roles(&tree, node), ["Document", "L", "LI", "P"]);
+
+        // Content of the same elements ends up in the same structure element.
+        assert_eq!(tree.resolve(&[list, item, par]), node);
+        assert_eq!(tree.nodes.len(), 4);
+    }
+
+    #[test]
+    fn test_structure_merges_nested_paragraphs() {
+        let mut locator = Locator::new();
+        let outer = marker(ParElem::new().pack(), Some(locator.locate(1)));
+        let inner = marker(ParElem::new().pack(), Some(locator.locate(2)));
+
+        let mut tree = StructTree::new();
+        let node = tree.resolve(&[outer, inner]);
+        assert_eq!(roles(&tree, node), ["Document", "P"]);
+    }
+
+    #[test]
+    fn test_structure_skips_untagged_elements() {
+        // Without tagging, lists and paragraphs aren't located.
+        let list = marker(ListElem::new().pack(), None);
+        let par = marker(ParElem::new().pack(), None);
+        let link = Meta::Link(Destination::Url("https://typst.app".into()));
+
+        let mut tree = StructTree::new();
+        let node = tree.resolve(&[list, par, link]);
+        assert_eq!(roles(&tree, node), ["Document", "Link"]);
+    }
+
+    #[test]
+    fn test_structure_tags_tables_and_links() {
+        let mut locator = Locator::new();
+        let table = marker(TableElem::new().pack(), Some(locator.locate(1)));
+        let cell = marker(ParElem::new().pack(), Some(locator.locate(2)));
+        let first = marker(ParElem::new().pack(), Some(locator.locate(3)));
+        let second = marker(ParElem::new().pack(), Some(locator.locate(4)));
+        let link = Meta::Link(Destination::Url("https://typst.app".into()));
+
+        let mut tree = StructTree::new();
+        let node = tree.resolve(&[table, cell]);
+        assert_eq!(roles(&tree, node), ["Document", "Table", "P"]);
+
+        // A link is only shared within the same paragraph.
+        let a = tree.resolve(&[first.clone(), link.clone()]);
+        assert_eq!(roles(&tree, a), ["Document", "P", "Link"]);
+        assert_eq!(tree.resolve(&[first, link.clone()]), a);
+        assert_ne!(tree.resolve(&[second, link]), a);
+    }
+
+    #[test]
+    fn test_structure_parent_tree_keys() {
+        let mut tree = StructTree::new();
+        let first = tree.add_page();
+        let second = tree.add_page();
+        assert_eq!((first, second), (0, 1));
+
+        // MCIDs are counted per page and annotations are keyed after the
+        // pages.
+        assert_eq!(tree.mark(0, first), 0);
+        assert_eq!(tree.mark(0, first), 1);
+        assert_eq!(tree.mark(0, second), 0);
+        assert_eq!(tree.annotate(0, second, Ref::new(1)), 2);
+        assert_eq!(tree.annotate(0, second, Ref::new(2)), 3);
+        assert_eq!(tree.nodes[0].kids.len(), 5);
+    }
+}
@@ -12,10 +12,11 @@ use ttf_parser::{GlyphId, OutlineBuilder};
 use usvg::{NodeExt, TreeParsing};
 
 use crate::doc::{Frame, FrameItem, GroupItem, Meta, TextItem};
+use crate::export::glyph::{color_glyph, colr_layers, ColorGlyph};
 use crate::font::Font;
 use crate::geom::{
-    self, Abs, Color, Geometry, LineCap, LineJoin, Paint, PathItem, Shape, Size, Stroke,
-    Transform,
+    self, Abs, Color, Geometry, Gradient, LineCap, LineJoin, Paint, PathItem, Pattern,
+    Point, Shape, Size, Stroke, Transform,
 };
 use crate::image::{DecodedImage, Image};
 
@@ -29,7 +30,7 @@ pub fn render(frame: &Frame, pixel_per_pt: f32, fill: Color) -> sk::Pixmap {
     let pxh = (pixel_per_pt * size.y.to_f32()).round().max(1.0) as u32;
 
     let mut canvas = sk::Pixmap::new(pxw, pxh).unwrap();
-    canvas.fill(fill.into());
+    canvas.fill((&fill).into());
 
     let ts = sk::Transform::from_scale(pixel_per_pt, pixel_per_pt);
     render_frame(&mut canvas, ts, None, frame);
@@ -66,6 +67,10 @@ fn render_frame(
                 Meta::Link(_) => {}
                 Meta::Elem(_) => {}
                 Meta::PageNumbering(_) => {}
+                Meta::PageNumber(_) => {}
+                Meta::PrintBoxes(_) => {}
+                Meta::File(_) => {}
+                Meta::Field(_) => {}
                 Meta::Hide => {}
             },
         }
@@ -132,15 +137,21 @@ fn render_text(
     mask: Option<&sk::Mask>,
     text: &TextItem,
 ) {
+    let (origin, size) = text.bbox();
     let mut x = 0.0;
     for glyph in &text.glyphs {
         let id = GlyphId(glyph.id);
         let offset = x + glyph.x_offset.at(text.size).to_f32();
         let ts = ts.pre_translate(offset, 0.0);
+        let bbox = (origin - Point::with_x(Abs::pt(offset.into())), size);
 
-        render_svg_glyph(canvas, ts, mask, text, id)
-            .or_else(|| render_bitmap_glyph(canvas, ts, mask, text, id))
-            .or_else(|| render_outline_glyph(canvas, ts, mask, text, id));
+        match color_glyph(&text.font, glyph.id) {
+            Some(ColorGlyph::Svg) => render_svg_glyph(canvas, ts, mask, text, id),
+            Some(ColorGlyph::Colr) => render_colr_glyph(canvas, ts, mask, text, id, bbox),
+            Some(ColorGlyph::Bitmap) => render_bitmap_glyph(canvas, ts, mask, text, id),
+            None => None,
+        }
+        .or_else(|| render_outline_glyph(canvas, ts, mask, text, id, bbox));
 
         x += glyph.x_advance.at(text.size).to_f32();
     }
@@ -243,6 +254,43 @@ fn render_svg_glyph(
     Some(())
 }
 
+/// Render a glyph with colored layers from the COLR table into the canvas.
+///
+/// The bounding box of the text run is given relative to the glyph's origin.
+fn render_colr_glyph(
+    canvas: &mut sk::Pixmap,
+    ts: sk::Transform,
+    mask: Option<&sk::Mask>,
+    text: &TextItem,
+    id: GlyphId,
+    (origin, size): (Point, Size),
+) -> Option<()> {
+    let layers = colr_layers(&text.font, id.0)?;
+
+    // The layers are in font units.
+    let scale = text.size.to_f32() / text.font.units_per_em() as f32;
+    let ts = ts.pre_scale(scale, scale);
+
+    // The text's fill is positioned relative to the text run, so we need to
+    // undo the glyph's scaling for it.
+    let shader_ts = sk::Transform::from_scale(1.0 / scale, 1.0 / scale)
+        .pre_translate(origin.x.to_f32(), origin.y.to_f32());
+
+    for layer in layers.iter() {
+        let Some(path) = convert_path(&layer.path) else { continue };
+        let fill = match layer.color {
+            Some(color) => Paint::Solid(Color::Rgba(color)),
+            None => text.fill.clone(),
+        };
+
+        let mut storage = None;
+        let paint = to_sk_paint(&fill, size, ts, shader_ts, &mut storage);
+        canvas.fill_path(&path, &paint, sk::FillRule::default(), ts, mask);
+    }
+
+    Some(())
+}
+
 /// Render a bitmap glyph into the canvas.
 fn render_bitmap_glyph(
     canvas: &mut sk::Pixmap,
@@ -268,32 +316,45 @@ fn render_bitmap_glyph(
 }
 
 /// Render an outline glyph into the canvas. This is the "normal" case.
+///
+/// The bounding box of the text run is given relative to the glyph's origin.
 fn render_outline_glyph(
     canvas: &mut sk::Pixmap,
     ts: sk::Transform,
     mask: Option<&sk::Mask>,
     text: &TextItem,
     id: GlyphId,
+    (origin, size): (Point, Size),
 ) -> Option<()> {
     let ppem = text.size.to_f32() * ts.sy;
 
     // Render a glyph directly as a path. This only happens when the fast glyph
-    // rasterization can't be used due to very large text size or weird
-    // scale/skewing transforms.
-    if ppem > 100.0 || ts.kx != 0.0 || ts.ky != 0.0 || ts.sx != ts.sy {
+    // rasterization can't be used due to very large text size, weird
+    // scale/skewing transforms or a non-solid paint.
+    if ppem > 100.0
+        || ts.kx != 0.0
+        || ts.ky != 0.0
+        || ts.sx != ts.sy
+        || !matches!(text.fill, Paint::Solid(_))
+    {
         let path = {
             let mut builder = WrappedPathBuilder(sk::PathBuilder::new());
             text.font.ttf().outline_glyph(id, &mut builder)?;
             builder.0.finish()?
         };
 
-        let paint = (&text.fill).into();
-        let rule = sk::FillRule::default();
-
         // Flip vertically because font design coordinate
         // system is Y-up.
         let scale = text.size.to_f32() / text.font.units_per_em() as f32;
         let ts = ts.pre_scale(scale, -scale);
+
+        // The paint is positioned relative to the text run, so we need to
+        // undo the glyph's scaling for it.
+        let shader_ts = sk::Transform::from_scale(1.0 / scale, -1.0 / scale)
+            .pre_translate(origin.x.to_f32(), origin.y.to_f32());
+        let mut storage = None;
+        let paint = to_sk_paint(&text.fill, size, ts, shader_ts, &mut storage);
+        let rule = sk::FillRule::default();
         canvas.fill_path(&path, &paint, rule, ts, mask);
         return Some(());
     }
@@ -326,7 +387,7 @@ fn render_outline_glyph(
         let mw = bitmap.width;
         let mh = bitmap.height;
 
-        let Paint::Solid(color) = text.fill;
+        let Paint::Solid(color) = &text.fill else { return None };
         let c = color.to_rgba();
 
         // Pad the pixmap with 1 pixel in each dimension so that we do
@@ -364,7 +425,7 @@ fn render_outline_glyph(
         let bottom = top + mh;
 
         // Premultiply the text color.
-        let Paint::Solid(color) = text.fill;
+        let Paint::Solid(color) = &text.fill else { return None };
         let c = color.to_rgba();
         let color = sk::ColorU8::from_rgba(c.r, c.g, c.b, 255).premultiply().get();
 
@@ -415,8 +476,12 @@ fn render_shape(
         Geometry::Path(ref path) => convert_path(path)?,
     };
 
+    let (origin, size) = shape.geometry.bbox();
+    let shader_ts = sk::Transform::from_translate(origin.x.to_f32(), origin.y.to_f32());
+
     if let Some(fill) = &shape.fill {
-        let mut paint: sk::Paint = fill.into();
+        let mut storage = None;
+        let mut paint = to_sk_paint(fill, size, ts, shader_ts, &mut storage);
         if matches!(shape.geometry, Geometry::Rect(_)) {
             paint.anti_alias = false;
         }
@@ -449,7 +514,8 @@ fn render_shape(
 
                 sk::StrokeDash::new(dash_array, pattern.phase.to_f32())
             });
-            let paint = paint.into();
+            let mut storage = None;
+            let paint = to_sk_paint(paint, size, ts, shader_ts, &mut storage);
             let stroke = sk::Stroke {
                 width,
                 line_cap: line_cap.into(),
@@ -581,18 +647,153 @@ impl From<Transform> for sk::Transform {
     }
 }
 
-impl From<&Paint> for sk::Paint<'static> {
-    fn from(paint: &Paint) -> Self {
-        let mut sk_paint = sk::Paint::default();
-        let Paint::Solid(color) = *paint;
-        sk_paint.set_color(color.into());
-        sk_paint.anti_alias = true;
-        sk_paint
+/// Convert a paint into a tiny-skia paint.
+///
+/// Gradients and patterns are positioned in a bounding box of the given size.
+/// The shader transform maps from the bounding box into the coordinate system
+/// of the path and `ts` is the transform the path is drawn with.
+fn to_sk_paint<'a>(
+    paint: &Paint,
+    size: Size,
+    ts: sk::Transform,
+    shader_ts: sk::Transform,
+    storage: &'a mut Option<Arc<sk::Pixmap>>,
+) -> sk::Paint<'a> {
+    let mut sk_paint = sk::Paint::default();
+    sk_paint.anti_alias = true;
+
+    // Textures are rasterized at roughly the resolution they are drawn at.
+    let device = ts.pre_concat(shader_ts);
+    let density = device.sx.hypot(device.ky).max(device.kx.hypot(device.sy));
+
+    match paint {
+        Paint::Solid(color) => sk_paint.set_color(color.into()),
+        Paint::Gradient(gradient) => {
+            sk_paint.shader =
+                gradient_shader(gradient, size, density, shader_ts, storage);
+        }
+        Paint::Pattern(pattern) => {
+            let cell = pattern.cell();
+            let (w, h) = (cell.x.to_f32(), cell.y.to_f32());
+            let pxw = (w * density).ceil().clamp(1.0, 2048.0) as u32;
+            let pxh = (h * density).ceil().clamp(1.0, 2048.0) as u32;
+            *storage = pattern_texture(pattern, pxw, pxh);
+            if let Some(texture) = storage.as_deref() {
+                sk_paint.shader = sk::Pattern::new(
+                    texture.as_ref(),
+                    sk::SpreadMode::Repeat,
+                    sk::FilterQuality::Bilinear,
+                    1.0,
+                    shader_ts.pre_scale(w / pxw as f32, h / pxh as f32),
+                );
+            } else {
+                sk_paint.set_color(sk::Color::TRANSPARENT);
+            }
+        }
     }
+
+    sk_paint
+}
+
+/// Create a shader for a gradient.
+fn gradient_shader<'a>(
+    gradient: &Gradient,
+    size: Size,
+    density: f32,
+    shader_ts: sk::Transform,
+    storage: &'a mut Option<Arc<sk::Pixmap>>,
+) -> sk::Shader<'a> {
+    // Avoid a degenerate bounding box for straight lines.
+    let size = size.map(|v| if v > Abs::zero() { v } else { Abs::pt(1.0) });
+    let (w, h) = (size.x.to_f32(), size.y.to_f32());
+    let stops = gradient
+        .srgb_stops()
+        .into_iter()
+        .map(|(color, offset)| {
+            sk::GradientStop::new(offset.get() as f32, (&color).into())
+        })
+        .collect();
+
+    let shader = match gradient {
+        Gradient::Linear(linear) => {
+            let (start, end) = linear.axis(size);
+            sk::LinearGradient::new(
+                sk::Point::from_xy(start.x.to_f32(), start.y.to_f32()),
+                sk::Point::from_xy(end.x.to_f32(), end.y.to_f32()),
+                stops,
+                sk::SpreadMode::Pad,
+                shader_ts,
+            )
+        }
+        Gradient::Radial(radial) => {
+            let center = sk::Point::from_xy(
+                radial.center.x.get() as f32,
+                radial.center.y.get() as f32,
+            );
+            sk::RadialGradient::new(
+                center,
+                center,
+                radial.radius.get() as f32,
+                stops,
+                sk::SpreadMode::Pad,
+                shader_ts.pre_scale(w, h),
+            )
+        }
+        Gradient::Conic(_) => {
+            let pxw = (w * density).ceil().clamp(1.0, 1024.0) as u32;
+            let pxh = (h * density).ceil().clamp(1.0, 1024.0) as u32;
+            *storage = conic_texture(gradient, size, pxw, pxh);
+            storage.as_deref().map(|texture| {
+                sk::Pattern::new(
+                    texture.as_ref(),
+                    sk::SpreadMode::Pad,
+                    sk::FilterQuality::Bilinear,
+                    1.0,
+                    shader_ts.pre_scale(w / pxw as f32, h / pxh as f32),
+                )
+            })
+        }
+    };
+
+    shader.unwrap_or_else(|| sk::Shader::SolidColor((&gradient.sample(0.0)).into()))
+}
+
+/// Rasterize a conic gradient into a texture that covers its bounding box.
+#[comemo::memoize]
+pub(super) fn conic_texture(
+    gradient: &Gradient,
+    size: Size,
+    w: u32,
+    h: u32,
+) -> Option<Arc<sk::Pixmap>> {
+    let mut pixmap = sk::Pixmap::new(w, h)?;
+    for (i, pixel) in pixmap.pixels_mut().iter_mut().enumerate() {
+        let x = (i as u32 % w) as f64 + 0.5;
+        let y = (i as u32 / w) as f64 + 0.5;
+        let point = Point::new(size.x * (x / w as f64), size.y * (y / h as f64));
+        let c = gradient.sample_at(point, size).to_rgba();
+        *pixel = sk::ColorU8::from_rgba(c.r, c.g, c.b, c.a).premultiply();
+    }
+    Some(Arc::new(pixmap))
+}
+
+/// Rasterize a pattern's tile, including the spacing around it.
+#[comemo::memoize]
+pub(super) fn pattern_texture(
+    pattern: &Pattern,
+    w: u32,
+    h: u32,
+) -> Option<Arc<sk::Pixmap>> {
+    let mut pixmap = sk::Pixmap::new(w, h)?;
+    let cell = pattern.cell();
+    let ts =
+        sk::Transform::from_scale(w as f32 / cell.x.to_f32(), h as f32 / cell.y.to_f32());
+    render_frame(&mut pixmap, ts, None, pattern.frame());
+    Some(Arc::new(pixmap))
 }
 
-impl From<Color> for sk::Color {
-    fn from(color: Color) -> Self {
+impl From<&Color> for sk::Color {
+    fn from(color: &Color) -> Self {
         let c = color.to_rgba();
         sk::Color::from_rgba8(c.r, c.g, c.b, c.a)
     }
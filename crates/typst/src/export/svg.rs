@@ -0,0 +1,844 @@
+//! Exporting into SVG files.
+
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter, Write};
+use std::io::Read;
+
+use base64::Engine;
+use ecow::{eco_format, EcoString};
+use ttf_parser::{GlyphId, OutlineBuilder};
+use xmlwriter::XmlWriter;
+
+use crate::doc::{Destination, Frame, FrameItem, GroupItem, Meta, TextItem};
+use crate::geom::{
+    Abs, Color, Geometry, Gradient, LineCap, LineJoin, Paint, PathItem, Pattern, Ratio,
+    RgbaColor, Shape, Size, Stroke, Transform,
+};
+use crate::image::{Image, ImageFormat, RasterFormat, VectorFormat};
+use crate::util::hash128;
+
+/// Export a frame into an SVG file.
+///
+/// Returns the serialized SVG document.
+#[tracing::instrument(skip_all)]
+pub fn svg(frame: &Frame) -> String {
+    let mut renderer = SvgRenderer::new();
+    renderer.write_header(frame.size());
+    renderer.render_frame(frame, Transform::identity());
+    renderer.finalize()
+}
+
+/// Renders frames into an SVG document.
+struct SvgRenderer {
+    /// The XML writer for the document's body.
+    xml: XmlWriter,
+    /// Glyphs that are referenced from the body. They are written once into
+    /// the `<defs>` section and then reused for every occurrence.
+    glyphs: Deduplicator<RenderedGlyph>,
+    /// Clip paths that are referenced from the body.
+    clip_paths: Deduplicator<EcoString>,
+    /// Gradients that are referenced from the body.
+    gradients: Deduplicator<SvgGradient>,
+    /// Patterns that are referenced from the body.
+    patterns: Deduplicator<SvgPattern>,
+}
+
+/// A glyph that has been prepared for inclusion in the `<defs>` section.
+///
+/// All glyph definitions live in font units with the y-axis pointing upwards,
+/// so that they can be reused for all sizes of the same font.
+#[derive(Debug, Clone)]
+enum RenderedGlyph {
+    /// An outline glyph, given as SVG path data.
+    Path(EcoString),
+    /// A bitmap or SVG glyph embedded as an image.
+    Image { url: EcoString, x: f64, y: f64, width: f64, height: f64 },
+}
+
+/// A gradient as it is used by an element.
+///
+/// Gradients are positioned relative to the bounding box of the element they
+/// are applied to. The transform maps from the bounding box into the user
+/// space of the element.
+#[derive(Debug, Clone, Hash)]
+struct SvgGradient {
+    /// The gradient.
+    gradient: Gradient,
+    /// The size of the bounding box.
+    size: Size,
+    /// Maps from the bounding box into the element's user space.
+    transform: Transform,
+}
+
+/// A pattern as it is used by an element.
+#[derive(Debug, Clone, Hash)]
+struct SvgPattern {
+    /// The pattern.
+    pattern: Pattern,
+    /// Maps from the bounding box into the element's user space.
+    transform: Transform,
+}
+
+impl SvgRenderer {
+    /// Create a new renderer.
+    fn new() -> Self {
+        Self {
+            xml: XmlWriter::new(xmlwriter::Options::default()),
+            glyphs: Deduplicator::new('g'),
+            clip_paths: Deduplicator::new('c'),
+            gradients: Deduplicator::new('r'),
+            patterns: Deduplicator::new('p'),
+        }
+    }
+
+    /// Write the opening `<svg>` tag.
+    fn write_header(&mut self, size: Size) {
+        self.xml.start_element("svg");
+        self.xml.write_attribute("class", "typst-doc");
+        self.xml.write_attribute_fmt(
+            "viewBox",
+            format_args!("0 0 {} {}", size.x.to_pt(), size.y.to_pt()),
+        );
+        self.xml.write_attribute("width", &size.x.to_pt());
+        self.xml.write_attribute("height", &size.y.to_pt());
+        self.xml.write_attribute("xmlns", "http://www.w3.org/2000/svg");
+        self.xml
+            .write_attribute("xmlns:xlink", "http://www.w3.org/1999/xlink");
+    }
+
+    /// Render a frame with the given transform.
+    fn render_frame(&mut self, frame: &Frame, ts: Transform) {
+        self.xml.start_element("g");
+        if !ts.is_identity() {
+            self.xml.write_attribute("transform", &SvgMatrix(ts));
+        }
+
+        for (pos, item) in frame.items() {
+            let x = pos.x.to_pt();
+            let y = pos.y.to_pt();
+            self.xml.start_element("g");
+            self.xml
+                .write_attribute_fmt("transform", format_args!("translate({x} {y})"));
+
+            match item {
+                FrameItem::Group(group) => self.render_group(group),
+                FrameItem::Text(text) => self.render_text(text),
+                FrameItem::Shape(shape, _) => self.render_shape(shape),
+                FrameItem::Image(image, size, _) => self.render_image(image, *size),
+                FrameItem::Meta(meta, size) => match meta {
+                    Meta::Link(dest) => self.render_link(dest, *size),
+                    Meta::Elem(_) => {}
+                    Meta::PageNumbering(_) => {}
+                    Meta::PageNumber(_) => {}
+                    Meta::PrintBoxes(_) => {}
+                    Meta::File(_) => {}
+                    Meta::Field(_) => {}
+                    Meta::Hide => {}
+                },
+            }
+
+            self.xml.end_element();
+        }
+
+        self.xml.end_element();
+    }
+
+    /// Render a group. If the group has `clips` set to true, a clip path is
+    /// created and applied in the group's coordinate system.
+    fn render_group(&mut self, group: &GroupItem) {
+        self.xml.start_element("g");
+        self.xml.write_attribute("class", "typst-group");
+        if !group.transform.is_identity() {
+            self.xml.write_attribute("transform", &SvgMatrix(group.transform));
+        }
+
+        if group.clips {
+            let size = group.frame.size();
+            let id = self.clip_paths.insert_with(hash128(&size), || {
+                let mut builder = SvgPathBuilder::default();
+                builder.rect(size.x.to_pt(), size.y.to_pt());
+                builder.0
+            });
+            self.xml.start_element("g");
+            self.xml.write_attribute_fmt("clip-path", format_args!("url(#{id})"));
+            self.render_frame(&group.frame, Transform::identity());
+            self.xml.end_element();
+        } else {
+            self.render_frame(&group.frame, Transform::identity());
+        }
+
+        self.xml.end_element();
+    }
+
+    /// Render a text item. The text is rendered as a group of glyphs. We try
+    /// to render the text as SVG first, then bitmap, then outline. If none of
+    /// them works, we skip the glyph.
+    fn render_text(&mut self, text: &TextItem) {
+        let upem = text.font.units_per_em();
+        let scale = text.size.to_pt() / upem;
+        let inv_scale = upem / text.size.to_pt();
+
+        self.xml.start_element("g");
+        self.xml.write_attribute("class", "typst-text");
+        self.xml
+            .write_attribute_fmt("transform", format_args!("scale({scale} {})", -scale));
+
+        // Gradients are resolved in the user space of each glyph, so they
+        // are written for each glyph individually.
+        let (origin, size) = text.bbox();
+        let solid = matches!(text.fill, Paint::Solid(_));
+        if solid {
+            self.write_fill(&text.fill, size, Transform::identity());
+        }
+
+        let mut x = 0.0;
+        for glyph in &text.glyphs {
+            let id = GlyphId(glyph.id);
+            let offset = x + glyph.x_offset.at(text.size).to_pt();
+
+            if let Some(glyph_id) = self
+                .prepare_svg_glyph(text, id)
+                .or_else(|| self.prepare_bitmap_glyph(text, id))
+                .or_else(|| self.prepare_outline_glyph(text, id))
+            {
+                self.xml.start_element("use");
+                self.xml
+                    .write_attribute_fmt("xlink:href", format_args!("#{glyph_id}"));
+                self.xml.write_attribute("x", &(offset * inv_scale));
+                if !solid {
+                    let ts =
+                        Transform::translate(Abs::pt(-offset * inv_scale), Abs::zero())
+                            .pre_concat(Transform::scale(
+                                Ratio::new(inv_scale),
+                                Ratio::new(-inv_scale),
+                            ))
+                            .pre_concat(Transform::translate(origin.x, origin.y));
+                    self.write_fill(&text.fill, size, ts);
+                }
+                self.xml.end_element();
+            }
+
+            x += glyph.x_advance.at(text.size).to_pt();
+        }
+
+        self.xml.end_element();
+    }
+
+    /// Prepare a glyph from the font's `SVG` table.
+    fn prepare_svg_glyph(&mut self, text: &TextItem, id: GlyphId) -> Option<DedupId> {
+        let hash = hash128(&(&text.font, id.0));
+        if let Some(glyph_ref) = self.glyphs.get(hash) {
+            return Some(glyph_ref);
+        }
+
+        let mut data = text.font.ttf().glyph_svg_image(id)?;
+
+        // Decompress SVGZ.
+        let mut decoded = vec![];
+        if data.starts_with(&[0x1f, 0x8b]) {
+            let mut decoder = flate2::read::GzDecoder::new(data);
+            decoder.read_to_end(&mut decoded).ok()?;
+            data = &decoded;
+        }
+
+        let xml = std::str::from_utf8(data).ok()?;
+        let document = roxmltree::Document::parse(xml).ok()?;
+        let root = document.root_element();
+
+        // Glyph documents are positioned in font units with the origin on the
+        // baseline. If there is no view box, we provide one that covers the
+        // em square above the baseline.
+        let upem = text.font.units_per_em();
+        let (source, view_box) = match root.attribute("viewBox") {
+            Some(view_box) => {
+                let mut parts = view_box
+                    .split(|c: char| c == ',' || c.is_whitespace())
+                    .filter(|s| !s.is_empty())
+                    .map(|s| s.parse::<f64>());
+                let mut next = || parts.next().and_then(Result::ok);
+                let view_box = [next()?, next()?, next()?, next()?];
+                (xml.to_string(), view_box)
+            }
+            None => {
+                let start = xml.find("<svg")? + "<svg".len();
+                let mut source = xml.to_string();
+                source.insert_str(
+                    start,
+                    &format!(r#" viewBox="0 {} {upem} {upem}""#, -upem),
+                );
+                (source, [0.0, -upem, upem, upem])
+            }
+        };
+
+        let [x, y, width, height] = view_box;
+        let url = data_url("image/svg+xml", source.as_bytes());
+        Some(self.glyphs.insert_with(hash, || RenderedGlyph::Image {
+            url,
+            x,
+            y,
+            width,
+            height,
+        }))
+    }
+
+    /// Prepare a bitmap glyph from the font's `sbix` or `CBDT` tables.
+    fn prepare_bitmap_glyph(&mut self, text: &TextItem, id: GlyphId) -> Option<DedupId> {
+        let hash = hash128(&(&text.font, id.0));
+        if let Some(glyph_ref) = self.glyphs.get(hash) {
+            return Some(glyph_ref);
+        }
+
+        // Take the largest available strike since the output is scalable.
+        let raster = text.font.ttf().glyph_raster_image(id, u16::MAX)?;
+        let image = Image::new(raster.data.into(), raster.format.into(), None).ok()?;
+
+        // Mirrors the positioning in `render_bitmap_glyph`, but in font units.
+        let upem = text.font.units_per_em();
+        let height = upem;
+        let width = (image.width() as f64 / image.height() as f64) * height;
+        let x = raster.x as f64 / image.width() as f64 * upem;
+        let y = -upem - raster.y as f64 / image.height() as f64 * upem;
+        let url = data_url("image/png", image.data());
+
+        Some(self.glyphs.insert_with(hash, || RenderedGlyph::Image {
+            url,
+            x,
+            y,
+            width,
+            height,
+        }))
+    }
+
+    /// Prepare an outline glyph. This is the "normal" case.
+    fn prepare_outline_glyph(&mut self, text: &TextItem, id: GlyphId) -> Option<DedupId> {
+        let hash = hash128(&(&text.font, id.0));
+        if let Some(glyph_ref) = self.glyphs.get(hash) {
+            return Some(glyph_ref);
+        }
+
+        let mut builder = SvgPathBuilder::default();
+        text.font.ttf().outline_glyph(id, &mut builder)?;
+        Some(self.glyphs.insert_with(hash, || RenderedGlyph::Path(builder.0)))
+    }
+
+    /// Render a geometrical shape.
+    fn render_shape(&mut self, shape: &Shape) {
+        self.xml.start_element("path");
+        self.xml.write_attribute("class", "typst-shape");
+
+        let (origin, size) = shape.geometry.bbox();
+        let ts = Transform::translate(origin.x, origin.y);
+
+        match &shape.fill {
+            Some(paint) => self.write_fill(paint, size, ts),
+            None => self.xml.write_attribute("fill", "none"),
+        }
+
+        if let Some(stroke) = &shape.stroke {
+            self.write_stroke(stroke, size, ts);
+        }
+
+        let path = convert_geometry(&shape.geometry);
+        self.xml.write_attribute("d", &path);
+        self.xml.end_element();
+    }
+
+    /// Write the `fill` and `fill-opacity` attributes for a paint.
+    ///
+    /// The size and transform describe the bounding box gradients are
+    /// positioned in.
+    fn write_fill(&mut self, paint: &Paint, size: Size, ts: Transform) {
+        match paint {
+            Paint::Solid(color) => {
+                self.xml.write_attribute("fill", &SvgColor(color.to_rgba()));
+                if let Some(opacity) = opacity(color) {
+                    self.xml.write_attribute("fill-opacity", &opacity);
+                }
+            }
+            Paint::Gradient(gradient) => {
+                let id = self.push_gradient(gradient, size, ts);
+                self.xml.write_attribute_fmt("fill", format_args!("url(#{id})"));
+            }
+            Paint::Pattern(pattern) => {
+                let id = self.push_pattern(pattern, ts);
+                self.xml.write_attribute_fmt("fill", format_args!("url(#{id})"));
+            }
+        }
+    }
+
+    /// Write the stroke attributes.
+    fn write_stroke(&mut self, stroke: &Stroke, size: Size, ts: Transform) {
+        match &stroke.paint {
+            Paint::Solid(color) => {
+                self.xml.write_attribute("stroke", &SvgColor(color.to_rgba()));
+                if let Some(opacity) = opacity(color) {
+                    self.xml.write_attribute("stroke-opacity", &opacity);
+                }
+            }
+            Paint::Gradient(gradient) => {
+                let id = self.push_gradient(gradient, size, ts);
+                self.xml.write_attribute_fmt("stroke", format_args!("url(#{id})"));
+            }
+            Paint::Pattern(pattern) => {
+                let id = self.push_pattern(pattern, ts);
+                self.xml.write_attribute_fmt("stroke", format_args!("url(#{id})"));
+            }
+        }
+
+        self.xml.write_attribute("stroke-width", &stroke.thickness.to_pt());
+        self.xml.write_attribute(
+            "stroke-linecap",
+            match stroke.line_cap {
+                LineCap::Butt => "butt",
+                LineCap::Round => "round",
+                LineCap::Square => "square",
+            },
+        );
+        self.xml.write_attribute(
+            "stroke-linejoin",
+            match stroke.line_join {
+                LineJoin::Miter => "miter",
+                LineJoin::Round => "round",
+                LineJoin::Bevel => "bevel",
+            },
+        );
+        self.xml.write_attribute("stroke-miterlimit", &stroke.miter_limit.0);
+
+        if let Some(pattern) = &stroke.dash_pattern {
+            self.xml.write_attribute("stroke-dashoffset", &pattern.phase.to_pt());
+            if !pattern.array.is_empty() {
+                self.xml.write_attribute(
+                    "stroke-dasharray",
+                    &pattern
+                        .array
+                        .iter()
+                        .map(|dash| dash.to_pt().to_string())
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                );
+            }
+        }
+    }
+
+    /// Render a raster or vector image.
+    fn render_image(&mut self, image: &Image, size: Size) {
+        let mime = match image.format() {
+            ImageFormat::Raster(RasterFormat::Png) => "image/png",
+            ImageFormat::Raster(RasterFormat::Jpg) => "image/jpeg",
+            ImageFormat::Raster(RasterFormat::Gif) => "image/gif",
+            ImageFormat::Vector(VectorFormat::Svg) => "image/svg+xml",
+        };
+
+        self.xml.start_element("image");
+        self.xml.write_attribute("width", &size.x.to_pt());
+        self.xml.write_attribute("height", &size.y.to_pt());
+        self.xml.write_attribute("preserveAspectRatio", "none");
+        self.xml.write_attribute("xlink:href", &data_url(mime, image.data()));
+        if let Some(alt) = image.alt() {
+            self.xml.write_attribute("aria-label", alt);
+        }
+        self.xml.end_element();
+    }
+
+    /// Register a gradient for the `<defs>` section and return its id.
+    fn push_gradient(
+        &mut self,
+        gradient: &Gradient,
+        size: Size,
+        ts: Transform,
+    ) -> DedupId {
+        // Avoid a degenerate bounding box for straight lines.
+        let size = size.map(|v| if v > Abs::zero() { v } else { Abs::pt(1.0) });
+        let gradient = SvgGradient { gradient: gradient.clone(), size, transform: ts };
+        self.gradients.insert_with(hash128(&gradient), || gradient)
+    }
+
+    /// Register a pattern for the `<defs>` section and return its id.
+    fn push_pattern(&mut self, pattern: &Pattern, ts: Transform) -> DedupId {
+        let pattern = SvgPattern { pattern: pattern.clone(), transform: ts };
+        self.patterns.insert_with(hash128(&pattern), || pattern)
+    }
+
+    /// Render a link as a transparent, clickable area.
+    ///
+    /// Only links to URLs are exported since internal destinations may point
+    /// to other pages, which end up in separate files.
+    fn render_link(&mut self, dest: &Destination, size: Size) {
+        let Destination::Url(url) = dest else { return };
+        self.xml.start_element("a");
+        self.xml.write_attribute("xlink:href", url);
+        self.xml.start_element("rect");
+        self.xml.write_attribute("width", &size.x.to_pt());
+        self.xml.write_attribute("height", &size.y.to_pt());
+        self.xml.write_attribute("fill", "transparent");
+        self.xml.end_element();
+        self.xml.end_element();
+    }
+
+    /// Finalize the SVG file. This must be called after all rendering is done.
+    fn finalize(mut self) -> String {
+        // Patterns go first since their tiles may reference glyphs, clip
+        // paths and gradients.
+        self.write_pattern_defs();
+        self.write_glyph_defs();
+        self.write_clip_path_defs();
+        self.write_gradient_defs();
+        self.xml.end_document()
+    }
+
+    /// Write the glyph definitions.
+    fn write_glyph_defs(&mut self) {
+        if self.glyphs.is_empty() {
+            return;
+        }
+
+        self.xml.start_element("defs");
+        self.xml.write_attribute("id", "glyph");
+        for (id, glyph) in self.glyphs.iter() {
+            match glyph {
+                RenderedGlyph::Path(path) => {
+                    self.xml.start_element("path");
+                    self.xml.write_attribute("id", &id);
+                    self.xml.write_attribute("d", path);
+                    self.xml.end_element();
+                }
+                RenderedGlyph::Image { url, x, y, width, height } => {
+                    // The image is defined in a y-down coordinate system, so
+                    // it needs to be flipped back into font units.
+                    self.xml.start_element("image");
+                    self.xml.write_attribute("id", &id);
+                    self.xml.write_attribute("x", x);
+                    self.xml.write_attribute("y", y);
+                    self.xml.write_attribute("width", width);
+                    self.xml.write_attribute("height", height);
+                    self.xml.write_attribute("transform", "scale(1 -1)");
+                    self.xml.write_attribute("xlink:href", url);
+                    self.xml.end_element();
+                }
+            }
+        }
+        self.xml.end_element();
+    }
+
+    /// Write the clip path definitions.
+    fn write_clip_path_defs(&mut self) {
+        if self.clip_paths.is_empty() {
+            return;
+        }
+
+        self.xml.start_element("defs");
+        self.xml.write_attribute("id", "clip-path");
+        for (id, path) in self.clip_paths.iter() {
+            self.xml.start_element("clipPath");
+            self.xml.write_attribute("id", &id);
+            self.xml.start_element("path");
+            self.xml.write_attribute("d", path);
+            self.xml.end_element();
+            self.xml.end_element();
+        }
+        self.xml.end_element();
+    }
+
+    /// Write the pattern definitions.
+    fn write_pattern_defs(&mut self) {
+        if self.patterns.is_empty() {
+            return;
+        }
+
+        self.xml.start_element("defs");
+        self.xml.write_attribute("id", "patterns");
+
+        // Rendering a tile may register further patterns, so the list can
+        // grow while we write it.
+        let mut index = 0;
+        while let Some((id, SvgPattern { pattern, transform })) =
+            self.patterns.at(index).map(|(id, pattern)| (id, pattern.clone()))
+        {
+            let cell = pattern.cell();
+            self.xml.start_element("pattern");
+            self.xml.write_attribute("id", &id);
+            self.xml.write_attribute("patternUnits", "userSpaceOnUse");
+            self.xml.write_attribute("patternTransform", &SvgMatrix(transform));
+            self.xml.write_attribute("width", &cell.x.to_pt());
+            self.xml.write_attribute("height", &cell.y.to_pt());
+            self.render_frame(pattern.frame(), Transform::identity());
+            self.xml.end_element();
+            index += 1;
+        }
+
+        self.xml.end_element();
+    }
+
+    /// Write the gradient definitions.
+    fn write_gradient_defs(&mut self) {
+        if self.gradients.is_empty() {
+            return;
+        }
+
+        self.xml.start_element("defs");
+        self.xml.write_attribute("id", "gradients");
+        for (id, SvgGradient { gradient, size, transform }) in self.gradients.iter() {
+            match gradient {
+                Gradient::Linear(linear) => {
+                    let (start, end) = linear.axis(*size);
+                    self.xml.start_element("linearGradient");
+                    self.xml.write_attribute("id", &id);
+                    self.xml.write_attribute("gradientUnits", "userSpaceOnUse");
+                    self.xml.write_attribute("gradientTransform", &SvgMatrix(*transform));
+                    self.xml.write_attribute("x1", &start.x.to_pt());
+                    self.xml.write_attribute("y1", &start.y.to_pt());
+                    self.xml.write_attribute("x2", &end.x.to_pt());
+                    self.xml.write_attribute("y2", &end.y.to_pt());
+                }
+                Gradient::Radial(radial) => {
+                    // Radial gradients live in the unit square that is
+                    // stretched over the bounding box.
+                    let ts = transform.pre_concat(Transform::scale(
+                        Ratio::new(size.x.to_pt()),
+                        Ratio::new(size.y.to_pt()),
+                    ));
+                    self.xml.start_element("radialGradient");
+                    self.xml.write_attribute("id", &id);
+                    self.xml.write_attribute("gradientUnits", "userSpaceOnUse");
+                    self.xml.write_attribute("gradientTransform", &SvgMatrix(ts));
+                    self.xml.write_attribute("cx", &radial.center.x.get());
+                    self.xml.write_attribute("cy", &radial.center.y.get());
+                    self.xml.write_attribute("r", &radial.radius.get());
+                }
+                Gradient::Conic(_) => {
+                    // SVG has no conic gradients, so we embed a raster image
+                    // of the gradient instead.
+                    let width = (size.x.to_pt() * 2.0).ceil().clamp(1.0, 1024.0) as u32;
+                    let height = (size.y.to_pt() * 2.0).ceil().clamp(1.0, 1024.0) as u32;
+                    let Some(png) =
+                        super::render::conic_texture(gradient, *size, width, height)
+                            .and_then(|pixmap| pixmap.encode_png().ok())
+                    else {
+                        continue;
+                    };
+
+                    self.xml.start_element("pattern");
+                    self.xml.write_attribute("id", &id);
+                    self.xml.write_attribute("patternUnits", "userSpaceOnUse");
+                    self.xml.write_attribute("patternTransform", &SvgMatrix(*transform));
+                    self.xml.write_attribute("width", &size.x.to_pt());
+                    self.xml.write_attribute("height", &size.y.to_pt());
+                    self.xml.start_element("image");
+                    self.xml.write_attribute("width", &size.x.to_pt());
+                    self.xml.write_attribute("height", &size.y.to_pt());
+                    self.xml.write_attribute("preserveAspectRatio", "none");
+                    self.xml.write_attribute("xlink:href", &data_url("image/png", &png));
+                    self.xml.end_element();
+                    self.xml.end_element();
+                    continue;
+                }
+            }
+
+            for (color, offset) in gradient.srgb_stops() {
+                self.xml.start_element("stop");
+                self.xml.write_attribute("offset", &offset.get());
+                self.xml.write_attribute("stop-color", &SvgColor(color.to_rgba()));
+                if let Some(opacity) = opacity(&color) {
+                    self.xml.write_attribute("stop-opacity", &opacity);
+                }
+                self.xml.end_element();
+            }
+            self.xml.end_element();
+        }
+        self.xml.end_element();
+    }
+}
+
+/// Deduplicates glyphs, clip paths, gradients and patterns, handing out an id
+/// for each.
+struct Deduplicator<T> {
+    /// The prefix of the ids handed out by this deduplicator.
+    kind: char,
+    /// The deduplicated elements in insertion order.
+    vec: Vec<T>,
+    /// Maps from hashes to indices into `vec`.
+    present: HashMap<u128, usize>,
+}
+
+impl<T> Deduplicator<T> {
+    /// Create a new deduplicator with the given id prefix.
+    fn new(kind: char) -> Self {
+        Self { kind, vec: vec![], present: HashMap::new() }
+    }
+
+    /// Look up the id of an element that was inserted before.
+    fn get(&self, hash: u128) -> Option<DedupId> {
+        self.present.get(&hash).map(|&index| DedupId(self.kind, index))
+    }
+
+    /// Inserts a value into the vector. If the hash is already present, returns
+    /// the id of the existing value and `f` will not be called. Otherwise,
+    /// inserts the value and returns its newly created id.
+    fn insert_with<F>(&mut self, hash: u128, f: F) -> DedupId
+    where
+        F: FnOnce() -> T,
+    {
+        let index = *self.present.entry(hash).or_insert_with(|| {
+            self.vec.push(f());
+            self.vec.len() - 1
+        });
+        DedupId(self.kind, index)
+    }
+
+    /// The element with the given index alongside its id.
+    fn at(&self, index: usize) -> Option<(DedupId, &T)> {
+        self.vec.get(index).map(|v| (DedupId(self.kind, index), v))
+    }
+
+    /// Whether no elements were inserted.
+    fn is_empty(&self) -> bool {
+        self.vec.is_empty()
+    }
+
+    /// Iterate over the elements alongside their ids.
+    fn iter(&self) -> impl Iterator<Item = (DedupId, &T)> {
+        let kind = self.kind;
+        self.vec.iter().enumerate().map(move |(i, v)| (DedupId(kind, i), v))
+    }
+}
+
+/// An id of a deduplicated element, referenced through `url(#id)` or
+/// `xlink:href="#id"`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+struct DedupId(char, usize);
+
+impl Display for DedupId {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.0, self.1)
+    }
+}
+
+/// Displays as an SVG matrix.
+struct SvgMatrix(Transform);
+
+impl Display for SvgMatrix {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        // Convert a [`Transform`] into a SVG transform string.
+        // See https://developer.mozilla.org/en-US/docs/Web/SVG/Attribute/transform
+        write!(
+            f,
+            "matrix({} {} {} {} {} {})",
+            self.0.sx.get(),
+            self.0.ky.get(),
+            self.0.kx.get(),
+            self.0.sy.get(),
+            self.0.tx.to_pt(),
+            self.0.ty.to_pt()
+        )
+    }
+}
+
+/// Displays as an opaque SVG color.
+struct SvgColor(RgbaColor);
+
+impl Display for SvgColor {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let c = self.0;
+        write!(f, "#{:02x}{:02x}{:02x}", c.r, c.g, c.b)
+    }
+}
+
+/// The opacity of a color, if it is not fully opaque.
+fn opacity(color: &Color) -> Option<f64> {
+    let alpha = color.to_rgba().a;
+    (alpha != u8::MAX).then(|| alpha as f64 / 255.0)
+}
+
+/// Encode data as a base64 data URL.
+fn data_url(mime: &str, data: &[u8]) -> EcoString {
+    let mut url = eco_format!("data:{mime};base64,");
+    url.push_str(&base64::engine::general_purpose::STANDARD.encode(data));
+    url
+}
+
+/// Convert a geometry into SVG path data.
+fn convert_geometry(geometry: &Geometry) -> EcoString {
+    let mut builder = SvgPathBuilder::default();
+    match geometry {
+        Geometry::Line(target) => {
+            builder.move_to(0.0, 0.0);
+            builder.line_to(target.x.to_pt() as f32, target.y.to_pt() as f32);
+        }
+        Geometry::Rect(size) => {
+            builder.rect(size.x.to_pt(), size.y.to_pt());
+        }
+        Geometry::Path(path) => {
+            for item in &path.0 {
+                match item {
+                    PathItem::MoveTo(p) => {
+                        builder.move_to(p.x.to_f32(), p.y.to_f32());
+                    }
+                    PathItem::LineTo(p) => {
+                        builder.line_to(p.x.to_f32(), p.y.to_f32());
+                    }
+                    PathItem::CubicTo(c1, c2, t) => builder.curve_to(
+                        c1.x.to_f32(),
+                        c1.y.to_f32(),
+                        c2.x.to_f32(),
+                        c2.y.to_f32(),
+                        t.x.to_f32(),
+                        t.y.to_f32(),
+                    ),
+                    PathItem::ClosePath => builder.close(),
+                }
+            }
+        }
+    }
+    builder.0
+}
+
+/// Builds SVG path data.
+#[derive(Debug, Default)]
+struct SvgPathBuilder(EcoString);
+
+impl SvgPathBuilder {
+    /// Create a rectangle path. The rectangle is created with the top-left
+    /// corner at (0, 0). The width and height are in points.
+    fn rect(&mut self, width: f64, height: f64) {
+        let (width, height) = (width as f32, height as f32);
+        self.move_to(0.0, 0.0);
+        self.line_to(0.0, height);
+        self.line_to(width, height);
+        self.line_to(width, 0.0);
+        self.close();
+    }
+}
+
+impl OutlineBuilder for SvgPathBuilder {
+    fn move_to(&mut self, x: f32, y: f32) {
+        write!(&mut self.0, "M {x} {y} ").unwrap();
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        write!(&mut self.0, "L {x} {y} ").unwrap();
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        write!(&mut self.0, "Q {x1} {y1} {x} {y} ").unwrap();
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        write!(&mut self.0, "C {x1} {y1} {x2} {y2} {x} {y} ").unwrap();
+    }
+
+    fn close(&mut self) {
+        write!(&mut self.0, "Z ").unwrap();
+    }
+}
+
+/// Additional methods for [`Abs`].
+trait AbsExt {
+    /// Convert to a number of points as f32.
+    fn to_f32(self) -> f32;
+}
+
+impl AbsExt for Abs {
+    fn to_f32(self) -> f32 {
+        self.to_pt() as f32
+    }
+}
@@ -0,0 +1,211 @@
+//! Exporting into plain text.
+
+use ecow::EcoString;
+
+use crate::doc::{Document, Frame, FrameItem, Meta, TextItem};
+use crate::geom::{Abs, Point, Transform};
+use crate::model::{Content, Location};
+
+/// Export a document into plain text.
+///
+/// The text is extracted from the laid-out pages in reading order. Lines of
+/// the same paragraph or heading are joined with spaces, undoing hyphenation,
+/// while separate ones are separated by an empty line. Headings and figures
+/// are found through their elements, paragraphs through the spacing between
+/// their lines.
+#[tracing::instrument(skip_all)]
+pub fn text(document: &Document) -> String {
+    let mut extractor = TextExtractor::default();
+    for page in &document.pages {
+        extractor.frame(page, Transform::identity());
+    }
+
+    let mut text = join(&extractor.runs);
+    if !text.is_empty() {
+        text.push('\n');
+    }
+    text
+}
+
+/// Extracts the text runs from frames.
+#[derive(Default)]
+struct TextExtractor {
+    /// The extracted runs in reading order.
+    runs: Vec<Run>,
+    /// The innermost block-level element the current content belongs to.
+    block: Option<Location>,
+}
+
+/// A text run on a page.
+struct Run {
+    /// The run's text.
+    text: EcoString,
+    /// The block-level element the run belongs to.
+    block: Option<Location>,
+    /// The position where the run's baseline starts.
+    start: Point,
+    /// The position where the run's baseline ends.
+    end: Point,
+    /// The font size.
+    size: Abs,
+    /// Whether the run ends with a hyphen that was inserted by hyphenation.
+    hyphenated: bool,
+}
+
+impl TextExtractor {
+    /// Extract the text from a frame.
+    fn frame(&mut self, frame: &Frame, ts: Transform) {
+        // Like in PDF export, frames carry the full chain of elements their
+        // content belongs to, so the innermost block of the chain replaces
+        // the outer one.
+        let markers: Vec<&Content> = frame
+            .items()
+            .filter_map(|(_, item)| match item {
+                FrameItem::Meta(Meta::Elem(elem), _) => Some(elem),
+                _ => None,
+            })
+            .collect();
+
+        let outer = self.block;
+        if !markers.is_empty() {
+            self.block = markers
+                .into_iter()
+                .filter(|elem| is_block(elem))
+                .filter_map(Content::location)
+                .last();
+        }
+
+        for (pos, item) in frame.items() {
+            match item {
+                FrameItem::Group(group) => {
+                    let ts = ts
+                        .pre_concat(Transform::translate(pos.x, pos.y))
+                        .pre_concat(group.transform);
+                    self.frame(&group.frame, ts);
+                }
+                FrameItem::Text(text) => self.run(text, pos.transform(ts)),
+                _ => {}
+            }
+        }
+
+        self.block = outer;
+    }
+
+    /// Add a text run at a position on the page.
+    fn run(&mut self, text: &TextItem, pos: Point) {
+        if text.text.is_empty() {
+            return;
+        }
+
+        self.runs.push(Run {
+            text: text.text.clone(),
+            block: self.block,
+            start: pos,
+            end: pos + Point::with_x(text.width()),
+            size: text.size,
+            hyphenated: text.glyphs.last().map_or(false, |g| g.range.is_empty()),
+        });
+    }
+}
+
+/// Join text runs into paragraphs.
+fn join(runs: &[Run]) -> String {
+    let pitches = Pitches::new(runs);
+    let mut text = String::new();
+    let mut last: Option<&Run> = None;
+    for run in runs {
+        if let Some(last) = last {
+            let new_line = last.new_line(run);
+            if last.block != run.block
+                || (new_line && pitches.is_par_break(last.advance(run)))
+            {
+                text.truncate(text.trim_end().len());
+                text.push_str("\n\n");
+            } else if !text.ends_with(char::is_whitespace)
+                && !run.text.starts_with(char::is_whitespace)
+            {
+                // Lines of the same block and gaps within a line are joined
+                // with a space, unless the line break hyphenated a word.
+                let gap = run.start.x - last.end.x > last.size * 0.15;
+                if (new_line && !last.hyphenated) || (!new_line && gap) {
+                    text.push(' ');
+                }
+            }
+        }
+
+        text.push_str(&run.text);
+        last = Some(run);
+    }
+    text
+}
+
+impl Run {
+    /// Whether the next run starts on a different line.
+    fn new_line(&self, next: &Self) -> bool {
+        (next.start.y - self.end.y).abs() > self.size / 2.0
+    }
+
+    /// How far below this run the next one starts, relative to the font
+    /// size.
+    fn advance(&self, next: &Self) -> f64 {
+        (next.start.y - self.end.y) / self.size
+    }
+}
+
+/// The distances between consecutive lines of the same block in a document,
+/// relative to their font size.
+struct Pitches {
+    /// The smallest distance.
+    closest: f64,
+    /// The largest distance.
+    widest: f64,
+}
+
+impl Pitches {
+    /// Measure the distances between the lines of the runs.
+    fn new(runs: &[Run]) -> Self {
+        let mut pitches = Self { closest: f64::INFINITY, widest: 0.0 };
+        for pair in runs.windows(2) {
+            let (last, run) = (&pair[0], &pair[1]);
+            let ratio = last.advance(run);
+            if last.block == run.block && last.new_line(run) && ratio > 0.0 {
+                pitches.closest = pitches.closest.min(ratio);
+                pitches.widest = pitches.widest.max(ratio);
+            }
+        }
+        pitches
+    }
+
+    /// Whether a line that starts the given distance below the last one
+    /// starts a new paragraph.
+    ///
+    /// Paragraphs are spaced further apart than the lines within them. If
+    /// the document has lines at different distances, a line that is
+    /// noticeably further away than the closest ones starts a new paragraph.
+    /// Otherwise, there is no way to tell whether all lines belong to the same
+    /// paragraph or each one to its own, and a fixed threshold decides. Lines
+    /// that move up, like at the start of a page or column, continue the
+    /// paragraph.
+    fn is_par_break(&self, ratio: f64) -> bool {
+        if ratio <= 0.0 {
+            false
+        } else if self.widest - self.closest > PAR_BREAK_TOLERANCE {
+            ratio - self.closest > PAR_BREAK_TOLERANCE
+        } else {
+            ratio > PAR_BREAK_DEFAULT
+        }
+    }
+}
+
+/// How much further apart than the closest lines, in multiples of the font
+/// size, two lines must be to belong to different paragraphs.
+const PAR_BREAK_TOLERANCE: f64 = 0.1;
+
+/// How far apart, in multiples of the font size, two lines must be to belong
+/// to different paragraphs when all lines are equally far apart.
+const PAR_BREAK_DEFAULT: f64 = 1.6;
+
+/// Whether text in the element is separated from the text around it.
+fn is_block(elem: &Content) -> bool {
+    matches!(elem.func().name(), "heading" | "figure")
+}
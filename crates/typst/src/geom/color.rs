@@ -1,12 +1,13 @@
-use ecow::{eco_format, EcoString};
 use std::str::FromStr;
 
+use ecow::{eco_format, EcoString};
+
 use super::*;
 use crate::diag::bail;
 use crate::eval::{cast, Array, Cast};
 
 /// A color in a dynamic format.
-#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+#[derive(Clone, Eq, PartialEq, Hash)]
 pub enum Color {
     /// An 8-bit luma color.
     Luma(LumaColor),
@@ -14,6 +15,8 @@ pub enum Color {
     Rgba(RgbaColor),
     /// An 8-bit CMYK color.
     Cmyk(CmykColor),
+    /// A tint of a spot color.
+    Spot(SpotColor),
 }
 
 impl Color {
@@ -37,38 +40,42 @@ impl Color {
     pub const LIME: Self = Self::Rgba(RgbaColor::new(0x01, 0xFF, 0x70, 0xFF));
 
     /// Convert this color to RGBA.
-    pub fn to_rgba(self) -> RgbaColor {
+    pub fn to_rgba(&self) -> RgbaColor {
         match self {
             Self::Luma(luma) => luma.to_rgba(),
-            Self::Rgba(rgba) => rgba,
+            Self::Rgba(rgba) => *rgba,
             Self::Cmyk(cmyk) => cmyk.to_rgba(),
+            Self::Spot(spot) => spot.to_rgba(),
         }
     }
 
     /// Lighten this color by the given factor.
-    pub fn lighten(self, factor: Ratio) -> Self {
+    pub fn lighten(&self, factor: Ratio) -> Self {
         match self {
             Self::Luma(luma) => Self::Luma(luma.lighten(factor)),
             Self::Rgba(rgba) => Self::Rgba(rgba.lighten(factor)),
             Self::Cmyk(cmyk) => Self::Cmyk(cmyk.lighten(factor)),
+            Self::Spot(spot) => Self::Spot(spot.lighten(factor)),
         }
     }
 
     /// Darken this color by the given factor.
-    pub fn darken(self, factor: Ratio) -> Self {
+    pub fn darken(&self, factor: Ratio) -> Self {
         match self {
             Self::Luma(luma) => Self::Luma(luma.darken(factor)),
             Self::Rgba(rgba) => Self::Rgba(rgba.darken(factor)),
             Self::Cmyk(cmyk) => Self::Cmyk(cmyk.darken(factor)),
+            Self::Spot(spot) => Self::Spot(spot.darken(factor)),
         }
     }
 
     /// Negate this color.
-    pub fn negate(self) -> Self {
+    pub fn negate(&self) -> Self {
         match self {
             Self::Luma(luma) => Self::Luma(luma.negate()),
             Self::Rgba(rgba) => Self::Rgba(rgba.negate()),
             Self::Cmyk(cmyk) => Self::Cmyk(cmyk.negate()),
+            Self::Spot(spot) => Self::Spot(spot.negate()),
         }
     }
 
@@ -104,6 +111,7 @@ impl Debug for Color {
             Self::Luma(c) => Debug::fmt(c, f),
             Self::Rgba(c) => Debug::fmt(c, f),
             Self::Cmyk(c) => Debug::fmt(c, f),
+            Self::Spot(c) => Debug::fmt(c, f),
         }
     }
 }
@@ -111,6 +119,13 @@ impl Debug for Color {
 /// A color with a weight.
 pub struct WeightedColor(Color, f32);
 
+impl WeightedColor {
+    /// Create a new weighted color.
+    pub const fn new(color: Color, weight: f32) -> Self {
+        Self(color, weight)
+    }
+}
+
 cast! {
     WeightedColor,
     v: Color => Self(v, 1.0),
@@ -465,6 +480,116 @@ cast! {
     self => Value::Color(self.into()),
 }
 
+/// A tint of a spot color.
+///
+/// A spot color is a named ink that is printed on its own plate instead of
+/// being mixed from the process colors. Devices without the ink show the
+/// alternate color instead.
+#[derive(Clone, Eq, PartialEq, Hash)]
+pub struct SpotColor {
+    /// The name of the ink.
+    pub name: EcoString,
+    /// The color that approximates the ink at full strength.
+    pub alternate: SpotAlternate,
+    /// How much of the ink is applied, from none to full strength.
+    pub tint: u8,
+}
+
+/// The color that approximates a spot color on devices without its ink.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum SpotAlternate {
+    /// An RGB approximation.
+    Rgb(RgbaColor),
+    /// A CMYK approximation.
+    Cmyk(CmykColor),
+}
+
+impl SpotColor {
+    /// Construct a new tint of a spot color.
+    pub fn new(name: EcoString, alternate: SpotAlternate, tint: u8) -> Self {
+        Self { name, alternate, tint }
+    }
+
+    /// Approximate this tint in CMYK by scaling the alternate color, if the
+    /// alternate is a CMYK color.
+    pub fn to_cmyk(&self) -> Option<CmykColor> {
+        let SpotAlternate::Cmyk(cmyk) = self.alternate else { return None };
+        let scale = |c: u8| round_u8(c as f64 * self.tint as f64 / 255.0);
+        Some(CmykColor::new(scale(cmyk.c), scale(cmyk.m), scale(cmyk.y), scale(cmyk.k)))
+    }
+
+    /// Approximate this tint in RGBA by mixing the alternate color with white.
+    pub fn to_rgba(&self) -> RgbaColor {
+        match self.alternate {
+            SpotAlternate::Rgb(rgba) => {
+                rgba.lighten(Ratio::new(1.0 - self.tint as f64 / 255.0))
+            }
+            SpotAlternate::Cmyk(_) => self.to_cmyk().unwrap().to_rgba(),
+        }
+    }
+
+    /// Lighten this color by reducing its tint.
+    pub fn lighten(&self, factor: Ratio) -> Self {
+        let dec = round_u8(self.tint as f64 * factor.get());
+        self.with_tint(self.tint.saturating_sub(dec))
+    }
+
+    /// Darken this color by increasing its tint.
+    pub fn darken(&self, factor: Ratio) -> Self {
+        let inc = round_u8((u8::MAX - self.tint) as f64 * factor.get());
+        self.with_tint(self.tint.saturating_add(inc))
+    }
+
+    /// Negate this color by inverting its tint.
+    pub fn negate(&self) -> Self {
+        self.with_tint(u8::MAX - self.tint)
+    }
+
+    /// This ink with a different tint.
+    pub fn with_tint(&self, tint: u8) -> Self {
+        Self { tint, ..self.clone() }
+    }
+}
+
+cast! {
+    SpotAlternate,
+    self => match self {
+        Self::Rgb(rgba) => Value::Color(rgba.into()),
+        Self::Cmyk(cmyk) => Value::Color(cmyk.into()),
+    },
+    v: Color => match v {
+        Color::Luma(luma) => Self::Rgb(luma.to_rgba()),
+        Color::Rgba(rgba) => Self::Rgb(rgba),
+        Color::Cmyk(cmyk) => Self::Cmyk(cmyk),
+        Color::Spot(_) => bail!("alternate of spot color must be an rgb or cmyk color"),
+    },
+}
+
+impl Debug for SpotColor {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "color.spot({:?}, ", self.name)?;
+        match self.alternate {
+            SpotAlternate::Rgb(rgba) => Debug::fmt(&rgba, f)?,
+            SpotAlternate::Cmyk(cmyk) => Debug::fmt(&cmyk, f)?,
+        }
+        if self.tint != u8::MAX {
+            write!(f, ", tint: {:.1}%", 100.0 * (self.tint as f64 / 255.0))?;
+        }
+        f.write_str(")")
+    }
+}
+
+impl From<SpotColor> for Color {
+    fn from(spot: SpotColor) -> Self {
+        Self::Spot(spot)
+    }
+}
+
+cast! {
+    SpotColor,
+    self => Value::Color(self.into()),
+}
+
 /// Convert to the closest u8.
 fn round_u8(value: f64) -> u8 {
     value.round() as u8
@@ -0,0 +1,380 @@
+use std::sync::Arc;
+
+use super::*;
+use crate::eval::IntoValue;
+
+/// A color gradient.
+///
+/// Gradients are defined relative to the bounding box of the item they are
+/// applied to: The bounding box of a shape or the bounding box of a run of
+/// text.
+#[derive(Clone, Eq, PartialEq, Hash)]
+pub enum Gradient {
+    /// A gradient that changes color along an axis.
+    Linear(Arc<LinearGradient>),
+    /// A gradient that changes color with the distance from a center point.
+    Radial(Arc<RadialGradient>),
+    /// A gradient that changes color with the angle around a center point.
+    Conic(Arc<ConicGradient>),
+}
+
+/// A gradient that changes color along an axis.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct LinearGradient {
+    /// The color stops, sorted by offset.
+    pub stops: Vec<(Color, Ratio)>,
+    /// The direction of the axis, measured clockwise from the right.
+    pub angle: Angle,
+    /// The color space to interpolate in.
+    pub space: ColorSpace,
+}
+
+/// A gradient that changes color with the distance from a center point.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct RadialGradient {
+    /// The color stops, sorted by offset.
+    pub stops: Vec<(Color, Ratio)>,
+    /// The center point, relative to the bounding box.
+    pub center: Axes<Ratio>,
+    /// The radius at which the last stop is reached, relative to the bounding
+    /// box.
+    pub radius: Ratio,
+    /// The color space to interpolate in.
+    pub space: ColorSpace,
+}
+
+/// A gradient that changes color with the angle around a center point.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ConicGradient {
+    /// The color stops, sorted by offset.
+    pub stops: Vec<(Color, Ratio)>,
+    /// The center point, relative to the bounding box.
+    pub center: Axes<Ratio>,
+    /// The angle at which the first stop is placed, measured clockwise from
+    /// the right.
+    pub angle: Angle,
+    /// The color space to interpolate in.
+    pub space: ColorSpace,
+}
+
+impl Gradient {
+    /// Create a new linear gradient.
+    pub fn linear(
+        stops: Vec<GradientStop>,
+        angle: Angle,
+        space: ColorSpace,
+    ) -> StrResult<Self> {
+        let stops = process_stops(stops)?;
+        Ok(Self::Linear(Arc::new(LinearGradient { stops, angle, space })))
+    }
+
+    /// Create a new radial gradient.
+    pub fn radial(
+        stops: Vec<GradientStop>,
+        center: Axes<Ratio>,
+        radius: Ratio,
+        space: ColorSpace,
+    ) -> StrResult<Self> {
+        if radius.get() <= 0.0 {
+            bail!("radius must be positive");
+        }
+
+        let stops = process_stops(stops)?;
+        Ok(Self::Radial(Arc::new(RadialGradient { stops, center, radius, space })))
+    }
+
+    /// Create a new conic gradient.
+    pub fn conic(
+        stops: Vec<GradientStop>,
+        center: Axes<Ratio>,
+        angle: Angle,
+        space: ColorSpace,
+    ) -> StrResult<Self> {
+        let stops = process_stops(stops)?;
+        Ok(Self::Conic(Arc::new(ConicGradient { stops, center, angle, space })))
+    }
+
+    /// The color stops of the gradient, sorted by offset.
+    pub fn stops(&self) -> &[(Color, Ratio)] {
+        match self {
+            Self::Linear(linear) => &linear.stops,
+            Self::Radial(radial) => &radial.stops,
+            Self::Conic(conic) => &conic.stops,
+        }
+    }
+
+    /// The color space the gradient interpolates in.
+    pub fn space(&self) -> ColorSpace {
+        match self {
+            Self::Linear(linear) => linear.space,
+            Self::Radial(radial) => radial.space,
+            Self::Conic(conic) => conic.space,
+        }
+    }
+
+    /// Sample the gradient at an offset between `0.0` and `1.0`.
+    ///
+    /// Offsets outside of this range are clamped to the first or last stop.
+    pub fn sample(&self, t: f64) -> Color {
+        let stops = self.stops();
+        let t = t.clamp(0.0, 1.0);
+
+        let next = stops.iter().position(|&(_, offset)| offset.get() >= t);
+        let (i, (color, offset)) = match next {
+            Some(0) => return stops[0].0.clone(),
+            Some(i) => (i, &stops[i]),
+            None => return stops[stops.len() - 1].0.clone(),
+        };
+
+        let (prev_color, prev_offset) = &stops[i - 1];
+        let span = offset.get() - prev_offset.get();
+        if span <= 0.0 {
+            return color.clone();
+        }
+
+        let w = (t - prev_offset.get()) / span;
+        interpolate(prev_color, color, w, self.space())
+    }
+
+    /// Approximate the gradient with stops between which the color can be
+    /// interpolated linearly in sRGB, as done by most output formats.
+    ///
+    /// The returned stops always start at `0%` and end at `100%`.
+    pub fn srgb_stops(&self) -> Vec<(Color, Ratio)> {
+        let stops = self.stops();
+        let segments = match self.space() {
+            ColorSpace::Srgb => 1,
+            ColorSpace::Oklab => SEGMENTS_PER_STOP,
+        };
+
+        let first = &stops[0];
+        let last = &stops[stops.len() - 1];
+        let mut out = vec![];
+        if first.1 > Ratio::zero() {
+            out.push((first.0.clone(), Ratio::zero()));
+        }
+
+        for window in stops.windows(2) {
+            let ((c0, a), (c1, b)) = (&window[0], &window[1]);
+            let (a, b) = (*a, *b);
+            out.push((c0.clone(), a));
+            if b > a {
+                for i in 1..segments {
+                    let w = i as f64 / segments as f64;
+                    let offset = Ratio::new(a.get() + (b.get() - a.get()) * w);
+                    out.push((interpolate(c0, c1, w, self.space()), offset));
+                }
+            }
+        }
+
+        out.push(last.clone());
+        if last.1 < Ratio::one() {
+            out.push((last.0.clone(), Ratio::one()));
+        }
+
+        out
+    }
+
+    /// Sample the gradient at a point inside of a bounding box of the given
+    /// size. The point is relative to the top-left corner of the box.
+    pub fn sample_at(&self, point: Point, size: Size) -> Color {
+        let t = match self {
+            Self::Linear(linear) => {
+                let (start, end) = linear.axis(size);
+                let axis = end - start;
+                let len = axis.x.to_pt().powi(2) + axis.y.to_pt().powi(2);
+                if len <= 0.0 {
+                    0.0
+                } else {
+                    let rel = point - start;
+                    (rel.x.to_pt() * axis.x.to_pt() + rel.y.to_pt() * axis.y.to_pt())
+                        / len
+                }
+            }
+            Self::Radial(radial) => {
+                let (u, v) = unit(point, size);
+                let dx = u - radial.center.x.get();
+                let dy = v - radial.center.y.get();
+                dx.hypot(dy) / radial.radius.get()
+            }
+            Self::Conic(conic) => {
+                let (u, v) = unit(point, size);
+                let dx = u - conic.center.x.get();
+                let dy = v - conic.center.y.get();
+                let angle = dy.atan2(dx) - conic.angle.to_rad();
+                angle.rem_euclid(2.0 * PI) / (2.0 * PI)
+            }
+        };
+
+        self.sample(t)
+    }
+}
+
+impl LinearGradient {
+    /// The start and end point of the gradient's axis in a bounding box of the
+    /// given size.
+    ///
+    /// The axis passes through the center of the box and is just long enough
+    /// for the first and last stop to touch the box's corners.
+    pub fn axis(&self, size: Size) -> (Point, Point) {
+        let (sin, cos) = (self.angle.sin(), self.angle.cos());
+        let len = size.x.to_pt() * cos.abs() + size.y.to_pt() * sin.abs();
+        let center = size.to_point() / 2.0;
+        let half = Point::new(Abs::pt(cos * len / 2.0), Abs::pt(sin * len / 2.0));
+        (center - half, center + half)
+    }
+}
+
+/// How many linearly interpolated segments to use between two stops when
+/// approximating a gradient in a non-linear color space.
+const SEGMENTS_PER_STOP: usize = 16;
+
+/// Interpolate between two colors in the given color space.
+fn interpolate(c0: &Color, c1: &Color, w: f64, space: ColorSpace) -> Color {
+    let w = w as f32;
+    let weighted =
+        [WeightedColor::new(c0.clone(), 1.0 - w), WeightedColor::new(c1.clone(), w)];
+    Color::mix(weighted, space).unwrap_or_else(|_| c1.clone())
+}
+
+/// Map a point in a bounding box into the unit square.
+fn unit(point: Point, size: Size) -> (f64, f64) {
+    let ratio = |v: Abs, whole: Abs| {
+        if whole.to_pt() > 0.0 {
+            v.to_pt() / whole.to_pt()
+        } else {
+            0.0
+        }
+    };
+    (ratio(point.x, size.x), ratio(point.y, size.y))
+}
+
+/// Validate and complete the color stops of a gradient.
+///
+/// If no stop has an explicit offset, the stops are distributed evenly.
+fn process_stops(stops: Vec<GradientStop>) -> StrResult<Vec<(Color, Ratio)>> {
+    if stops.len() < 2 {
+        bail!("a gradient must have at least two stops");
+    }
+
+    let explicit = stops.iter().filter(|stop| stop.offset.is_some()).count();
+    if explicit != 0 && explicit != stops.len() {
+        bail!("either all stops must have an offset or none of them can");
+    }
+
+    let count = stops.len();
+    let mut last = Ratio::zero();
+    let mut out = Vec::with_capacity(count);
+    for (i, GradientStop { color, offset }) in stops.into_iter().enumerate() {
+        let offset = offset.unwrap_or_else(|| Ratio::new(i as f64 / (count - 1) as f64));
+        if !(0.0..=1.0).contains(&offset.get()) {
+            bail!("offset must be between 0% and 100%");
+        }
+        if offset < last {
+            bail!("offsets must be in monotonic order");
+        }
+        last = offset;
+        out.push((color, offset));
+    }
+
+    Ok(out)
+}
+
+impl Debug for Gradient {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let (name, stops, space) = match self {
+            Self::Linear(linear) => ("linear", &linear.stops, linear.space),
+            Self::Radial(radial) => ("radial", &radial.stops, radial.space),
+            Self::Conic(conic) => ("conic", &conic.stops, conic.space),
+        };
+
+        write!(f, "gradient.{name}(")?;
+        for (color, offset) in stops {
+            write!(f, "({color:?}, {offset:?}), ")?;
+        }
+
+        match self {
+            Self::Linear(linear) => write!(f, "angle: {:?}", linear.angle)?,
+            Self::Radial(radial) => write!(
+                f,
+                "center: ({:?}, {:?}), radius: {:?}",
+                radial.center.x, radial.center.y, radial.radius
+            )?,
+            Self::Conic(conic) => write!(
+                f,
+                "center: ({:?}, {:?}), angle: {:?}",
+                conic.center.x, conic.center.y, conic.angle
+            )?,
+        }
+
+        if space != ColorSpace::Oklab {
+            write!(f, ", space: {:?}", space.into_value())?;
+        }
+
+        f.write_str(")")
+    }
+}
+
+cast! {
+    type Gradient: "gradient",
+}
+
+/// A color stop of a gradient with an optional offset.
+pub struct GradientStop {
+    /// The color of the stop.
+    pub color: Color,
+    /// Where the stop is placed along the gradient.
+    pub offset: Option<Ratio>,
+}
+
+cast! {
+    GradientStop,
+    self => match self.offset {
+        Some(offset) => array![self.color, offset].into_value(),
+        None => self.color.into_value(),
+    },
+    color: Color => Self { color, offset: None },
+    array: Array => {
+        let mut iter = array.into_iter();
+        match (iter.next(), iter.next(), iter.next()) {
+            (Some(a), Some(b), None) => {
+                Self { color: a.cast()?, offset: Some(b.cast()?) }
+            }
+            _ => bail!("a color stop must contain exactly two entries"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gradient_sample() {
+        let stops = vec![
+            GradientStop { color: Color::BLACK, offset: None },
+            GradientStop { color: Color::WHITE, offset: None },
+        ];
+        let gradient = Gradient::linear(stops, Angle::zero(), ColorSpace::Srgb).unwrap();
+        assert_eq!(gradient.sample(-1.0), Color::BLACK);
+        assert_eq!(gradient.sample(0.0), Color::BLACK);
+        assert_eq!(gradient.sample(1.0), Color::WHITE);
+        assert_eq!(gradient.sample(2.0), Color::WHITE);
+        assert_eq!(gradient.sample(0.5).to_rgba(), RgbaColor::new(128, 128, 128, 255));
+    }
+
+    #[test]
+    fn test_linear_gradient_axis() {
+        let stops = vec![
+            GradientStop { color: Color::BLACK, offset: None },
+            GradientStop { color: Color::WHITE, offset: None },
+        ];
+        let gradient =
+            Gradient::linear(stops, Angle::deg(90.0), ColorSpace::Srgb).unwrap();
+        let size = Size::new(Abs::pt(20.0), Abs::pt(10.0));
+        let top = gradient.sample_at(Point::with_x(Abs::pt(15.0)), size);
+        let bottom = gradient.sample_at(size.to_point(), size);
+        assert_eq!(top, Color::BLACK);
+        assert_eq!(bottom, Color::WHITE);
+    }
+}
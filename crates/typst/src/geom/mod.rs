@@ -12,9 +12,11 @@ mod dir;
 mod ellipse;
 mod em;
 mod fr;
+mod gradient;
 mod length;
 mod paint;
 mod path;
+mod pattern;
 mod point;
 mod ratio;
 mod rel;
@@ -32,16 +34,21 @@ pub use self::align::{Align, GenAlign, HorizontalAlign, VerticalAlign};
 pub use self::angle::{Angle, AngleUnit};
 pub use self::axes::{Axes, Axis};
 pub use self::color::{
-    CmykColor, Color, ColorSpace, LumaColor, RgbaColor, WeightedColor,
+    CmykColor, Color, ColorSpace, LumaColor, RgbaColor, SpotAlternate, SpotColor,
+    WeightedColor,
 };
 pub use self::corners::{Corner, Corners};
 pub use self::dir::Dir;
 pub use self::ellipse::ellipse;
 pub use self::em::Em;
 pub use self::fr::Fr;
+pub use self::gradient::{
+    ConicGradient, Gradient, GradientStop, LinearGradient, RadialGradient,
+};
 pub use self::length::Length;
 pub use self::paint::Paint;
 pub use self::path::{Path, PathItem};
+pub use self::pattern::Pattern;
 pub use self::point::Point;
 pub use self::ratio::Ratio;
 pub use self::rel::Rel;
@@ -5,6 +5,10 @@ use super::*;
 pub enum Paint {
     /// A solid color.
     Solid(Color),
+    /// A gradient.
+    Gradient(Gradient),
+    /// A repeating pattern.
+    Pattern(Pattern),
 }
 
 impl<T: Into<Color>> From<T> for Paint {
@@ -13,10 +17,24 @@ impl<T: Into<Color>> From<T> for Paint {
     }
 }
 
+impl From<Gradient> for Paint {
+    fn from(gradient: Gradient) -> Self {
+        Self::Gradient(gradient)
+    }
+}
+
+impl From<Pattern> for Paint {
+    fn from(pattern: Pattern) -> Self {
+        Self::Pattern(pattern)
+    }
+}
+
 impl Debug for Paint {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         match self {
             Self::Solid(color) => color.fmt(f),
+            Self::Gradient(gradient) => gradient.fmt(f),
+            Self::Pattern(pattern) => pattern.fmt(f),
         }
     }
 }
@@ -25,6 +43,10 @@ cast! {
     Paint,
     self => match self {
         Self::Solid(color) => Value::Color(color),
+        Self::Gradient(gradient) => Value::dynamic(gradient),
+        Self::Pattern(pattern) => Value::dynamic(pattern),
     },
     color: Color => Self::Solid(color),
+    gradient: Gradient => Self::Gradient(gradient),
+    pattern: Pattern => Self::Pattern(pattern),
 }
@@ -0,0 +1,66 @@
+use std::sync::Arc;
+
+use comemo::Prehashed;
+
+use super::*;
+use crate::doc::Frame;
+
+/// A fill that repeats a tile of content.
+///
+/// Like gradients, patterns are defined relative to the bounding box of the
+/// item they are applied to. The first tile is placed at the top-left corner
+/// of the bounding box.
+#[derive(Clone, Eq, PartialEq, Hash)]
+pub struct Pattern(Arc<Repr>);
+
+/// The internal representation of a pattern.
+#[derive(Eq, PartialEq, Hash)]
+struct Repr {
+    /// The laid-out content of a tile.
+    frame: Prehashed<Frame>,
+    /// The size of a tile.
+    size: Size,
+    /// The gap between neighbouring tiles.
+    spacing: Size,
+}
+
+impl Pattern {
+    /// Create a new pattern from a laid-out tile.
+    pub fn new(frame: Frame, size: Size, spacing: Size) -> Self {
+        Self(Arc::new(Repr { frame: Prehashed::new(frame), size, spacing }))
+    }
+
+    /// The laid-out content of a tile.
+    pub fn frame(&self) -> &Frame {
+        &self.0.frame
+    }
+
+    /// The size of a tile.
+    pub fn size(&self) -> Size {
+        self.0.size
+    }
+
+    /// The gap between neighbouring tiles.
+    pub fn spacing(&self) -> Size {
+        self.0.spacing
+    }
+
+    /// The distance between the origins of neighbouring tiles.
+    pub fn cell(&self) -> Size {
+        self.0.size + self.0.spacing
+    }
+}
+
+impl Debug for Pattern {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "pattern(size: ({:?}, {:?}), spacing: ({:?}, {:?}))",
+            self.0.size.x, self.0.size.y, self.0.spacing.x, self.0.spacing.y
+        )
+    }
+}
+
+cast! {
+    type Pattern: "pattern",
+}
@@ -32,4 +32,34 @@ impl Geometry {
     pub fn stroked(self, stroke: Stroke) -> Shape {
         Shape { geometry: self, fill: None, stroke: Some(stroke) }
     }
+
+    /// The bounding box of the geometry as its top-left corner and its size.
+    ///
+    /// For paths, the control points are included in the box.
+    pub fn bbox(&self) -> (Point, Size) {
+        let (min, max) = match self {
+            Self::Line(target) => (target.min(Point::zero()), target.max(Point::zero())),
+            Self::Rect(size) => (Point::zero(), size.to_point()),
+            Self::Path(path) => {
+                let mut min = Point::splat(Abs::inf());
+                let mut max = Point::splat(-Abs::inf());
+                for item in &path.0 {
+                    let points = match item {
+                        PathItem::MoveTo(p) | PathItem::LineTo(p) => vec![*p],
+                        PathItem::CubicTo(p1, p2, p3) => vec![*p1, *p2, *p3],
+                        PathItem::ClosePath => vec![],
+                    };
+                    for p in points {
+                        min = min.min(p);
+                        max = max.max(p);
+                    }
+                }
+                if min.x > max.x {
+                    return (Point::zero(), Size::zero());
+                }
+                (min, max)
+            }
+        };
+        (min, (max - min).to_size())
+    }
 }
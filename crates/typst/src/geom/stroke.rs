@@ -216,6 +216,14 @@ cast! {
         paint: Smart::Custom(color.into()),
         ..Default::default()
     },
+    gradient: Gradient => Self {
+        paint: Smart::Custom(gradient.into()),
+        ..Default::default()
+    },
+    pattern: Pattern => Self {
+        paint: Smart::Custom(pattern.into()),
+        ..Default::default()
+    },
     mut dict: Dict => {
         fn take<T: FromValue>(dict: &mut Dict, key: &str) -> StrResult<Smart<T>> {
             Ok(dict.take(key).ok().map(T::from_value)
@@ -1,6 +1,6 @@
 use std::num::NonZeroUsize;
 
-use ecow::EcoString;
+use ecow::{eco_format, EcoString};
 
 use crate::doc::{Destination, Frame, FrameItem, Meta, Position};
 use crate::geom::{Geometry, Point, Size};
@@ -48,6 +48,9 @@ pub fn jump_from_click(
                             .get_or_insert_with(|| Introspector::new(frames))
                             .position(*loc),
                     ),
+                    Destination::Remote(dest) => {
+                        Jump::Url(eco_format!("{}#{}", dest.file, dest.name))
+                    }
                 });
             }
         }
@@ -17,7 +17,7 @@
 //!   per page with items at fixed positions.
 //! - **Exporting:**
 //!   These frames can finally be exported into an output format (currently
-//!   supported are [PDF] and [raster images]).
+//!   supported are [PDF], [raster images], and [SVG]).
 //!
 //! [tokens]: syntax::SyntaxKind
 //! [parsed]: syntax::parse
@@ -31,6 +31,7 @@
 //! [frame]: doc::Frame
 //! [PDF]: export::pdf
 //! [raster images]: export::render
+//! [SVG]: export::svg
 
 #![recursion_limit = "1000"]
 #![allow(clippy::comparison_chain)]
@@ -86,6 +87,49 @@ pub fn compile(world: &dyn World, tracer: &mut Tracer) -> SourceResult<Document>
     model::typeset(world, tracer, &module.content())
 }
 
+/// Compile a source file into an HTML document.
+///
+/// Unlike the other export formats, HTML is generated from the document's
+/// content rather than from its laid-out pages.
+#[tracing::instrument(skip_all)]
+pub fn compile_html(world: &dyn World, tracer: &mut Tracer) -> SourceResult<String> {
+    let route = Route::default();
+    let world = world.track();
+    let mut tracer = tracer.track_mut();
+
+    // Evaluate the source file into a module.
+    let module = eval::eval(
+        world,
+        route.track(),
+        TrackedMut::reborrow_mut(&mut tracer),
+        &world.main(),
+    )?;
+
+    // Convert it.
+    model::typeset_html(world, tracer, &module.content())
+}
+
+/// Compile a source file into a Markdown document.
+///
+/// Like HTML, Markdown is generated from the document's content.
+#[tracing::instrument(skip_all)]
+pub fn compile_markdown(world: &dyn World, tracer: &mut Tracer) -> SourceResult<String> {
+    let route = Route::default();
+    let world = world.track();
+    let mut tracer = tracer.track_mut();
+
+    // Evaluate the source file into a module.
+    let module = eval::eval(
+        world,
+        route.track(),
+        TrackedMut::reborrow_mut(&mut tracer),
+        &world.main(),
+    )?;
+
+    // Convert it.
+    model::typeset_markdown(world, tracer, &module.content())
+}
+
 /// The environment in which typesetting occurs.
 ///
 /// All loading functions (`main`, `source`, `file`, `font`) should perform
@@ -5,10 +5,11 @@ use std::ops::{Add, AddAssign};
 
 use comemo::Prehashed;
 use ecow::{eco_format, EcoString, EcoVec};
+use serde::{Serialize, Serializer};
 
 use super::{
     element, Behave, Behaviour, ElemFunc, Element, Guard, Label, Locatable, Location,
-    Recipe, Selector, Style, Styles, Synthesize,
+    Recipe, Selector, Style, Styles, Synthesize, Tagged,
 };
 use crate::diag::{SourceResult, StrResult};
 use crate::doc::Meta;
@@ -347,6 +348,7 @@ impl Content {
     pub fn needs_preparation(&self) -> bool {
         (self.can::<dyn Locatable>()
             || self.can::<dyn Synthesize>()
+            || self.can::<dyn Tagged>()
             || self.label().is_some())
             && !self.is_prepared()
     }
@@ -463,6 +465,15 @@ impl Debug for Content {
     }
 }
 
+impl Serialize for Content {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(
+            std::iter::once(("func", self.func().name().into_value()))
+                .chain(self.fields().map(|(key, value)| (key.as_str(), value))),
+        )
+    }
+}
+
 impl Default for Content {
     fn default() -> Self {
         Self::empty()
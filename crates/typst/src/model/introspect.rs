@@ -80,6 +80,9 @@ pub struct Locator<'a> {
     /// covariant over the constraint. If it becomes invariant, we're in for a
     /// world of lifetime pain.
     outer: Option<Tracked<'a, Self, <Locator<'static> as Validate>::Constraint>>,
+    /// The locations of laid-out elements by their hash, in the order in
+    /// which they were laid out.
+    laid_out: HashMap<u128, Vec<Location>>,
 }
 
 impl<'a> Locator<'a> {
@@ -93,6 +96,42 @@ impl<'a> Locator<'a> {
         Self { outer: Some(outer), ..Default::default() }
     }
 
+    /// Create a locator that hands out the locations that elements have in a
+    /// laid-out document.
+    ///
+    /// Exports that realize the document's content again instead of reading
+    /// its pages, like HTML export, use it so that introspection, like
+    /// counters, works the same as for the pages. Elements with the same hash
+    /// are matched in the order in which they were laid out. Copies that were
+    /// laid out, but didn't end up on a page, like those laid out for
+    /// measurement, are skipped.
+    pub fn laid_out(frames: &[Frame]) -> Self {
+        fn collect(frame: &Frame, laid_out: &mut HashMap<u128, Vec<Location>>) {
+            for (_, item) in frame.items() {
+                match item {
+                    FrameItem::Group(group) => collect(&group.frame, laid_out),
+                    FrameItem::Meta(Meta::Elem(elem), _) => {
+                        let loc = elem.location().unwrap();
+                        laid_out.entry(loc.hash).or_default().push(loc);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let mut laid_out = HashMap::new();
+        for frame in frames {
+            collect(frame, &mut laid_out);
+        }
+
+        for locations in laid_out.values_mut() {
+            locations.sort_by_key(|loc| loc.disambiguator);
+            locations.dedup();
+        }
+
+        Self { laid_out, ..Default::default() }
+    }
+
     /// Start tracking this locator.
     ///
     /// In comparison to [`Track::track`], this method skips this chain link
@@ -112,6 +151,17 @@ impl<'a> Locator<'a> {
         // Bump the next disambiguator up by one.
         self.hashes.borrow_mut().insert(hash, disambiguator + 1);
 
+        // Reuse the location of the matching laid-out element. Elements
+        // beyond those get locations that no laid-out element has.
+        if let Some(locations) = self.laid_out.get(&hash) {
+            if let Some(&location) = locations.get(disambiguator) {
+                return location;
+            }
+            let last = locations.last().map_or(0, |loc| loc.disambiguator + 1);
+            let disambiguator = last + disambiguator - locations.len();
+            return Location { hash, disambiguator, variant: 0 };
+        }
+
         // Create the location in its default variant.
         Location { hash, disambiguator, variant: 0 }
     }
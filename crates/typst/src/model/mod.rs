@@ -16,7 +16,8 @@ pub use self::element::{Construct, ElemFunc, Element, NativeElemFunc, Set};
 pub use self::introspect::{Introspector, Location, Locator};
 pub use self::label::{Label, Unlabellable};
 pub use self::realize::{
-    applicable, realize, Behave, Behaviour, Finalize, Guard, Locatable, Show, Synthesize,
+    applicable, realize, realize_recipes, Behave, Behaviour, Finalize, Guard, Locatable,
+    Show, Synthesize, Tagged,
 };
 pub use self::selector::{LocatableSelector, Selector, ShowableSelector};
 pub use self::styles::{
@@ -98,6 +99,68 @@ pub fn typeset(
     Ok(document)
 }
 
+/// Typeset content into an HTML document.
+///
+/// The content is laid out into pages first so that introspections like
+/// counters and references resolve just like in the paged document.
+#[tracing::instrument(skip(world, tracer, content))]
+pub fn typeset_html(
+    world: Tracked<dyn World + '_>,
+    tracer: TrackedMut<Tracer>,
+    content: &Content,
+) -> SourceResult<String> {
+    let html = world.library().items.html;
+    typeset_markup(world, tracer, content, html)
+}
+
+/// Typeset content into a Markdown document.
+///
+/// Like for HTML, the content is laid out into pages first.
+#[tracing::instrument(skip(world, tracer, content))]
+pub fn typeset_markdown(
+    world: Tracked<dyn World + '_>,
+    tracer: TrackedMut<Tracer>,
+    content: &Content,
+) -> SourceResult<String> {
+    let markdown = world.library().items.markdown;
+    typeset_markup(world, tracer, content, markdown)
+}
+
+/// Lay out content into pages and then convert it with a markup export
+/// function, which can introspect the laid-out document.
+fn typeset_markup(
+    world: Tracked<dyn World + '_>,
+    mut tracer: TrackedMut<Tracer>,
+    content: &Content,
+    export: fn(&mut Vt, &Content, StyleChain, &Document) -> SourceResult<String>,
+) -> SourceResult<String> {
+    let document = typeset(world, TrackedMut::reborrow_mut(&mut tracer), content)?;
+
+    tracing::info!("Starting markup export");
+
+    let library = world.library();
+    let styles = StyleChain::new(&library.styles);
+    let introspector = Introspector::new(&document.pages);
+    let mut delayed = DelayedErrors::default();
+    let mut locator = Locator::laid_out(&document.pages);
+    let mut vt = Vt {
+        world,
+        tracer,
+        locator: &mut locator,
+        introspector: introspector.track(),
+        delayed: delayed.track_mut(),
+    };
+
+    let output = export(&mut vt, content, styles, &document)?;
+
+    // Promote delayed errors.
+    if !delayed.0.is_empty() {
+        return Err(Box::new(delayed.0));
+    }
+
+    Ok(output)
+}
+
 /// A virtual typesetter.
 ///
 /// Holds the state needed to [typeset] content.
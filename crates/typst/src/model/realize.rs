@@ -36,7 +36,8 @@ pub fn realize(
     // Pre-process.
     if target.needs_preparation() {
         let mut elem = target.clone();
-        if target.can::<dyn Locatable>() || target.label().is_some() {
+        let tagged = target.can::<dyn Tagged>() && (item!(tagged))(styles);
+        if target.can::<dyn Locatable>() || tagged || target.label().is_some() {
             let location = vt.locator.locate(hash128(target));
             elem.set_location(location);
         }
@@ -59,21 +60,8 @@ pub fn realize(
         return Ok(Some(elem));
     }
 
-    // Find out how many recipes there are.
-    let mut n = styles.recipes().count();
-
     // Find an applicable recipe.
-    let mut realized = None;
-    for recipe in styles.recipes() {
-        let guard = Guard::Nth(n);
-        if recipe.applicable(target) && !target.is_guarded(guard) {
-            if let Some(content) = try_apply(vt, target, recipe, guard)? {
-                realized = Some(content);
-                break;
-            }
-        }
-        n -= 1;
-    }
+    let mut realized = realize_recipes(vt, target, styles)?;
 
     // Realize if there was no matching recipe.
     if let Some(showable) = target.with::<dyn Show>() {
@@ -95,6 +83,33 @@ pub fn realize(
     Ok(realized)
 }
 
+/// Apply only the user-defined show rules in the given style chain to a
+/// target, leaving out its base recipe.
+///
+/// This is used by exporters that map some elements to their own output
+/// instead of realizing them with their base recipe.
+pub fn realize_recipes(
+    vt: &mut Vt,
+    target: &Content,
+    styles: StyleChain,
+) -> SourceResult<Option<Content>> {
+    // Find out how many recipes there are.
+    let mut n = styles.recipes().count();
+
+    // Apply the first recipe that matches.
+    for recipe in styles.recipes() {
+        let guard = Guard::Nth(n);
+        if recipe.applicable(target) && !target.is_guarded(guard) {
+            if let Some(content) = try_apply(vt, target, recipe, guard)? {
+                return Ok(Some(content));
+            }
+        }
+        n -= 1;
+    }
+
+    Ok(None)
+}
+
 /// Try to apply a recipe to the target.
 fn try_apply(
     vt: &mut Vt,
@@ -168,6 +183,11 @@ fn try_apply(
 /// Makes this element locatable through `vt.locate`.
 pub trait Locatable {}
 
+/// Makes this element locatable in tagged documents, so that exporters can map
+/// its content back to it. Unlike [`Locatable`] elements, tagged elements
+/// can't be queried since they are only located on demand.
+pub trait Tagged {}
+
 /// Synthesize fields on an element. This happens before execution of any show
 /// rule.
 pub trait Synthesize {
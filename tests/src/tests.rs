@@ -20,7 +20,7 @@ use tiny_skia as sk;
 use unscanny::Scanner;
 use walkdir::WalkDir;
 
-use typst::diag::{bail, FileError, FileResult, Severity, StrResult};
+use typst::diag::{bail, FileError, FileResult, Severity, SourceError, StrResult};
 use typst::doc::{Document, Frame, FrameItem, Meta};
 use typst::eval::{eco_format, func, Datetime, Library, NoneValue, Tracer, Value};
 use typst::font::{Font, FontBook};
@@ -350,6 +350,7 @@ fn test(
     let mut line = 0;
     let mut compare_ref = None;
     let mut validate_hints = None;
+    let mut exports = vec![];
     let mut compare_ever = false;
     let mut rng = LinearShift::new();
 
@@ -379,6 +380,12 @@ fn test(
             for line in part.lines() {
                 compare_ref = get_flag_metadata(line, "Ref").or(compare_ref);
                 validate_hints = get_flag_metadata(line, "Hints").or(validate_hints);
+                if let Some(formats) = get_metadata(line, "Export") {
+                    exports = formats
+                        .split_whitespace()
+                        .map(|format| (Export::parse(format), String::new()))
+                        .collect();
+                }
             }
         } else {
             let (part_ok, compare_here, part_frames) = test_part(
@@ -389,6 +396,7 @@ fn test(
                 i,
                 compare_ref.unwrap_or(true),
                 validate_hints.unwrap_or(true),
+                &mut exports,
                 line,
                 &mut rng,
             );
@@ -404,7 +412,7 @@ fn test(
     let document = Document { pages: frames, ..Default::default() };
     if compare_ever {
         if let Some(pdf_path) = pdf_path {
-            let pdf_data = typst::export::pdf(&document);
+            let pdf_data = typst::export::pdf(&document).unwrap();
             fs::create_dir_all(pdf_path.parent().unwrap()).unwrap();
             fs::write(pdf_path, pdf_data).unwrap();
         }
@@ -447,6 +455,27 @@ fn test(
         }
     }
 
+    for (export, actual) in &exports {
+        let ref_path = ref_path.with_extension(export.extension());
+        match fs::read_to_string(&ref_path) {
+            Ok(expected) if expected == *actual => {}
+            _ if args.update => {
+                fs::write(&ref_path, actual).unwrap();
+                updated = true;
+            }
+            Ok(_) => {
+                writeln!(output, "  Does not match reference {export:?} export.")
+                    .unwrap();
+                ok = false;
+            }
+            Err(_) => {
+                writeln!(output, "  Failed to open reference {export:?} export.")
+                    .unwrap();
+                ok = false;
+            }
+        }
+    }
+
     {
         let mut stdout = io::stdout().lock();
         stdout.write_all(name.to_string_lossy().as_bytes()).unwrap();
@@ -456,7 +485,7 @@ fn test(
             writeln!(stdout, " ❌").unwrap();
         }
         if updated {
-            writeln!(stdout, "  Updated reference image.").unwrap();
+            writeln!(stdout, "  Updated reference output.").unwrap();
         }
         if !output.is_empty() {
             stdout.write_all(output.as_bytes()).unwrap();
@@ -492,6 +521,7 @@ fn test_part(
     i: usize,
     compare_ref: bool,
     validate_hints: bool,
+    exports: &mut [(Export, String)],
     line: usize,
     rng: &mut LinearShift,
 ) -> (bool, bool, Vec<Frame>) {
@@ -531,6 +561,31 @@ fn test_part(
         }
     };
 
+    // Exports are compared independently of the reference images.
+    for (export, out) in exports.iter_mut() {
+        match export {
+            Export::Text => {
+                let document = Document { pages: frames.clone(), ..Default::default() };
+                out.push_str(&typst::export::text(&document));
+            }
+            Export::Markdown => {
+                match typst::compile_markdown(world, &mut Tracer::default()) {
+                    Ok(markdown) => out.push_str(&markdown),
+                    Err(errors) => ok &= export_failed(output, i, *export, &errors),
+                }
+            }
+            Export::Html => match typst::compile_html(world, &mut Tracer::default()) {
+                Ok(html) => out.push_str(&number_ids(&html)),
+                Err(errors) => ok &= export_failed(output, i, *export, &errors),
+            },
+            Export::Svg => {
+                for frame in &frames {
+                    out.push_str(&typst::export::svg(frame));
+                }
+            }
+        }
+    }
+
     // Don't retain frames if we don't want to compare with reference images.
     if !compare_ref {
         frames.clear();
@@ -596,6 +651,47 @@ fn test_part(
     (ok, compare_ref, frames)
 }
 
+/// Report the errors of a failed export and return `false`.
+fn export_failed(
+    output: &mut String,
+    i: usize,
+    export: Export,
+    errors: &[SourceError],
+) -> bool {
+    writeln!(output, "  Subtest {i} failed to export {export:?}.").unwrap();
+    for error in errors {
+        writeln!(output, "    {}", error.message).unwrap();
+    }
+    false
+}
+
+/// Number the ids that HTML export derives from hashed locations in the order
+/// they appear in, so that references don't depend on the hashes.
+fn number_ids(html: &str) -> String {
+    let mut ids = vec![];
+    let mut numbered = String::new();
+    let mut s = Scanner::new(html);
+    while !s.done() {
+        numbered.push_str(s.eat_until("loc-"));
+        if !s.eat_if("loc-") {
+            continue;
+        }
+
+        numbered.push_str("loc-");
+        let id = s.eat_while(|c: char| c.is_ascii_hexdigit());
+        if id.is_empty() {
+            continue;
+        }
+
+        let i = ids.iter().position(|&prev| prev == id).unwrap_or_else(|| {
+            ids.push(id);
+            ids.len() - 1
+        });
+        write!(numbered, "{}", i + 1).unwrap();
+    }
+    numbered
+}
+
 fn print_user_output(
     output: &mut String,
     source: &Source,
@@ -621,6 +717,37 @@ fn print_user_output(
         .unwrap();
 }
 
+/// A format besides PNG that the output of a test is compared in, selected
+/// with `// Export: ...` in the header.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Export {
+    Text,
+    Markdown,
+    Html,
+    Svg,
+}
+
+impl Export {
+    fn parse(format: &str) -> Self {
+        match format {
+            "txt" => Self::Text,
+            "md" => Self::Markdown,
+            "html" => Self::Html,
+            "svg" => Self::Svg,
+            _ => panic!("unknown export format: {format}"),
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Text => "txt",
+            Self::Markdown => "md",
+            Self::Html => "html",
+            Self::Svg => "svg",
+        }
+    }
+}
+
 struct TestConfiguration {
     compare_ref: Option<bool>,
     validate_hints: Option<bool>,
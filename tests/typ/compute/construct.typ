@@ -34,6 +34,15 @@
 #test(color.mix((rgb("#aaff00"), 50%), (rgb("#aa00ff"), 50%), space: "srgb"), rgb("#aa8080"))
 #test(color.mix((rgb("#aaff00"), 75%), (rgb("#aa00ff"), 25%), space: "srgb"), rgb("#aabf40"))
 
+---
+// Test spot colors and their tints.
+#let ink = color.spot("Brand Blue", cmyk(100%, 60%, 0%, 10%))
+#test(ink.kind(), color.spot)
+#test(ink.lighten(40%), color.spot("Brand Blue", cmyk(100%, 60%, 0%, 10%), tint: 60%))
+#test(ink.lighten(40%).cmyk(), cmyk(60%, 36%, 0%, 6.3%).cmyk())
+#test(color.spot("Orange", rgb("ff8000"), tint: 0%).hex(), "#ffffff")
+#test(color.spot("Orange", rgb("ff8000")).hex(), "#ff8000")
+
 ---
 // Test gray color conversion.
 // Ref: true
@@ -223,3 +232,11 @@
 ---
 // Error: 26-36 failed to format datetime in the requested format
 #datetime.today().display("[hour]")
+
+---
+// Error: 20-44 alternate of spot color must be an rgb or cmyk color
+#color.spot("Gold", color.spot("Brass", red))
+
+---
+// Error: 12-14 spot color name must not be empty
+#color.spot("", red)
@@ -0,0 +1,43 @@
+// Test gradient construction and methods.
+// Ref: false
+
+---
+// Stops are distributed evenly if no offsets are given.
+#let g = gradient.linear(red, green, blue)
+#test(type(g), "gradient")
+#test(g.stops(), ((red, 0%), (green, 50%), (blue, 100%)))
+#test(gradient.radial((red, 10%), (blue, 80%)).stops(), ((red, 10%), (blue, 80%)))
+
+---
+// Sampling interpolates between the stops.
+#let g = gradient.linear(black, white, space: "srgb")
+#test(g.sample(0%), black)
+#test(g.sample(100%), white)
+#test(g.sample(50%), color.mix(black, white, space: "srgb"))
+#test(gradient.conic(red, blue).sample(50%), color.mix(red, blue))
+#test(gradient.linear((red, 20%), (blue, 40%)).sample(10%), red)
+
+---
+// Gradients can be used as strokes.
+#rect(stroke: gradient.linear(red, blue) + 2pt)
+#text(fill: gradient.linear(red, blue))[Hello]
+
+---
+// Error: 16-21 a gradient must have at least two stops
+#gradient.linear(red)
+
+---
+// Error: 16-40 either all stops must have an offset or none of them can
+#gradient.linear((red, 0%), blue, green)
+
+---
+// Error: 16-41 offsets must be in monotonic order
+#gradient.linear((red, 50%), (blue, 20%))
+
+---
+// Error: 16-39 radius must be positive
+#gradient.radial(red, blue, radius: 0%)
+
+---
+// Error: 17-29 a color stop must contain exactly two entries
+#gradient.linear((red, 0%, 1), blue)
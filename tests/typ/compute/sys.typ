@@ -0,0 +1,13 @@
+// Test the `sys` module.
+// Ref: false
+
+---
+// Without inputs from the command line, the dictionary is empty.
+#test(type(sys.inputs), "dictionary")
+#test(sys.inputs.len(), 0)
+#test(sys.inputs.at("name", default: "Anonymous"), "Anonymous")
+
+---
+// The inputs are read-only.
+// Error: 3-6 cannot mutate a constant: sys
+#(sys.inputs.x = 1)
@@ -0,0 +1,19 @@
+// Test page bleed and printer's marks.
+// Ref: false
+
+---
+#set page(width: 80pt, height: 60pt, bleed: 6pt, fill: aqua)
+#set page(background: rect(width: 100%, height: 100%, fill: teal))
+Bleed
+
+---
+#set page(width: 80pt, height: 60pt, bleed: 3mm, marks: true)
+Marks
+
+---
+#set page(width: 80pt, height: 60pt, marks: true)
+No bleed
+
+---
+// Error: 18-22 page bleed must not be negative
+#set page(bleed: -1pt)
@@ -0,0 +1,17 @@
+// Test MathML in HTML export.
+// Ref: false
+// Export: html
+
+---
+// Inline and block equations with fractions, attachments and roots.
+The sum $sum_(i=0)^n i = (n(n+1))/2$ and the root:
+$ sqrt(x^2 + y^2) < root(3, z) $
+
+---
+// Matrices, cases, accents and text.
+$ mat(1, 2; 3, 4) quad cases(x "if" x > 0, 0 "else") quad hat(a) + arrow(v) $
+
+---
+// Aligned equations over multiple lines and styled letters.
+$ a &= bold(b) + upright(c) \
+    &= cal(A) + bb(R) $
@@ -16,6 +16,25 @@ What's up?
 #set document(author: (123,))
 What's up?
 
+---
+// Ref: false
+#set document(description: "A test", keywords: ("a", "b"))
+#set document(date: datetime(year: 2023, month: 7, day: 1))
+#set document(date: none)
+#set document(custom: (project: "Apollo", build-id: "3"))
+
+---
+// Error: 23-35 invalid metadata key: a b
+#set document(custom: ("a b": "x"))
+
+---
+// Error: 23-29 expected string, found integer
+#set document(custom: (a: 1))
+
+---
+// Error: 21-24 expected datetime, none, or auto, found integer
+#set document(date: 123)
+
 ---
 Hello
 
@@ -43,3 +62,16 @@ Hello
   // Error: 4-15 pagebreaks are not allowed inside of containers
   #pagebreak()
 ]
+
+---
+// Tagging tracks paragraphs, lists and tables without changing the layout.
+// Ref: false
+#set document(tagged: true)
+= Introduction
+A paragraph with a #link("https://typst.app")[link].
+
+- Bullet
++ Numbered
+/ Term: Description
+
+#table(columns: 2, [A], [B])
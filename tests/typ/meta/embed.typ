@@ -0,0 +1,10 @@
+// Test embedding files into PDF export.
+
+---
+// Ref: false
+#pdf.embed("/files/data.csv", description: "Measurements", mime-type: "text/csv")
+#pdf.embed("notes.txt", "Generated on the fly.", annotate: true)
+
+---
+// Error: 11-31 file not found (searched at files/missing.csv)
+#pdf.embed("/files/missing.csv")
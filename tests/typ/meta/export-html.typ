@@ -0,0 +1,32 @@
+// Test HTML export.
+// Ref: false
+// Export: html
+
+---
+#set document(title: "Export")
+#set heading(numbering: "1.")
+= Introduction
+Some _emphasis_, *strong* text, `code` and a #link("https://typst.app")[link].
+
+- One
+- Two
+  + Nested
+
+/ Term: Description
+
+= Results <results>
+As shown in @results, a footnote#footnote[The note.] and an equation:
+$ a^2 + b^2 = c^2 $
+
+#table(columns: 2, [A], [B], [C], [D])
+
+---
+// Elements match their laid-out counterparts even when a copy of them is
+// hidden on the pages.
+#set heading(numbering: "1.")
+#let intro = [= Intro]
+#hide(intro)
+#intro
+See @details.
+
+= Details <details>
@@ -0,0 +1,23 @@
+// Test Markdown export.
+// Ref: false
+// Export: md
+
+---
+= Heading
+Some _emphasis_, *strong* text, `code` and "quotes".
+
+- One
+- Two
+  - Nested
+
++ Three
++ Four
+
+/ Term: Description
+
+---
+#link("https://typst.app")[A link] and a footnote#footnote[The note.].
+
+```rust
+fn main() {}
+```
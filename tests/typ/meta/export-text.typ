@@ -0,0 +1,23 @@
+// Test plain text export.
+// Ref: false
+// Export: txt
+
+---
+= Introduction
+This paragraph is long enough to be broken across several lines of the page
+and hyphenates some of its extraordinarily long words.
+
+A second paragraph.
+
+---
+#set par(leading: 1em)
+A paragraph with wide leading that still spans several lines of the page.
+
+Another paragraph.
+
+---
+#figure(rect[Content], caption: [A figure])
+Text after the figure.
+
+---
+Footnotes#footnote[A note.] are separated from the text.
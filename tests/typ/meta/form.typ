@@ -0,0 +1,19 @@
+// Test interactive form fields.
+
+---
+// Ref: false
+Name: #form.text-field("name", value: "Jane Doe", fill: luma(240)) \
+#form.text-field("comments", multiline: true, width: 100%, height: 3em)
+#form.checkbox("terms", checked: true) I accept the terms \
+#form.radio("size", "s") Small
+#form.radio("size", "m", checked: true) Medium
+#form.radio("size", "l", stroke: blue) Large \
+#form.dropdown("pet", ("cat", "dog"), selected: "dog")
+
+---
+// Error: 1-55 selected option is not one of the options
+#form.dropdown("pet", ("cat", "dog"), selected: "fish")
+
+---
+// Error: 1-26 radio button value must not be `Off`
+#form.radio("size", "Off")
@@ -0,0 +1,10 @@
+// Test embedding content as a frame in HTML export.
+
+---
+// Ref: false
+#html.frame(alt: "A blue circle")[#circle(fill: blue, radius: 8pt)]
+#html.frame(format: "png")[$x^2$]
+
+---
+// Error: 20-25 expected "svg" or "png"
+#html.frame(format: "jpg")[A]
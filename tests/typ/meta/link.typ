@@ -75,3 +75,12 @@ Text <hey>
 Text <hey>
 // Error: 2-20 label occurs multiple times in the document
 #link(<hey>)[Nope.]
+
+---
+// Ref: false
+// Link to a named destination in another PDF file.
+#link((file: "manual.pdf", name: "installation"))[See the manual]
+
+---
+// Error: 6-26 missing key: "name"
+#link((file: "manual.pdf"))[Nope.]
@@ -0,0 +1,18 @@
+// Test color glyphs from the different color formats.
+// Ref: false
+
+---
+// Bitmap glyphs.
+#set text(font: "Noto Color Emoji")
+🐪 🌋 🏞 👍🏿
+
+---
+// SVG glyphs.
+#set text(font: "Twitter Color Emoji")
+🐪 🌋 🏞 👍🏿
+
+---
+// Color glyphs mixed with outline glyphs in the same run and scaled.
+#set text(font: ("Linux Libertine", "Twitter Color Emoji"), size: 16pt)
+Camel 🐪 and volcano 🌋.
+#text(font: ("Linux Libertine", "Noto Color Emoji"))[Camel 🐪 and volcano 🌋.]
@@ -0,0 +1,17 @@
+// Test SVG export.
+// Ref: false
+// Export: svg
+
+---
+#set page(width: 120pt, height: auto, margin: 10pt)
+#rect(width: 100%, height: 20pt, fill: red, stroke: 2pt + blue)
+#circle(radius: 10pt, fill: green)
+#line(length: 100%, stroke: (paint: black, thickness: 1pt, dash: "dashed"))
+Hello *SVG* export!
+
+---
+// Each page of a multi-page document is exported.
+#set page(width: 60pt, height: 40pt, margin: 5pt)
+First
+#pagebreak()
+Second
@@ -0,0 +1,40 @@
+// Test rendering of gradients.
+// Ref: false
+
+---
+// Linear gradients in different directions and color spaces.
+#set page(width: 120pt, height: auto)
+#set rect(width: 100%, height: 16pt)
+#rect(fill: gradient.linear(red, blue))
+#rect(fill: gradient.linear(red, blue, angle: 45deg))
+#rect(fill: gradient.linear(red, blue, space: "srgb"))
+#rect(fill: gradient.linear((red, 20%), (yellow, 50%), (blue, 80%)))
+
+---
+// Radial and conic gradients.
+#set page(width: 120pt, height: auto)
+#stack(
+  dir: ltr,
+  spacing: 8pt,
+  circle(radius: 24pt, fill: gradient.radial(white, teal)),
+  circle(radius: 24pt, fill: gradient.radial(white, teal, center: (30%, 30%))),
+  square(size: 48pt, fill: gradient.conic(red, yellow, green, blue, red)),
+)
+
+---
+// Gradients as strokes, text fills and table fills.
+#set page(width: 120pt, height: auto)
+#rect(width: 100%, stroke: gradient.linear(red, blue) + 3pt)[
+  #text(fill: gradient.linear(red, blue), size: 16pt)[*Gradient*]
+]
+#table(columns: 3, fill: gradient.linear(silver, white))[A][B][C]
+
+---
+// Stops are distributed evenly unless they have offsets.
+#test(gradient.linear(red, blue).stops(), ((red, 0%), (blue, 100%)))
+#test(
+  gradient.conic(red, green, blue).stops(),
+  ((red, 0%), (green, 50%), (blue, 100%)),
+)
+#test(gradient.linear(black, white, space: "srgb").sample(50%), rgb("#808080"))
+#test(gradient.linear(black, white).sample(-10%), black)
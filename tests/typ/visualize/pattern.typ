@@ -0,0 +1,35 @@
+// Test tiling patterns.
+// Ref: false
+
+---
+#let hatch = pattern(size: (5pt, 5pt), line(start: (0%, 100%), end: (100%, 0%)))
+#test(type(hatch), "pattern")
+#test(repr(hatch), "pattern(size: (5pt, 5pt), spacing: (0pt, 0pt))")
+#test(repr(pattern(spacing: (1pt, 2pt), square(size: 4pt))),
+  "pattern(size: (4pt, 4pt), spacing: (1pt, 2pt))")
+
+// Patterns can be used as fills and strokes.
+#rect(fill: hatch, stroke: hatch + 2pt)
+#table(columns: 2, fill: hatch)[A][B]
+
+---
+// The tile is laid out with the styles passed to the pattern.
+#set text(fill: red, 8pt)
+#style(styles => rect(
+  width: 100%,
+  height: 20pt,
+  fill: pattern(styles: styles, size: (10pt, 10pt))[x],
+))
+#rect(width: 100%, height: 20pt, fill: pattern(size: (10pt, 10pt))[x])
+
+---
+// Error: 8-12 pattern tile must have a positive size
+#pattern([])
+
+---
+// Error: 8-30 pattern tile must have a positive size
+#pattern(size: (0pt, 5pt), [])
+
+---
+// Error: 8-35 pattern spacing must not be negative
+#pattern(spacing: (-1pt, 0pt), [x])